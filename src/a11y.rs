@@ -0,0 +1,18 @@
+// Screen reader support.
+//
+// eframe already publishes the widget tree through AccessKit; these helpers
+// fill in what egui can't work out by itself: names for controls that show an
+// icon or only a hint instead of a label, and live regions whose changes are
+// read out without moving focus, e.g. the page number or the match count.
+
+use eframe::egui::{self, accesskit};
+
+// Name a control for screen readers when its visible text doesn't describe it
+pub fn set_name(response: &egui::Response, name: &str) {
+    response.ctx.accesskit_node_builder(response.id, |node| node.set_name(name));
+}
+
+// Have screen readers announce changes to this widget's text
+pub fn make_live(response: &egui::Response) {
+    response.ctx.accesskit_node_builder(response.id, |node| node.set_live(accesskit::Live::Polite));
+}
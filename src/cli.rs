@@ -0,0 +1,56 @@
+// Command line arguments.
+//
+// `csv_reader_app [FILE] [--row N] [--column NAME] [--filter COLUMN=VALUE]`
+// lets scripts and other tools open the viewer at a given place. Rows are
+// numbered as in the row-number column, where the header is row 1. The filter
+// is an exact match on one column, the same as "Filter to this value".
+//
+// File managers pass the file as the only argument when a CSV is opened with
+// the app; packaging/ has the desktop entry, registry file and Info.plist
+// additions that register it.
+
+pub const USAGE: &str = "Usage: csv_reader_app [FILE] [--row N] [--column NAME] [--filter COLUMN=VALUE]";
+
+#[derive(Default)]
+pub struct LaunchArgs {
+    pub help: bool,
+    pub file: Option<String>,
+    pub row: Option<usize>,
+    pub column: Option<String>,
+    pub filter: Option<(String, String)>, // Column name and value
+}
+
+impl LaunchArgs {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut launch = LaunchArgs::default();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+            match arg.as_str() {
+                "-h" | "--help" => launch.help = true,
+                // Process serial number that older macOS versions pass when
+                // the app is started from Finder
+                psn if psn.starts_with("-psn_") => {}
+                "--row" => {
+                    let row = value("--row")?;
+                    launch.row = Some(
+                        row.parse().ok().filter(|&row| row >= 2).ok_or_else(|| format!("--row must be 2 or more, not {}", row))?,
+                    );
+                }
+                "--column" => launch.column = Some(value("--column")?),
+                "--filter" => {
+                    let filter = value("--filter")?;
+                    let (column, value) =
+                        filter.split_once('=').ok_or_else(|| format!("--filter must be COLUMN=VALUE, not {}", filter))?;
+                    launch.filter = Some((column.to_string(), value.to_string()));
+                }
+                flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+                _ if launch.file.is_none() => launch.file = Some(arg),
+                _ => return Err(format!("Only one file can be opened, but {} was also given", arg)),
+            }
+        }
+        if launch.file.is_none() && (launch.row.is_some() || launch.column.is_some() || launch.filter.is_some()) {
+            return Err("--row, --column and --filter need a file to open".to_string());
+        }
+        Ok(launch)
+    }
+}
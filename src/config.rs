@@ -0,0 +1,55 @@
+// Settings kept between runs.
+//
+// Stored as `key=value` lines in `csv_reader_app/config` under the user's
+// config directory. Keys this version doesn't know about are kept as they are,
+// so settings written by a newer version survive a run of an older one.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Default)]
+pub struct Config {
+    values: BTreeMap<String, String>,
+}
+
+impl Config {
+    // Falls back to empty settings when there is no readable config file
+    pub fn load() -> Self {
+        let mut config = Config::default();
+        let Some(text) = config_path().and_then(|path| std::fs::read_to_string(path).ok()) else {
+            return config;
+        };
+        for line in text.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                config.values.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+        config
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = config_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text: String = self.values.iter().map(|(key, value)| format!("{}={}\n", key, value)).collect();
+        std::fs::write(path, text)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: &str, value: impl Into<String>) {
+        self.values.insert(key.to_string(), value.into());
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("csv_reader_app").join("config"))
+}
@@ -0,0 +1,198 @@
+// Money amounts like "$1,234.50", "1.234,50 €", "USD 99" or "(12.00)",
+// reduced to a plain number and the currency they were written in.
+//
+// A currency is a symbol or an ISO 4217 code before or after the number.
+// A minus sign before or after, or parentheses around the amount as in
+// accounting, make it negative. "$" alone stands for the code chosen in the
+// window, since several currencies use it, or for no code if none is chosen.
+
+use crate::decimals::{self, Convention};
+
+// Longest first, so "US$" is taken before "$"
+const SYMBOLS: &[(&str, &str)] = &[
+    ("US$", "USD"),
+    ("NZ$", "NZD"),
+    ("HK$", "HKD"),
+    ("R$", "BRL"),
+    ("C$", "CAD"),
+    ("A$", "AUD"),
+    ("zł", "PLN"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥", "JPY"),
+    ("₹", "INR"),
+    ("₱", "PHP"),
+    ("₩", "KRW"),
+    ("₽", "RUB"),
+    ("₺", "TRY"),
+    ("₫", "VND"),
+    ("₪", "ILS"),
+    ("฿", "THB"),
+];
+
+// ISO 4217 codes in use, sorted, so words like "CAT" aren't read as currencies
+const CODES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP",
+    "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF",
+    "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD",
+    "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP",
+    "LKR", "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR",
+    "MVR", "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB",
+    "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD",
+    "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SLL", "SOS", "SRD", "SSP", "STN", "SVC", "SYP",
+    "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD",
+    "UYU", "UZS", "VES", "VND", "VUV", "WST", "XAF", "XCD", "XCG", "XOF", "XPF", "YER", "ZAR",
+    "ZMW", "ZWG", "ZWL",
+];
+
+pub struct Amount {
+    pub value: f64,
+    pub code: Option<String>,
+}
+
+fn is_code(text: &str) -> bool {
+    CODES.binary_search(&text).is_ok()
+}
+
+// The currency at the start of `text`, and the rest
+fn leading_currency<'t>(text: &'t str, dollar: &str) -> Option<(String, &'t str)> {
+    if let Some((symbol, code)) = SYMBOLS.iter().find(|(symbol, _)| text.starts_with(symbol)) {
+        return Some((code.to_string(), &text[symbol.len()..]));
+    }
+    if let Some(rest) = text.strip_prefix('$') {
+        return Some((dollar.to_string(), rest));
+    }
+    let code = text.get(..3).filter(|code| is_code(code))?;
+    let rest = &text[3..];
+    (!rest.starts_with(|ch: char| ch.is_alphabetic())).then(|| (code.to_string(), rest))
+}
+
+// The currency at the end of `text`, and the rest
+fn trailing_currency<'t>(text: &'t str, dollar: &str) -> Option<(String, &'t str)> {
+    if let Some((symbol, code)) = SYMBOLS.iter().find(|(symbol, _)| text.ends_with(symbol)) {
+        return Some((code.to_string(), &text[..text.len() - symbol.len()]));
+    }
+    if let Some(rest) = text.strip_suffix('$') {
+        return Some((dollar.to_string(), rest));
+    }
+    let start = text.len().checked_sub(3).filter(|&start| text.is_char_boundary(start))?;
+    let (rest, code) = text.split_at(start);
+    (is_code(code) && !rest.ends_with(|ch: char| ch.is_alphabetic())).then(|| (code.to_string(), rest))
+}
+
+// The number of `cell` with its currency and sign taken off
+fn split(cell: &str, dollar: &str) -> Option<(bool, Option<String>, String)> {
+    let mut text = cell.trim();
+    let mut negative = false;
+    if let Some(inner) = text.strip_prefix('(').and_then(|text| text.strip_suffix(')')) {
+        negative = true;
+        text = inner.trim();
+    }
+    let mut code = None;
+    loop {
+        if let Some(rest) = text.strip_prefix('-').or_else(|| text.strip_suffix('-')) {
+            if negative {
+                return None;
+            }
+            negative = true;
+            text = rest.trim();
+        } else if let Some((found, rest)) = leading_currency(text, dollar).or_else(|| trailing_currency(text, dollar)) {
+            if code.is_some() {
+                return None;
+            }
+            code = Some(found);
+            text = rest.trim();
+        } else {
+            break;
+        }
+    }
+    if text.is_empty() {
+        return None;
+    }
+    Some((negative, code, text.to_string()))
+}
+
+// The convention most amounts in `cells` are written in; amounts like 1,234
+// that read either way don't count
+pub fn detect_convention<'c>(cells: impl Iterator<Item = &'c str>, dollar: &str) -> Convention {
+    let (mut point, mut comma) = (0, 0);
+    for cell in cells {
+        let Some((_, _, number)) = split(cell, dollar) else {
+            continue;
+        };
+        match (decimals::parse(&number, Convention::Point).is_some(), decimals::parse(&number, Convention::Comma).is_some()) {
+            (true, false) => point += 1,
+            (false, true) => comma += 1,
+            _ => {}
+        }
+    }
+    if comma > point { Convention::Comma } else { Convention::Point }
+}
+
+pub fn parse(cell: &str, convention: Convention, dollar: &str) -> Option<Amount> {
+    let (negative, code, number) = split(cell, dollar)?;
+    let value = decimals::parse(&number, convention)?;
+    Some(Amount {
+        value: if negative { -value } else { value },
+        code: code.filter(|code| !code.is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(cell: &str, convention: Convention, dollar: &str) -> Option<(f64, Option<String>)> {
+        parse(cell, convention, dollar).map(|amount| (amount.value, amount.code))
+    }
+
+    fn code(code: &str) -> Option<String> {
+        Some(code.to_string())
+    }
+
+    #[test]
+    fn symbols_and_codes() {
+        assert_eq!(amount("$1,234.50", Convention::Point, "USD"), Some((1234.5, code("USD"))));
+        assert_eq!(amount("1.234,50 €", Convention::Comma, ""), Some((1234.5, code("EUR"))));
+        assert_eq!(amount("US$ 5", Convention::Point, "CAD"), Some((5.0, code("USD"))));
+        assert_eq!(amount("R$10,00", Convention::Comma, ""), Some((10.0, code("BRL"))));
+        assert_eq!(amount("USD 99", Convention::Point, ""), Some((99.0, code("USD"))));
+        assert_eq!(amount("99 CHF", Convention::Point, ""), Some((99.0, code("CHF"))));
+        assert_eq!(amount("42", Convention::Point, "USD"), Some((42.0, None)));
+    }
+
+    #[test]
+    fn blank_dollar_code_is_no_code() {
+        assert_eq!(amount("$12", Convention::Point, ""), Some((12.0, None)));
+        assert_eq!(amount("12$", Convention::Point, ""), Some((12.0, None)));
+        assert_eq!(amount("$12", Convention::Point, "AUD"), Some((12.0, code("AUD"))));
+    }
+
+    #[test]
+    fn words_are_not_codes() {
+        assert!(parse("CAT 5", Convention::Point, "").is_none());
+        assert!(parse("5 CAT", Convention::Point, "").is_none());
+        assert!(parse("usd 5", Convention::Point, "").is_none());
+        assert!(parse("USDX 5", Convention::Point, "").is_none());
+        assert!(parse("€", Convention::Point, "").is_none());
+    }
+
+    #[test]
+    fn signs() {
+        assert_eq!(amount("(12.00)", Convention::Point, ""), Some((-12.0, None)));
+        assert_eq!(amount("-$3", Convention::Point, "USD"), Some((-3.0, code("USD"))));
+        assert_eq!(amount("$3-", Convention::Point, "USD"), Some((-3.0, code("USD"))));
+        assert_eq!(amount("£-7", Convention::Point, ""), Some((-7.0, code("GBP"))));
+        assert!(parse("(-3)", Convention::Point, "").is_none());
+        assert!(parse("--3", Convention::Point, "").is_none());
+        assert!(parse("$ €3", Convention::Point, "USD").is_none());
+    }
+
+    #[test]
+    fn convention() {
+        assert_eq!(detect_convention(["1.234,50 €", "€ 3,5", "1,234"].into_iter(), ""), Convention::Comma);
+        assert_eq!(detect_convention(["$1,234.50", "$3.5", "1,234"].into_iter(), ""), Convention::Point);
+    }
+}
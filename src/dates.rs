@@ -0,0 +1,328 @@
+// Sanity checks for date columns: dates in the future, dates before the
+// earliest plausible one, times with a mix of UTC offsets, and day/month
+// dates like 01/02/2023 that could be read either way.
+//
+// Dates are ISO (2023-02-01, optionally with a time and offset) or numbers
+// separated by slashes, dots or dashes with a four-digit year last. A column
+// is checked when at least four in five of its filled cells are dates. Any
+// cell with a first part above 12 settles a column as day first, and one
+// with a second part above 12 as month first; until then each cell whose
+// parts differ and are both 12 or less is ambiguous.
+
+use crate::i18n::tr;
+use crate::jobs::{Cancelled, Progress};
+use crate::table::Table;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Rows scanned between progress updates and cancellation checks
+const SCAN_CHUNK_ROWS: usize = 64 * 1024;
+
+pub type Ymd = (i32, u32, u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    Future,
+    BeforeEpoch,
+    MixedZone,
+    Ambiguous,
+}
+
+impl Check {
+    pub const ALL: [Check; 4] = [Check::Future, Check::BeforeEpoch, Check::MixedZone, Check::Ambiguous];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Check::Future => "In the future",
+            Check::BeforeEpoch => "Before the earliest date",
+            Check::MixedZone => "Different UTC offset",
+            Check::Ambiguous => "Day or month first?",
+        })
+    }
+}
+
+// The time zone of a date with a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Zone {
+    Local,       // No offset given
+    Offset(i32), // Minutes east of UTC
+}
+
+impl Zone {
+    pub fn label(self) -> String {
+        match self {
+            Zone::Local => tr("no offset").to_string(),
+            Zone::Offset(minutes) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                format!("{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Unknown,
+    DayFirst,
+    MonthFirst,
+    Mixed,
+}
+
+#[derive(Debug, PartialEq)]
+enum Date {
+    Iso(Ymd, Option<Zone>), // The zone if there is a time
+    Numeric(u32, u32, i32), // The first two parts, in either order, and the year
+}
+
+fn number(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn is_valid((year, month, day): Ymd) -> bool {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+// An ISO date, for the earliest date setting
+pub fn parse_iso(text: &str) -> Option<Ymd> {
+    match parse(text)? {
+        Date::Iso(ymd, _) => Some(ymd),
+        Date::Numeric(..) => None,
+    }
+}
+
+fn parse(cell: &str) -> Option<Date> {
+    let cell = cell.trim();
+    let bytes = cell.as_bytes();
+    if bytes.len() >= 10 && matches!(bytes[4], b'-' | b'/') && bytes[7] == bytes[4] && cell.is_char_boundary(10) {
+        let ymd = (number(&cell[..4])? as i32, number(&cell[5..7])?, number(&cell[8..10])?);
+        if !is_valid(ymd) {
+            return None;
+        }
+        let rest = &cell[10..];
+        if rest.is_empty() {
+            return Some(Date::Iso(ymd, None));
+        }
+        let time = rest.strip_prefix(['T', ' '])?;
+        return Some(Date::Iso(ymd, Some(parse_time(time)?)));
+    }
+    let mut parts = cell.split(['/', '.', '-']);
+    let (first, second, year) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || first.len() > 2 || second.len() > 2 || year.len() != 4 {
+        return None;
+    }
+    let (first, second, year) = (number(first)?, number(second)?, number(year)? as i32);
+    (is_valid((year, first, second)) || is_valid((year, second, first))).then_some(Date::Numeric(first, second, year))
+}
+
+// A time like 13:45, 13:45:30.250 or 13:45:30Z, with its zone
+fn parse_time(time: &str) -> Option<Zone> {
+    let split = time.find(['Z', 'z', '+', '-']).unwrap_or(time.len());
+    let (clock, zone) = (time[..split].trim_end(), &time[split..]);
+    let mut fields = clock.splitn(3, ':');
+    let hours = number(fields.next()?)?;
+    let minutes = number(fields.next()?)?;
+    if let Some(seconds) = fields.next() {
+        let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+        if number(whole)? > 60 || number(fraction).is_none() {
+            return None;
+        }
+    }
+    if hours > 24 || minutes > 59 {
+        return None;
+    }
+    let zone = match zone {
+        "" => Zone::Local,
+        "Z" | "z" => Zone::Offset(0),
+        _ => {
+            let sign = if zone.starts_with('-') { -1 } else { 1 };
+            let digits = zone[1..].replace(':', "");
+            if ![2, 4].contains(&digits.len()) || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            let hours = number(&digits[..2])? as i32;
+            let minutes = number(&digits[2..]).unwrap_or(0) as i32;
+            Zone::Offset(sign * (hours * 60 + minutes))
+        }
+    };
+    Some(zone)
+}
+
+// Today's date in UTC
+pub fn today() -> Ymd {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    // Days since 1970-01-01 to a civil date, from Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
+
+pub struct DateColumn {
+    pub col: usize,
+    pub dates: usize,
+    pub order: Order,
+    pub zone: Option<Zone>,        // The most common zone of dates with a time
+    pub counts: [usize; 4],        // By Check as usize
+    pub first: [Option<usize>; 4], // Data index of the first cell with each problem
+}
+
+pub struct DateReport {
+    pub columns: Vec<DateColumn>, // Date columns only
+}
+
+// Per column, gathered in the first pass
+#[derive(Default)]
+struct Tally {
+    filled: usize,
+    dates: usize,
+    day_first: bool,
+    month_first: bool,
+    zones: HashMap<Zone, usize>,
+}
+
+// Dates after `today` are in the future. The date of a cell that could be
+// read either way only counts as in the future or too early if both readings
+// are.
+pub fn scan(data: &Table, columns: usize, today: Ymd, epoch: Ymd, progress: &Progress) -> Result<DateReport, Cancelled> {
+    progress.set_total(data.len() * 2);
+    let mut tallies: Vec<Tally> = (0..columns).map(|_| Tally::default()).collect();
+    for start in (0..data.len()).step_by(SCAN_CHUNK_ROWS) {
+        progress.check()?;
+        let end = (start + SCAN_CHUNK_ROWS).min(data.len());
+        for idx in start..end {
+            for (tally, cell) in tallies.iter_mut().zip(data.row(idx).iter()) {
+                if cell.trim().is_empty() {
+                    continue;
+                }
+                tally.filled += 1;
+                let Some(date) = parse(cell) else {
+                    continue;
+                };
+                tally.dates += 1;
+                match date {
+                    Date::Iso(_, Some(zone)) => *tally.zones.entry(zone).or_insert(0) += 1,
+                    Date::Iso(_, None) => {}
+                    Date::Numeric(first, second, _) => {
+                        tally.day_first |= first > 12;
+                        tally.month_first |= second > 12;
+                    }
+                }
+            }
+        }
+        progress.set_done(end);
+    }
+    let mut report = DateReport { columns: Vec::new() };
+    for (col, tally) in tallies.iter().enumerate() {
+        if tally.dates == 0 || tally.dates * 5 < tally.filled * 4 {
+            continue;
+        }
+        let order = match (tally.day_first, tally.month_first) {
+            (false, false) => Order::Unknown,
+            (true, false) => Order::DayFirst,
+            (false, true) => Order::MonthFirst,
+            (true, true) => Order::Mixed,
+        };
+        let zone = tally.zones.iter().max_by_key(|&(zone, count)| (count, *zone == Zone::Local)).map(|(zone, _)| *zone);
+        report.columns.push(DateColumn {
+            col,
+            dates: tally.dates,
+            order,
+            zone,
+            counts: [0; 4],
+            first: [None; 4],
+        });
+    }
+    for start in (0..data.len()).step_by(SCAN_CHUNK_ROWS) {
+        progress.check()?;
+        let end = (start + SCAN_CHUNK_ROWS).min(data.len());
+        for idx in start..end {
+            let row = data.row(idx);
+            for column in &mut report.columns {
+                let Some(date) = parse(row.get(column.col).unwrap_or("")) else {
+                    continue;
+                };
+                let mut flag = |check: Check| {
+                    column.counts[check as usize] += 1;
+                    column.first[check as usize].get_or_insert(idx);
+                };
+                let readings: Vec<Ymd> = match date {
+                    Date::Iso(ymd, zone) => {
+                        if zone.is_some() && zone != column.zone {
+                            flag(Check::MixedZone);
+                        }
+                        vec![ymd]
+                    }
+                    Date::Numeric(first, second, year) => {
+                        let day_first = (year, second, first);
+                        let month_first = (year, first, second);
+                        let readings: Vec<Ymd> = match column.order {
+                            Order::DayFirst => vec![day_first],
+                            Order::MonthFirst => vec![month_first],
+                            Order::Unknown | Order::Mixed => vec![day_first, month_first],
+                        };
+                        let readings: Vec<Ymd> = readings.into_iter().filter(|&ymd| is_valid(ymd)).collect();
+                        if readings.len() == 2 && first != second {
+                            flag(Check::Ambiguous);
+                        }
+                        readings
+                    }
+                };
+                if !readings.is_empty() && readings.iter().all(|&ymd| ymd > today) {
+                    flag(Check::Future);
+                }
+                if !readings.is_empty() && readings.iter().all(|&ymd| ymd < epoch) {
+                    flag(Check::BeforeEpoch);
+                }
+            }
+        }
+        progress.set_done(data.len() + end);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(cell: &str) -> Option<Zone> {
+        match parse(cell)? {
+            Date::Iso(_, zone) => zone,
+            Date::Numeric(..) => None,
+        }
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(zone("2023-01-01T10:00"), Some(Zone::Local));
+        assert_eq!(zone("2023-01-01T10:00Z"), Some(Zone::Offset(0)));
+        assert_eq!(zone("2023-01-01T10:00:30.250z"), Some(Zone::Offset(0)));
+        assert_eq!(zone("2023-01-01T10:00+05"), Some(Zone::Offset(300)));
+        assert_eq!(zone("2023-01-01 10:00+0530"), Some(Zone::Offset(330)));
+        assert_eq!(zone("2023-01-01T10:00+05:30"), Some(Zone::Offset(330)));
+        assert_eq!(zone("2023-01-01T10:00-03:30"), Some(Zone::Offset(-210)));
+    }
+
+    #[test]
+    fn bad_offsets_are_not_dates() {
+        for cell in ["2023-01-01T10:00+1é1", "2023-01-01T10:00+é", "2023-01-01T10:00+5", "2023-01-01T10:00+05:3x", "2023-01-01T10:00+"] {
+            assert_eq!(parse(cell), None, "{}", cell);
+        }
+    }
+}
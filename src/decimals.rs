@@ -0,0 +1,213 @@
+// Decimal separators: whether a column writes numbers as 1,234.56 (decimal
+// point) or 1.234,56 (decimal comma), and converting between the two.
+//
+// A number is an optional sign, digits with optional thousands groups, and an
+// optional fraction. Groups are separated by the other convention's separator,
+// a space, a no-break space or an apostrophe; the first group has one to three
+// digits, not starting with 0, and the rest exactly three. A cell like 1,234
+// reads as a number either way and so doesn't settle a column's convention.
+
+use crate::i18n::tr;
+use crate::table::Table;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Convention {
+    Point,
+    Comma,
+}
+
+impl Convention {
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Convention::Point => "Decimal point (1,234.5)",
+            Convention::Comma => "Decimal comma (1.234,5)",
+        })
+    }
+
+    fn separator(self) -> char {
+        match self {
+            Convention::Point => '.',
+            Convention::Comma => ',',
+        }
+    }
+
+    fn group(self) -> char {
+        match self {
+            Convention::Point => ',',
+            Convention::Comma => '.',
+        }
+    }
+
+    // For the change log
+    pub fn description(self) -> &'static str {
+        match self {
+            Convention::Point => "decimal comma to point",
+            Convention::Comma => "decimal point to comma",
+        }
+    }
+}
+
+// Whether `cell` is a number written in `convention`
+fn fits(cell: &str, convention: Convention) -> bool {
+    let cell = cell.trim();
+    let body = cell.strip_prefix(['-', '+']).unwrap_or(cell);
+    let (whole, fraction) = match body.split_once(convention.separator()) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (body, None),
+    };
+    if let Some(fraction) = fraction
+        && (fraction.is_empty() || !fraction.bytes().all(|byte| byte.is_ascii_digit()))
+    {
+        return false;
+    }
+    if whole.is_empty() {
+        return fraction.is_some();
+    }
+    let groups: Vec<&str> = whole.split([convention.group(), ' ', '\u{a0}', '\u{202f}', '\'']).collect();
+    if groups.iter().any(|group| group.is_empty() || !group.bytes().all(|byte| byte.is_ascii_digit())) {
+        return false;
+    }
+    groups.len() == 1 || (groups[0].len() <= 3 && !groups[0].starts_with('0') && groups[1..].iter().all(|group| group.len() == 3))
+}
+
+// The value of `cell` if it is a number written in `convention`
+pub fn parse(cell: &str, convention: Convention) -> Option<f64> {
+    if !fits(cell, convention) {
+        return None;
+    }
+    let plain: String = cell
+        .trim()
+        .chars()
+        .filter(|&ch| ch.is_ascii_digit() || matches!(ch, '-' | '+') || ch == convention.separator())
+        .map(|ch| if ch == ',' { '.' } else { ch })
+        .collect();
+    plain.parse().ok()
+}
+
+// How each column of the table writes its numbers
+pub struct ColumnConventions {
+    pub col: usize,
+    pub point: usize,  // Cells that are numbers only with a decimal point
+    pub comma: usize,  // Cells that are numbers only with a decimal comma
+    pub either: usize, // Cells with a separator that are numbers both ways
+}
+
+impl ColumnConventions {
+    // None if the column has no numbers settling it, or both conventions
+    pub fn convention(&self) -> Option<Convention> {
+        match (self.point > 0, self.comma > 0) {
+            (true, false) => Some(Convention::Point),
+            (false, true) => Some(Convention::Comma),
+            _ => None,
+        }
+    }
+
+    pub fn is_mixed(&self) -> bool {
+        self.point > 0 && self.comma > 0
+    }
+}
+
+// The columns among `columns` with a number that has a separator
+pub fn detect(data: &Table, columns: &[usize]) -> Vec<ColumnConventions> {
+    let mut found: Vec<ColumnConventions> = columns
+        .iter()
+        .map(|&col| ColumnConventions {
+            col,
+            point: 0,
+            comma: 0,
+            either: 0,
+        })
+        .collect();
+    for row in data.rows() {
+        for column in &mut found {
+            let cell = row.get(column.col).unwrap_or("");
+            if !cell.contains(['.', ',']) {
+                continue;
+            }
+            match (fits(cell, Convention::Point), fits(cell, Convention::Comma)) {
+                (true, true) => column.either += 1,
+                (true, false) => column.point += 1,
+                (false, true) => column.comma += 1,
+                (false, false) => {}
+            }
+        }
+    }
+    found.retain(|column| column.point + column.comma + column.either > 0);
+    found
+}
+
+// `value` with `decimals` places, a point and no thousands separators
+pub fn format(value: f64, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value);
+    // Rounding can leave -0.00
+    match text.strip_prefix('-') {
+        Some(rest) if rest.bytes().all(|byte| byte == b'0' || byte == b'.') => rest.to_string(),
+        _ => text,
+    }
+}
+
+// `cell` written in `to`, if it is a number written in `from`
+pub fn convert(cell: &str, from: Convention, to: Convention) -> Option<String> {
+    if from == to || !cell.contains(['.', ',']) || !fits(cell, from) {
+        return None;
+    }
+    Some(
+        cell.chars()
+            .map(|ch| match ch {
+                '.' => ',',
+                ',' => '.',
+                ch => ch,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grouping() {
+        for cell in ["1,234", "12,345.6", "123,456,789.01", "-1,234", "+9,999.5", "1 234", "1\u{a0}234", "1'234'567"] {
+            assert!(fits(cell, Convention::Point), "{}", cell);
+        }
+        for cell in ["1,2345", "1,23", "1234,567", "0,123", "01,234", "1,,234", ",234", "1,234,", "1 234,56 7"] {
+            assert!(!fits(cell, Convention::Point), "{}", cell);
+        }
+        assert!(fits("1.234.567,89", Convention::Comma));
+        assert!(fits("1'234,5", Convention::Comma));
+        assert!(fits("1\u{a0}234,5", Convention::Comma));
+        assert!(!fits("1.23,4", Convention::Comma));
+    }
+
+    #[test]
+    fn fractions() {
+        assert!(fits(".5", Convention::Point));
+        assert!(fits("0123.5", Convention::Point)); // Leading zeros are fine without groups
+        assert!(!fits("1.", Convention::Point));
+        assert!(!fits("1.2.3", Convention::Point));
+        assert!(!fits("1.5e3", Convention::Point));
+        assert!(!fits("-", Convention::Point));
+        assert!(!fits("", Convention::Point));
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(parse("1,234.5", Convention::Point), Some(1234.5));
+        assert_eq!(parse("1.234,5", Convention::Comma), Some(1234.5));
+        assert_eq!(parse("-1\u{a0}234,5", Convention::Comma), Some(-1234.5));
+        assert_eq!(parse("1'234'567", Convention::Point), Some(1234567.0));
+        assert_eq!(parse("1,2345", Convention::Point), None);
+    }
+
+    #[test]
+    fn conversion() {
+        assert_eq!(convert("1.234,5", Convention::Comma, Convention::Point).as_deref(), Some("1,234.5"));
+        assert_eq!(convert("1 234,5", Convention::Comma, Convention::Point).as_deref(), Some("1 234.5"));
+        assert_eq!(convert("1234", Convention::Comma, Convention::Point), None);
+        assert_eq!(convert("1,2345", Convention::Point, Convention::Comma), None);
+        assert_eq!(format(-0.001, 2), "0.00");
+        assert_eq!(format(2.5, 0), "2");
+        assert_eq!(format(1234.5678, 2), "1234.57");
+    }
+}
@@ -0,0 +1,166 @@
+// Text encodings a CSV can be saved in, for tools that don't read plain
+// UTF-8. Saved in the config as `export.csv_encoding`.
+//
+// The csv writer always produces UTF-8; EncodingWriter re-encodes it on the
+// way to the file. A character that doesn't exist in the target encoding
+// fails the save rather than being silently replaced.
+
+use crate::i18n::tr;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Windows1252,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] = [Encoding::Utf8, Encoding::Utf8Bom, Encoding::Utf16Le, Encoding::Windows1252];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16 LE",
+            Encoding::Windows1252 => "Windows-1252 (Western European)",
+        })
+    }
+
+    pub fn config_value(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf8Bom => "utf-8-bom",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8Bom => b"\xEF\xBB\xBF",
+            Encoding::Utf16Le => b"\xFF\xFE",
+            Encoding::Utf8 | Encoding::Windows1252 => b"",
+        }
+    }
+}
+
+pub struct EncodingWriter<W: Write> {
+    inner: W,
+    encoding: Encoding,
+    pending: Vec<u8>, // The start of a character split across two writes
+    encoded: Vec<u8>,
+}
+
+impl<W: Write> EncodingWriter<W> {
+    pub fn new(mut inner: W, encoding: Encoding) -> io::Result<Self> {
+        inner.write_all(encoding.bom())?;
+        Ok(EncodingWriter {
+            inner,
+            encoding,
+            pending: Vec::new(),
+            encoded: Vec::new(),
+        })
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "text ends partway through a character"));
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn encode(&mut self, text: &str) -> io::Result<()> {
+        self.encoded.clear();
+        match self.encoding {
+            Encoding::Utf8 | Encoding::Utf8Bom => self.encoded.extend_from_slice(text.as_bytes()),
+            Encoding::Utf16Le => {
+                for unit in text.encode_utf16() {
+                    self.encoded.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            Encoding::Windows1252 => {
+                for ch in text.chars() {
+                    let byte = windows_1252_byte(ch).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("'{}' can't be written in {}", ch, Encoding::Windows1252.label()),
+                        )
+                    })?;
+                    self.encoded.push(byte);
+                }
+            }
+        }
+        self.inner.write_all(&self.encoded)
+    }
+}
+
+impl<W: Write> Write for EncodingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoding == Encoding::Utf8 {
+            return self.inner.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        // Only an incomplete character at the end is expected
+        let valid_len = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+        let pending = std::mem::take(&mut self.pending);
+        let (valid, rest) = pending.split_at(valid_len);
+        let text = std::str::from_utf8(valid).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.encode(text)?;
+        self.pending = rest.to_vec();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// The printable characters Windows puts in 0x80-0x9F
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\0', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\0', 'Ž', '\0',
+    '\0', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\0', 'ž', 'Ÿ',
+];
+
+// Latin-1 plus WINDOWS_1252_HIGH
+pub fn windows_1252_byte(ch: char) -> Option<u8> {
+    match u32::from(ch) {
+        code @ (0..=0x7F | 0xA0..=0xFF) => Some(code as u8),
+        _ => WINDOWS_1252_HIGH.iter().position(|&high| high == ch).map(|pos| 0x80 + pos as u8),
+    }
+}
+
+// The UTF-8 bytes of a file written by EncodingWriter, for reading one back.
+// UTF-8 is left as it is, since cells may have been written with their
+// original invalid bytes.
+pub fn decode(bytes: Vec<u8>, encoding: Encoding) -> Result<Vec<u8>, String> {
+    let body = bytes.strip_prefix(encoding.bom()).ok_or_else(|| tr("the byte order mark is missing").to_string())?;
+    match encoding {
+        Encoding::Utf8 => Ok(bytes),
+        Encoding::Utf8Bom => Ok(body.to_vec()),
+        Encoding::Utf16Le => {
+            if body.len() % 2 != 0 {
+                return Err(tr("the file ends partway through a character").to_string());
+            }
+            let units: Vec<u16> = body.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16(&units).map(String::into_bytes).map_err(|err| err.to_string())
+        }
+        Encoding::Windows1252 => {
+            let text: String = body
+                .iter()
+                .map(|&byte| match byte {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+                    _ => char::from(byte),
+                })
+                .collect();
+            Ok(text.into_bytes())
+        }
+    }
+}
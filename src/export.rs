@@ -0,0 +1,959 @@
+// Formats the table can be exported and copied as.
+//
+// CSV files are saved by write_csv in main.rs; CSV here is for the clipboard.
+// JSON writes an array of objects keyed by header name; NDJSON writes one
+// object per line. Cells are strings unless number coercion is on, in which
+// case cells that are valid JSON numbers are written bare. Markdown writes a
+// pipe table that GitHub issues and wikis render. HTML writes a standalone
+// page with its own styling, so it looks the same attached to an email.
+// LaTeX writes a booktabs `tabular`, which needs `\usepackage{booktabs}`. SQL
+// writes a CREATE TABLE with column types inferred from the values, then
+// INSERT statements of SQL_BATCH_ROWS rows each. PDF is written by pdf.rs.
+// XML writes one record element per row inside a root element, with each
+// column as a child element or, if chosen, as an attribute of the record.
+// A row template is any text file; it is written out once per row, with each
+// `{{Column name}}` replaced by that row's cell.
+//
+// The CSV, JSON, SQL and XML options are saved in the config under
+// `export.<name>`.
+
+use crate::config::Config;
+use crate::encoding::Encoding;
+use crate::i18n::tr;
+use crate::jobs::Progress;
+use crate::table::{Row, Table};
+use crate::{SAVE_BUFFER_BYTES, SAVE_CHUNK_ROWS, Selection};
+use rfd::FileDialog;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Clone, PartialEq)]
+pub enum ExportFormat {
+    Csv(CsvOptions),
+    Json(JsonOptions),
+    Markdown,
+    Html,
+    Latex,
+    Sql(SqlOptions),
+    Xml(XmlOptions),
+    Pdf { landscape: bool },
+    Template(RowTemplate),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RowTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Cell(usize),
+}
+
+impl RowTemplate {
+    // Names must match a column exactly, apart from spaces inside the braces
+    pub fn parse(text: &str, header: &[String]) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..].find("}}").ok_or("template has a {{ without a matching }}")? + start;
+            let name = rest[start + 2..end].trim();
+            let col = header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| format!("template uses {{{{{}}}}}, but there is no column named {}", name, name))?;
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            parts.push(TemplatePart::Cell(col));
+            rest = &rest[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+        Ok(RowTemplate { parts })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quote_all: bool, // Quote every field, not only those that need it
+    pub crlf: bool,      // Windows line endings
+    pub header: bool,    // Write the header row
+    pub encoding: Encoding,
+    pub keep_invalid_bytes: bool, // Write cells that weren't UTF-8 back as they were read
+    pub verify: bool,             // Read a saved file back and compare it with what was written
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            quote_all: false,
+            crlf: false,
+            header: true,
+            encoding: Encoding::Utf8,
+            keep_invalid_bytes: false,
+            verify: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+    pub fn delimiter_label(delimiter: u8) -> &'static str {
+        tr(match delimiter {
+            b',' => "Comma",
+            b';' => "Semicolon",
+            b'\t' => "Tab",
+            _ => "Pipe",
+        })
+    }
+
+    fn from_config(config: &Config) -> Self {
+        let delimiter = match config.get("export.csv_delimiter") {
+            Some("tab") => b'\t',
+            Some(value) => Self::DELIMITERS.into_iter().find(|&d| value.as_bytes() == [d]).unwrap_or(b','),
+            None => b',',
+        };
+        CsvOptions {
+            delimiter,
+            quote_all: config.get("export.csv_quote") == Some("always"),
+            crlf: config.get("export.csv_line_ending") == Some("crlf"),
+            header: config.get("export.csv_header") != Some("false"),
+            encoding: config
+                .get("export.csv_encoding")
+                .and_then(|value| Encoding::ALL.into_iter().find(|encoding| encoding.config_value() == value))
+                .unwrap_or_default(),
+            keep_invalid_bytes: config.get("export.csv_keep_invalid_bytes") == Some("true"),
+            verify: config.get("export.csv_verify") == Some("true"),
+        }
+    }
+
+    fn save_to(&self, config: &mut Config) {
+        let delimiter = match self.delimiter {
+            b'\t' => "tab".to_string(),
+            delimiter => char::from(delimiter).to_string(),
+        };
+        config.set("export.csv_delimiter", delimiter);
+        config.set("export.csv_quote", if self.quote_all { "always" } else { "necessary" });
+        config.set("export.csv_line_ending", if self.crlf { "crlf" } else { "lf" });
+        config.set("export.csv_header", self.header.to_string());
+        config.set("export.csv_encoding", self.encoding.config_value());
+        config.set("export.csv_keep_invalid_bytes", self.keep_invalid_bytes.to_string());
+        config.set("export.csv_verify", self.verify.to_string());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct JsonOptions {
+    pub lines: bool,          // NDJSON rather than one array
+    pub coerce_numbers: bool, // Write numeric-looking cells as numbers
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SqlOptions {
+    pub dialect: SqlDialect,
+    pub table: String,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct XmlOptions {
+    pub root: String,
+    pub record: String,
+    pub attributes: Vec<String>, // Columns written as attributes of the record
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqlDialect {
+    #[default]
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl SqlDialect {
+    pub const ALL: [SqlDialect; 3] = [SqlDialect::Postgres, SqlDialect::MySql, SqlDialect::Sqlite];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SqlDialect::Postgres => "PostgreSQL",
+            SqlDialect::MySql => "MySQL",
+            SqlDialect::Sqlite => "SQLite",
+        }
+    }
+
+    fn config_value(self) -> &'static str {
+        match self {
+            SqlDialect::Postgres => "postgres",
+            SqlDialect::MySql => "mysql",
+            SqlDialect::Sqlite => "sqlite",
+        }
+    }
+
+    fn type_name(self, sql_type: SqlType) -> &'static str {
+        match (self, sql_type) {
+            (SqlDialect::Sqlite, SqlType::Integer) => "INTEGER",
+            (_, SqlType::Integer) => "BIGINT",
+            (SqlDialect::Postgres, SqlType::Real) => "DOUBLE PRECISION",
+            (SqlDialect::MySql, SqlType::Real) => "DOUBLE",
+            (SqlDialect::Sqlite, SqlType::Real) => "REAL",
+            (_, SqlType::Text) => "TEXT",
+        }
+    }
+
+    fn quote_identifier(self, name: &str) -> String {
+        match self {
+            SqlDialect::MySql => format!("`{}`", name.replace('`', "``")),
+            SqlDialect::Postgres | SqlDialect::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    // MySQL also treats backslashes in strings as escapes by default
+    fn push_string(self, out: &mut String, text: &str) {
+        out.push('\'');
+        for ch in text.chars() {
+            match ch {
+                '\'' => out.push_str("''"),
+                '\\' if self == SqlDialect::MySql => out.push_str("\\\\"),
+                ch => out.push(ch),
+            }
+        }
+        out.push('\'');
+    }
+}
+
+const DEFAULT_SPLIT_TEMPLATE: &str = "{column}_{value}.csv";
+const DEFAULT_CHUNK_ROWS: usize = 10_000;
+const DEFAULT_XML_ROOT: &str = "rows";
+const DEFAULT_XML_RECORD: &str = "row";
+
+// Options that apply to every export, as chosen in the Export menu
+pub struct ExportSettings {
+    pub csv: CsvOptions,
+    pub coerce_numbers: bool,
+    pub sql_dialect: SqlDialect,
+    pub sql_table: String, // Empty to name the table after the file
+    pub pdf_landscape: bool,
+    pub split_template: String, // File names for "Export split by column"
+    pub chunk_rows: usize,      // Rows per file for "Export in chunks"
+    pub template_path: String,  // Last row template used
+    pub xml_root: String,
+    pub xml_record: String,
+    pub xml_attributes: Vec<String>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            csv: CsvOptions::default(),
+            coerce_numbers: false,
+            sql_dialect: SqlDialect::default(),
+            sql_table: String::new(),
+            pdf_landscape: false,
+            split_template: DEFAULT_SPLIT_TEMPLATE.to_string(),
+            chunk_rows: DEFAULT_CHUNK_ROWS,
+            template_path: String::new(),
+            xml_root: DEFAULT_XML_ROOT.to_string(),
+            xml_record: DEFAULT_XML_RECORD.to_string(),
+            xml_attributes: Vec::new(),
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn from_config(config: &Config) -> Self {
+        ExportSettings {
+            csv: CsvOptions::from_config(config),
+            coerce_numbers: config.get("export.coerce_numbers") == Some("true"),
+            sql_dialect: config
+                .get("export.sql_dialect")
+                .and_then(|value| SqlDialect::ALL.into_iter().find(|dialect| dialect.config_value() == value))
+                .unwrap_or_default(),
+            sql_table: config.get("export.sql_table").unwrap_or("").to_string(),
+            pdf_landscape: config.get("export.pdf_landscape") == Some("true"),
+            split_template: config.get("export.split_template").unwrap_or(DEFAULT_SPLIT_TEMPLATE).to_string(),
+            chunk_rows: config
+                .get("export.chunk_rows")
+                .and_then(|rows| rows.parse().ok())
+                .filter(|&rows| rows > 0)
+                .unwrap_or(DEFAULT_CHUNK_ROWS),
+            template_path: config.get("export.template_path").unwrap_or("").to_string(),
+            xml_root: config.get("export.xml_root").unwrap_or(DEFAULT_XML_ROOT).to_string(),
+            xml_record: config.get("export.xml_record").unwrap_or(DEFAULT_XML_RECORD).to_string(),
+            // Tab-separated, since column names often contain commas
+            xml_attributes: config
+                .get("export.xml_attributes")
+                .filter(|names| !names.is_empty())
+                .map(|names| names.split('\t').map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn save_to(&self, config: &mut Config) {
+        self.csv.save_to(config);
+        config.set("export.coerce_numbers", self.coerce_numbers.to_string());
+        config.set("export.sql_dialect", self.sql_dialect.config_value());
+        config.set("export.sql_table", self.sql_table.clone());
+        config.set("export.pdf_landscape", self.pdf_landscape.to_string());
+        config.set("export.split_template", self.split_template.clone());
+        config.set("export.chunk_rows", self.chunk_rows.to_string());
+        config.set("export.template_path", self.template_path.clone());
+        config.set("export.xml_root", self.xml_root.clone());
+        config.set("export.xml_record", self.xml_record.clone());
+        config.set("export.xml_attributes", self.xml_attributes.join("\t"));
+    }
+}
+
+impl ExportFormat {
+    pub fn add_filters(dialog: FileDialog) -> FileDialog {
+        dialog
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .add_filter("NDJSON", &["ndjson", "jsonl"])
+            .add_filter("Markdown", &["md", "markdown"])
+            .add_filter("HTML", &["html", "htm"])
+            .add_filter("LaTeX", &["tex"])
+            .add_filter("SQL", &["sql"])
+            .add_filter("XML", &["xml"])
+            .add_filter("PDF", &["pdf"])
+    }
+
+    pub fn json(settings: &ExportSettings, lines: bool) -> Self {
+        ExportFormat::Json(JsonOptions {
+            lines,
+            coerce_numbers: settings.coerce_numbers,
+        })
+    }
+
+    // Anything unrecognised is written as CSV
+    pub fn from_path(path: &str, settings: &ExportSettings) -> Self {
+        let path = Path::new(path);
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => ExportFormat::json(settings, false),
+            "ndjson" | "jsonl" => ExportFormat::json(settings, true),
+            "md" | "markdown" => ExportFormat::Markdown,
+            "html" | "htm" => ExportFormat::Html,
+            "tex" => ExportFormat::Latex,
+            "sql" => {
+                let table = match settings.sql_table.trim() {
+                    "" => path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("data"),
+                    table => table,
+                };
+                ExportFormat::Sql(SqlOptions {
+                    dialect: settings.sql_dialect,
+                    table: table.to_string(),
+                })
+            }
+            "xml" => ExportFormat::Xml(XmlOptions {
+                root: settings.xml_root.clone(),
+                record: settings.xml_record.clone(),
+                attributes: settings.xml_attributes.clone(),
+            }),
+            "pdf" => ExportFormat::Pdf {
+                landscape: settings.pdf_landscape,
+            },
+            _ => ExportFormat::Csv(settings.csv),
+        }
+    }
+}
+
+// Same chunking as write_csv: flush, publish progress and check cancellation
+// every SAVE_CHUNK_ROWS rows
+pub fn write_text(
+    path: &str,
+    format: ExportFormat,
+    header: &[String],
+    data: &Table,
+    selection: &Selection,
+    progress: &Progress,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let row_count = selection.row_count(data);
+    progress.set_total(row_count);
+    let mut out = BufWriter::with_capacity(SAVE_BUFFER_BYTES, File::create(path)?);
+    let writer = TextWriter::new(format, header, data, selection);
+    let mut text = String::new();
+    writer.push_start(&mut text);
+    for (idx, row) in selection.rows(data).enumerate() {
+        writer.push_row(&mut text, idx, &row);
+        out.write_all(text.as_bytes())?;
+        text.clear();
+        if (idx + 1) % SAVE_CHUNK_ROWS == 0 {
+            out.flush()?;
+            progress.set_done(idx + 1);
+            if progress.is_cancelled() {
+                return Ok(false);
+            }
+        }
+    }
+    writer.push_end(&mut text, row_count);
+    out.write_all(text.as_bytes())?;
+    let file = out.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
+    progress.set_done(row_count);
+    Ok(true)
+}
+
+// The whole selection as one string, for the clipboard
+pub fn render(format: ExportFormat, header: &[String], data: &Table, selection: &Selection) -> String {
+    let writer = TextWriter::new(format, header, data, selection);
+    let mut text = String::new();
+    writer.push_start(&mut text);
+    for (idx, row) in selection.rows(data).enumerate() {
+        writer.push_row(&mut text, idx, &row);
+    }
+    writer.push_end(&mut text, selection.row_count(data));
+    text
+}
+
+// Formats offered when copying a single cell, row or column from a menu
+#[derive(Clone, Copy, PartialEq)]
+pub enum CopyFormat {
+    Text,
+    Csv,
+    Json,
+    Markdown,
+    Quoted,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 5] = [
+        CopyFormat::Text,
+        CopyFormat::Csv,
+        CopyFormat::Json,
+        CopyFormat::Markdown,
+        CopyFormat::Quoted,
+    ];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            CopyFormat::Text => "Plain text",
+            CopyFormat::Csv => "CSV",
+            CopyFormat::Json => "JSON",
+            CopyFormat::Markdown => "Markdown table",
+            CopyFormat::Quoted => "Quoted list ('a', 'b')",
+        })
+    }
+}
+
+// One value. JSON gives an object keyed by the column name, like a row would.
+pub fn copy_cell(format: CopyFormat, name: &str, value: &str) -> String {
+    match format {
+        CopyFormat::Text => value.to_string(),
+        CopyFormat::Csv => {
+            let mut out = String::new();
+            push_csv_row(&mut out, &CsvOptions::default(), [value].into_iter());
+            out.truncate(out.trim_end_matches('\n').len());
+            out
+        }
+        CopyFormat::Json | CopyFormat::Markdown => copy_row(format, &[name], &[value]),
+        CopyFormat::Quoted => copy_quoted([value].into_iter()),
+    }
+}
+
+// Values side by side, each under its own column name
+pub fn copy_row(format: CopyFormat, header: &[&str], cells: &[&str]) -> String {
+    let mut out = String::new();
+    match format {
+        // Tab-separated so it pastes into spreadsheet cells
+        CopyFormat::Text => out = cells.join("\t"),
+        CopyFormat::Csv => push_csv_row(&mut out, &CsvOptions::default(), cells.iter().copied()),
+        CopyFormat::Json => {
+            out.push('{');
+            for (i, (name, cell)) in header.iter().zip(cells).enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_json_string(&mut out, name);
+                out.push(':');
+                push_json_string(&mut out, cell);
+            }
+            out.push('}');
+        }
+        CopyFormat::Markdown => {
+            push_markdown_row(&mut out, header.iter().copied());
+            push_markdown_separator(&mut out, header.len());
+            push_markdown_row(&mut out, cells.iter().copied());
+        }
+        CopyFormat::Quoted => out = copy_quoted(cells.iter().copied()),
+    }
+    out
+}
+
+// Values one under the other, all in the column `name`
+pub fn copy_column<'c>(format: CopyFormat, name: &'c str, values: impl Iterator<Item = &'c str>) -> String {
+    let mut out = String::new();
+    match format {
+        CopyFormat::Text => out = values.collect::<Vec<_>>().join("\n"),
+        CopyFormat::Csv => {
+            for value in std::iter::once(name).chain(values) {
+                push_csv_row(&mut out, &CsvOptions::default(), [value].into_iter());
+            }
+        }
+        CopyFormat::Json => {
+            out.push('[');
+            for (i, value) in values.enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_json_string(&mut out, value);
+            }
+            out.push(']');
+        }
+        CopyFormat::Markdown => {
+            push_markdown_row(&mut out, [name].into_iter());
+            push_markdown_separator(&mut out, 1);
+            for value in values {
+                push_markdown_row(&mut out, [value].into_iter());
+            }
+        }
+        CopyFormat::Quoted => out = copy_quoted(values),
+    }
+    out
+}
+
+// SQL-style literals, ready for an `IN (...)` list
+fn copy_quoted<'c>(values: impl Iterator<Item = &'c str>) -> String {
+    let mut out = String::new();
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        SqlDialect::Postgres.push_string(&mut out, value);
+    }
+    out
+}
+
+// Rows per INSERT statement, well under the statement size limits of all
+// three databases for typical rows
+const SQL_BATCH_ROWS: usize = 500;
+
+// Column types for SQL, from narrowest to widest. Empty cells become NULL and
+// fit any type.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum SqlType {
+    Integer,
+    Real,
+    Text,
+}
+
+impl SqlType {
+    fn widen(self, cell: &str) -> SqlType {
+        if cell.is_empty() || self == SqlType::Text {
+            return self;
+        }
+        let cell_type = if !is_json_number(cell) {
+            SqlType::Text
+        } else if cell.parse::<i64>().is_ok() {
+            SqlType::Integer
+        } else if cell.parse::<f64>().is_ok_and(f64::is_finite) {
+            SqlType::Real
+        } else {
+            SqlType::Text
+        };
+        if cell_type > self { cell_type } else { self }
+    }
+}
+
+struct TextWriter<'a> {
+    format: ExportFormat,
+    header: &'a [String],
+    columns: Vec<usize>,
+    keys: Vec<String>, // Escaped JSON keys, quoted SQL column names or XML names
+    types: Vec<SqlType>,
+    attributes: Vec<bool>, // XML columns written as attributes
+}
+
+impl<'a> TextWriter<'a> {
+    fn new(format: ExportFormat, header: &'a [String], data: &Table, selection: &Selection) -> Self {
+        let columns: Vec<usize> = match &selection.columns {
+            Some(columns) => columns.clone(),
+            None => (0..header.len()).collect(),
+        };
+        let keys = match &format {
+            ExportFormat::Json(_) => columns.iter().map(|&col| json_string(&header[col])).collect(),
+            ExportFormat::Sql(options) => columns.iter().map(|&col| options.dialect.quote_identifier(&header[col])).collect(),
+            ExportFormat::Xml(_) => columns.iter().map(|&col| xml_name(&header[col])).collect(),
+            _ => Vec::new(),
+        };
+        let attributes = match &format {
+            ExportFormat::Xml(options) => columns.iter().map(|&col| options.attributes.contains(&header[col])).collect(),
+            _ => Vec::new(),
+        };
+        // Types need every value, so SQL exports read the rows twice
+        let types = match &format {
+            ExportFormat::Sql(_) => {
+                let mut types = vec![SqlType::Integer; columns.len()];
+                for row in selection.rows(data) {
+                    for (sql_type, &col) in types.iter_mut().zip(&columns) {
+                        *sql_type = sql_type.widen(row.get(col).unwrap_or(""));
+                    }
+                }
+                types
+            }
+            _ => Vec::new(),
+        };
+        TextWriter {
+            format,
+            header,
+            columns,
+            keys,
+            types,
+            attributes,
+        }
+    }
+
+    fn cells<'r>(&'r self, row: &'r Row<'_>) -> impl Iterator<Item = &'r str> + 'r {
+        self.columns.iter().map(move |&col| row.get(col).unwrap_or(""))
+    }
+
+    fn push_start(&self, out: &mut String) {
+        match &self.format {
+            ExportFormat::Csv(options) => {
+                if options.header {
+                    push_csv_row(out, options, self.columns.iter().map(|&col| self.header[col].as_str()));
+                }
+            }
+            ExportFormat::Json(options) => {
+                if !options.lines {
+                    out.push('[');
+                }
+            }
+            ExportFormat::Markdown => {
+                push_markdown_row(out, self.columns.iter().map(|&col| self.header[col].as_str()));
+                push_markdown_separator(out, self.columns.len());
+            }
+            ExportFormat::Html => {
+                out.push_str(HTML_START);
+                push_html_row(out, "th", self.columns.iter().map(|&col| self.header[col].as_str()));
+                out.push_str("</thead>\n<tbody>\n");
+            }
+            ExportFormat::Latex => {
+                out.push_str(&format!("\\begin{{tabular}}{{{}}}\n\\toprule\n", "l".repeat(self.columns.len())));
+                push_latex_row(out, self.columns.iter().map(|&col| self.header[col].as_str()));
+                out.push_str("\\midrule\n");
+            }
+            ExportFormat::Pdf { .. } | ExportFormat::Template(_) => {}
+            ExportFormat::Xml(options) => {
+                out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+                out.push_str(&format!("<{}>\n", xml_name(&options.root)));
+            }
+            ExportFormat::Sql(options) => {
+                out.push_str(&format!("CREATE TABLE {} (\n", options.dialect.quote_identifier(&options.table)));
+                for (i, (key, &sql_type)) in self.keys.iter().zip(&self.types).enumerate() {
+                    let separator = if i + 1 < self.keys.len() { "," } else { "" };
+                    out.push_str(&format!("  {} {}{}\n", key, options.dialect.type_name(sql_type), separator));
+                }
+                out.push_str(");\n");
+            }
+        }
+    }
+
+    fn push_row(&self, out: &mut String, idx: usize, row: &Row<'_>) {
+        match &self.format {
+            ExportFormat::Csv(options) => push_csv_row(out, options, self.cells(row)),
+            ExportFormat::Json(options) => {
+                if !options.lines {
+                    out.push_str(if idx > 0 { ",\n  " } else { "\n  " });
+                }
+                out.push('{');
+                for (i, (cell, key)) in self.cells(row).zip(&self.keys).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(key);
+                    out.push(':');
+                    if options.coerce_numbers && is_json_number(cell) {
+                        out.push_str(cell);
+                    } else {
+                        push_json_string(out, cell);
+                    }
+                }
+                out.push('}');
+                if options.lines {
+                    out.push('\n');
+                }
+            }
+            ExportFormat::Markdown => push_markdown_row(out, self.cells(row)),
+            ExportFormat::Html => push_html_row(out, "td", self.cells(row)),
+            ExportFormat::Latex => push_latex_row(out, self.cells(row)),
+            ExportFormat::Pdf { .. } => {}
+            ExportFormat::Xml(options) => {
+                let record = xml_name(&options.record);
+                out.push_str(&format!("  <{}", record));
+                let fields = || self.cells(row).zip(&self.keys).zip(&self.attributes);
+                for ((cell, key), _) in fields().filter(|(_, attribute)| **attribute) {
+                    out.push_str(&format!(" {}=\"", key));
+                    push_xml_text(out, cell);
+                    out.push('"');
+                }
+                out.push_str(">\n");
+                for ((cell, key), _) in fields().filter(|(_, attribute)| !**attribute) {
+                    if cell.is_empty() {
+                        out.push_str(&format!("    <{}/>\n", key));
+                    } else {
+                        out.push_str(&format!("    <{}>", key));
+                        push_xml_text(out, cell);
+                        out.push_str(&format!("</{}>\n", key));
+                    }
+                }
+                out.push_str(&format!("  </{}>\n", record));
+            }
+            ExportFormat::Template(template) => {
+                for part in &template.parts {
+                    match part {
+                        TemplatePart::Text(text) => out.push_str(text),
+                        TemplatePart::Cell(col) => out.push_str(row.get(*col).unwrap_or("")),
+                    }
+                }
+            }
+            ExportFormat::Sql(options) => {
+                if idx.is_multiple_of(SQL_BATCH_ROWS) {
+                    if idx > 0 {
+                        out.push_str(";\n");
+                    }
+                    let table = options.dialect.quote_identifier(&options.table);
+                    out.push_str(&format!("INSERT INTO {} ({}) VALUES\n(", table, self.keys.join(", ")));
+                } else {
+                    out.push_str(",\n(");
+                }
+                for (i, (cell, &sql_type)) in self.cells(row).zip(&self.types).enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    match sql_type {
+                        _ if cell.is_empty() => out.push_str("NULL"),
+                        SqlType::Integer | SqlType::Real => out.push_str(cell),
+                        SqlType::Text => options.dialect.push_string(out, cell),
+                    }
+                }
+                out.push(')');
+            }
+        }
+    }
+
+    fn push_end(&self, out: &mut String, row_count: usize) {
+        match &self.format {
+            ExportFormat::Csv(_) | ExportFormat::Markdown | ExportFormat::Pdf { .. } | ExportFormat::Template(_) => {}
+            ExportFormat::Json(options) => {
+                if !options.lines {
+                    out.push_str(if row_count == 0 { "]\n" } else { "\n]\n" });
+                }
+            }
+            ExportFormat::Html => out.push_str("</tbody>\n</table>\n</body>\n</html>\n"),
+            ExportFormat::Xml(options) => out.push_str(&format!("</{}>\n", xml_name(&options.root))),
+            ExportFormat::Latex => out.push_str("\\bottomrule\n\\end{tabular}\n"),
+            ExportFormat::Sql(_) => {
+                if row_count > 0 {
+                    out.push_str(";\n");
+                }
+            }
+        }
+    }
+}
+
+// Quoted the same way write_csv quotes
+fn push_csv_row<'c>(out: &mut String, options: &CsvOptions, cells: impl Iterator<Item = &'c str>) {
+    let delimiter = char::from(options.delimiter);
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        if options.quote_all || cell.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&cell.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(cell);
+        }
+    }
+    out.push_str(if options.crlf { "\r\n" } else { "\n" });
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    push_json_string(&mut out, text);
+    out
+}
+
+fn push_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch < ' ' => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+// The JSON number grammar: -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+// Stricter than parsing as f64, which would also take "+1", "1." or "inf", and
+// keeps values like zip codes with a leading zero as strings.
+pub fn is_json_number(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let mut pos = usize::from(bytes.first() == Some(&b'-'));
+    let digits = |pos: &mut usize| {
+        let start = *pos;
+        while bytes.get(*pos).is_some_and(u8::is_ascii_digit) {
+            *pos += 1;
+        }
+        *pos - start
+    };
+    match bytes.get(pos) {
+        Some(b'0') => pos += 1,
+        Some(b'1'..=b'9') => {
+            digits(&mut pos);
+        }
+        _ => return false,
+    }
+    if bytes.get(pos) == Some(&b'.') {
+        pos += 1;
+        if digits(&mut pos) == 0 {
+            return false;
+        }
+    }
+    if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+        pos += 1;
+        if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+            pos += 1;
+        }
+        if digits(&mut pos) == 0 {
+            return false;
+        }
+    }
+    pos == bytes.len()
+}
+
+// Pipes would end the cell and line breaks the row, so they're escaped and
+// turned into <br>, which GitHub renders inside table cells
+fn push_markdown_row<'c>(out: &mut String, cells: impl Iterator<Item = &'c str>) {
+    out.push('|');
+    for cell in cells {
+        out.push(' ');
+        let mut lines = cell.lines();
+        if let Some(first) = lines.next() {
+            out.push_str(&first.replace('|', "\\|"));
+        }
+        for line in lines {
+            out.push_str("<br>");
+            out.push_str(&line.replace('|', "\\|"));
+        }
+        out.push_str(" |");
+    }
+    out.push('\n');
+}
+
+// Cells keep their line breaks through `white-space: pre-wrap`
+const HTML_START: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Table</title>
+<style>
+body { font-family: sans-serif; margin: 1em; }
+table { border-collapse: collapse; font-size: 14px; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; white-space: pre-wrap; }
+th { position: sticky; top: 0; background: #e8e8e8; }
+tbody tr:nth-child(even) { background: #f5f5f5; }
+</style>
+</head>
+<body>
+<table>
+<thead>
+";
+
+fn push_html_row<'c>(out: &mut String, tag: &str, cells: impl Iterator<Item = &'c str>) {
+    out.push_str("<tr>");
+    for cell in cells {
+        out.push_str(&format!("<{}>", tag));
+        for ch in cell.chars() {
+            match ch {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                ch => out.push(ch),
+            }
+        }
+        out.push_str(&format!("</{}>", tag));
+    }
+    out.push_str("</tr>\n");
+}
+
+// An element or attribute name from any text: characters XML doesn't allow
+// in names become '_', as does a leading digit, dot or hyphen, or "xml"
+// in any case, which is reserved
+fn xml_name(text: &str) -> String {
+    let mut name: String = text
+        .trim()
+        .chars()
+        .map(|ch| if ch.is_alphanumeric() || matches!(ch, '_' | '-' | '.') { ch } else { '_' })
+        .collect();
+    let starts_badly = name.chars().next().is_none_or(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '.'));
+    if starts_badly || name.to_ascii_lowercase().starts_with("xml") {
+        name.insert(0, '_');
+    }
+    name
+}
+
+// Escaped for both text and double-quoted attributes. Control characters
+// other than tab and newlines aren't allowed in XML 1.0 at all, so they are
+// dropped.
+fn push_xml_text(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\r' => out.push_str("&#13;"),
+            '\t' | '\n' => out.push(ch),
+            ch if ch.is_control() && u32::from(ch) < 0x20 => {}
+            ch => out.push(ch),
+        }
+    }
+}
+
+fn push_markdown_separator(out: &mut String, columns: usize) {
+    out.push('|');
+    for _ in 0..columns {
+        out.push_str(" --- |");
+    }
+    out.push('\n');
+}
+
+// Characters LaTeX treats as commands or markup are written as the commands
+// that print them; line breaks inside a cell become spaces
+fn push_latex_row<'c>(out: &mut String, cells: impl Iterator<Item = &'c str>) {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            out.push_str(" & ");
+        }
+        for ch in cell.chars() {
+            match ch {
+                '\\' => out.push_str("\\textbackslash{}"),
+                '~' => out.push_str("\\textasciitilde{}"),
+                '^' => out.push_str("\\textasciicircum{}"),
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                    out.push('\\');
+                    out.push(ch);
+                }
+                '\n' | '\r' => out.push(' '),
+                ch => out.push(ch),
+            }
+        }
+    }
+    out.push_str(" \\\\\n");
+}
@@ -0,0 +1,429 @@
+// Expressions over the values of one row, for schema rules that relate
+// columns, like `end_date >= start_date` or `total == price * qty`.
+//
+// Names are column names; a name with spaces or symbols goes in backticks,
+// as in `unit price`. Text goes in double quotes. A cell is a number if it
+// parses as one and text otherwise, so ISO dates compare in order as text.
+// Numbers count as equal when they agree to about nine significant digits,
+// which absorbs the rounding in products like price * qty. Operators, from
+// loosest to tightest: `or` (`||`), `and` (`&&`), `not` (`!`), the
+// comparisons `= == != <> < <= > >=`, `+ -`, `* /` and unary minus.
+
+// Limits that keep parsing and evaluation from running out of stack
+const MAX_TOKENS: usize = 1000;
+const MAX_DEPTH: usize = 64; // Brackets, `not` and unary minus inside one another
+
+#[derive(Clone, Debug)]
+pub struct Expr {
+    root: Node,
+    columns: Vec<String>, // Names the expression refers to, in order of first use
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Number(f64),
+    Text(String),
+    Column(usize), // Index into `columns`
+    Not(Box<Node>),
+    Negate(Box<Node>),
+    Binary(Op, Box<Node>, Box<Node>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Name(String),
+    Op(Op),
+    Not,
+    Open,
+    Close,
+}
+
+impl Expr {
+    pub fn new(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        if tokens.len() > MAX_TOKENS {
+            return Err("the expression is too long".to_string());
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            columns: Vec::new(),
+        };
+        let root = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err("unexpected text after the expression".to_string());
+        }
+        Ok(Expr {
+            root,
+            columns: parser.columns,
+        })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    // Whether the row passes, given the cell of each of `columns()`. None if
+    // the expression can't be evaluated, like text times a number, or isn't
+    // a condition.
+    pub fn is_true(&self, cells: &[&str]) -> Option<bool> {
+        match eval(&self.root, cells)? {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+fn eval(node: &Node, cells: &[&str]) -> Option<Value> {
+    Some(match node {
+        Node::Number(value) => Value::Number(*value),
+        Node::Text(text) => Value::Text(text.clone()),
+        Node::Column(idx) => {
+            let cell = cells.get(*idx)?.trim();
+            match cell.parse::<f64>() {
+                Ok(value) if value.is_finite() => Value::Number(value),
+                _ => Value::Text(cell.to_string()),
+            }
+        }
+        Node::Not(node) => match eval(node, cells)? {
+            Value::Bool(value) => Value::Bool(!value),
+            _ => return None,
+        },
+        Node::Negate(node) => match eval(node, cells)? {
+            Value::Number(value) => Value::Number(-value),
+            _ => return None,
+        },
+        Node::Binary(op @ (Op::Or | Op::And), left, right) => {
+            let Value::Bool(left) = eval(left, cells)? else {
+                return None;
+            };
+            // Short-circuits, so `qty = 0 or total / qty > 1` works
+            if left == (*op == Op::Or) {
+                return Some(Value::Bool(left));
+            }
+            match eval(right, cells)? {
+                Value::Bool(right) => Value::Bool(right),
+                _ => return None,
+            }
+        }
+        Node::Binary(op, left, right) => binary(*op, eval(left, cells)?, eval(right, cells)?)?,
+    })
+}
+
+fn binary(op: Op, left: Value, right: Value) -> Option<Value> {
+    use std::cmp::Ordering;
+    let order = match (&left, &right) {
+        (Value::Number(a), Value::Number(b)) => {
+            let arithmetic = match op {
+                Op::Add => Some(a + b),
+                Op::Sub => Some(a - b),
+                Op::Mul => Some(a * b),
+                Op::Div if *b != 0.0 => Some(a / b),
+                Op::Div => return None,
+                _ => None,
+            };
+            if let Some(value) = arithmetic {
+                return Some(Value::Number(value));
+            }
+            if (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0) {
+                Ordering::Equal
+            } else {
+                a.partial_cmp(b)?
+            }
+        }
+        (Value::Text(a), Value::Text(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) if matches!(op, Op::Eq | Op::Ne) => a.cmp(b),
+        _ => return None,
+    };
+    Some(Value::Bool(match op {
+        Op::Eq => order.is_eq(),
+        Op::Ne => order.is_ne(),
+        Op::Lt => order.is_lt(),
+        Op::Le => order.is_le(),
+        Op::Gt => order.is_gt(),
+        Op::Ge => order.is_ge(),
+        _ => return None,
+    }))
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let ch = chars[pos];
+        let next = chars.get(pos + 1).copied();
+        pos += 1;
+        let token = match (ch, next) {
+            (ch, _) if ch.is_whitespace() => continue,
+            ('(', _) => Token::Open,
+            (')', _) => Token::Close,
+            ('+', _) => Token::Op(Op::Add),
+            ('-', _) => Token::Op(Op::Sub),
+            ('*', _) => Token::Op(Op::Mul),
+            ('/', _) => Token::Op(Op::Div),
+            ('=', Some('=')) | ('!', Some('=')) | ('<', Some('=' | '>')) | ('>', Some('=')) | ('&', Some('&')) | ('|', Some('|')) => {
+                pos += 1;
+                match (ch, next) {
+                    ('=', _) => Token::Op(Op::Eq),
+                    ('!', _) | ('<', Some('>')) => Token::Op(Op::Ne),
+                    ('<', _) => Token::Op(Op::Le),
+                    ('>', _) => Token::Op(Op::Ge),
+                    ('&', _) => Token::Op(Op::And),
+                    _ => Token::Op(Op::Or),
+                }
+            }
+            ('=', _) => Token::Op(Op::Eq),
+            ('<', _) => Token::Op(Op::Lt),
+            ('>', _) => Token::Op(Op::Gt),
+            ('!', _) => Token::Not,
+            ('"', _) => {
+                // "" inside quotes is a quote, as in CSV
+                let mut text = String::new();
+                loop {
+                    match (chars.get(pos), chars.get(pos + 1)) {
+                        (Some('"'), Some('"')) => {
+                            text.push('"');
+                            pos += 2;
+                        }
+                        (Some('"'), _) => break,
+                        (Some(&ch), _) => {
+                            text.push(ch);
+                            pos += 1;
+                        }
+                        (None, _) => return Err("unterminated text in quotes".to_string()),
+                    }
+                }
+                pos += 1;
+                Token::Text(text)
+            }
+            ('`', _) => {
+                let end = chars[pos..].iter().position(|&ch| ch == '`').ok_or("unterminated column name in backticks")?;
+                let name: String = chars[pos..pos + end].iter().collect();
+                pos += end + 1;
+                Token::Name(name)
+            }
+            (ch, _) if ch.is_ascii_digit() || ch == '.' => {
+                let start = pos - 1;
+                while chars.get(pos).is_some_and(|ch| ch.is_ascii_alphanumeric() || *ch == '.')
+                    || (matches!(chars.get(pos), Some('+' | '-')) && matches!(chars[pos - 1], 'e' | 'E'))
+                {
+                    pos += 1;
+                }
+                let number: String = chars[start..pos].iter().collect();
+                Token::Number(number.parse().map_err(|_| format!("{} isn't a number", number))?)
+            }
+            (ch, _) if ch.is_alphabetic() || ch == '_' => {
+                let start = pos - 1;
+                while chars.get(pos).is_some_and(|ch| ch.is_alphanumeric() || *ch == '_') {
+                    pos += 1;
+                }
+                let word: String = chars[start..pos].iter().collect();
+                match word.to_lowercase().as_str() {
+                    "and" => Token::Op(Op::And),
+                    "or" => Token::Op(Op::Or),
+                    "not" => Token::Not,
+                    _ => Token::Name(word),
+                }
+            }
+            (ch, _) => return Err(format!("unexpected {} at position {}", ch, pos)),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    columns: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    // The next token if it is one of `ops`
+    fn op(&mut self, ops: &[Op]) -> Option<Op> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    // Left-associative operators of one level, with `operand` for the next
+    fn level(&mut self, ops: &[Op], operand: fn(&mut Self) -> Result<Node, String>) -> Result<Node, String> {
+        let mut node = operand(self)?;
+        while let Some(op) = self.op(ops) {
+            node = Node::Binary(op, Box::new(node), Box::new(operand(self)?));
+        }
+        Ok(node)
+    }
+
+    // `operand` one level deeper in brackets, `not` or unary minus
+    fn nested(&mut self, operand: fn(&mut Self) -> Result<Node, String>) -> Result<Node, String> {
+        if self.depth == MAX_DEPTH {
+            return Err("the expression is nested too deeply".to_string());
+        }
+        self.depth += 1;
+        let node = operand(self)?;
+        self.depth -= 1;
+        Ok(node)
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        self.level(&[Op::Or], Parser::and)
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        self.level(&[Op::And], Parser::not)
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Node::Not(Box::new(self.nested(Parser::not)?)));
+        }
+        self.comparison()
+    }
+
+    // Comparisons don't chain: `a < b < c` is an error
+    fn comparison(&mut self) -> Result<Node, String> {
+        let left = self.sum()?;
+        let Some(op) = self.op(&[Op::Eq, Op::Ne, Op::Lt, Op::Le, Op::Gt, Op::Ge]) else {
+            return Ok(left);
+        };
+        Ok(Node::Binary(op, Box::new(left), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        self.level(&[Op::Add, Op::Sub], Parser::product)
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        self.level(&[Op::Mul, Op::Div], Parser::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.op(&[Op::Sub]).is_some() {
+            return Ok(Node::Negate(Box::new(self.nested(Parser::unary)?)));
+        }
+        let token = self.tokens.get(self.pos).cloned().ok_or("the expression ends too soon")?;
+        self.pos += 1;
+        Ok(match token {
+            Token::Number(value) => Node::Number(value),
+            Token::Text(text) => Node::Text(text),
+            Token::Name(name) => {
+                let idx = self.columns.iter().position(|column| *column == name).unwrap_or_else(|| {
+                    self.columns.push(name);
+                    self.columns.len() - 1
+                });
+                Node::Column(idx)
+            }
+            Token::Open => {
+                let node = self.nested(Parser::or)?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err("missing )".to_string());
+                }
+                self.pos += 1;
+                node
+            }
+            _ => return Err("expected a value, a column name or (".to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, cells: &[&str]) -> Option<bool> {
+        Expr::new(text).unwrap().is_true(cells)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(check("1 + 2 * 3 = 7", &[]), Some(true));
+        assert_eq!(check("(1 + 2) * 3 = 9", &[]), Some(true));
+        assert_eq!(check("8 / 4 / 2 = 1", &[]), Some(true));
+        assert_eq!(check("10 - 4 - 3 = 3", &[]), Some(true));
+        assert_eq!(check("a = 1 or b = 2 and c = 3", &["1", "0", "0"]), Some(true));
+        assert_eq!(check("not a = 1 and b = 2", &["0", "2"]), Some(true));
+        assert_eq!(check("a >= b && !(a == 0)", &["2", "1"]), Some(true));
+        assert!(Expr::new("a < b < c").is_err());
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(check("-a * 2 = -6", &["3"]), Some(true));
+        assert_eq!(check("- -3 = 3", &[]), Some(true));
+        assert_eq!(check("2 - -1 = 3", &[]), Some(true));
+        assert_eq!(check("-a = 1", &["x"]), None);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        assert_eq!(check("a / b > 1", &["1", "0"]), None);
+        assert_eq!(check("b = 0 or a / b > 1", &["0", "1"]), Some(true));
+    }
+
+    #[test]
+    fn columns_and_cells() {
+        let expr = Expr::new("total == price * `unit count`").unwrap();
+        assert_eq!(expr.columns(), ["total", "price", "unit count"]);
+        assert_eq!(expr.is_true(&["6", "2", "3"]), Some(true));
+        assert_eq!(expr.is_true(&["6.000000001", "2", "3"]), Some(true));
+        // A column without a cell, as when the file lacks it
+        assert_eq!(expr.is_true(&["6", "2"]), None);
+        assert_eq!(check("end >= start", &["2023-02-01", "2023-01-31"]), Some(true));
+        assert_eq!(check("a == \"say \"\"hi\"\"\"", &["say \"hi\""]), Some(true));
+    }
+
+    #[test]
+    fn malformed() {
+        let deep = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        let long = vec!["1"; 100_000].join(" + ");
+        let nots = format!("{}a", "not ".repeat(100_000));
+        let minuses = format!("{}1 = 1", "-".repeat(100_000));
+        for text in [
+            "", "a +", "(a", "a)", "a b", "\"open", "`open", "1..2 = 1", "a # b", "== 1", "not", "()", "a = = 1", deep.as_str(),
+            long.as_str(), nots.as_str(), minuses.as_str(),
+        ] {
+            assert!(Expr::new(text).is_err(), "{}", text);
+        }
+        assert_eq!(check("a + 1", &["1"]), None);
+        assert_eq!(check("a * 2 = 4", &["two"]), None);
+    }
+}
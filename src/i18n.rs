@@ -0,0 +1,700 @@
+// Translations of the UI text.
+//
+// English text doubles as the lookup key: `tr("Load CSV")` returns the
+// translation for the current language, or the English text when there is
+// none, so an incomplete catalog still shows every control. Text with values
+// filled in goes through `tr!`, whose template marks each value with `{}`;
+// translations keep the values in the same order. The language is saved in
+// the config as `language=en|es`.
+
+use crate::config::Config;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// `tr!("Page {} of {}", page, pages)` is `tr` followed by filling in the values
+macro_rules! tr {
+    ($text:literal) => {
+        $crate::i18n::tr($text)
+    };
+    ($text:literal, $($value:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::tr($text), &[$(&$value as &dyn std::fmt::Display),+])
+    };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    // Each language is listed under its own name
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn config_value(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        config
+            .get("language")
+            .and_then(|value| Language::ALL.into_iter().find(|language| language.config_value() == value))
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(self, config: &mut Config) {
+        config.set("language", self.config_value());
+    }
+}
+
+// Read by every `tr` call; set once at startup and again from the picker
+static LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+pub fn language() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Language::Spanish,
+        _ => Language::English,
+    }
+}
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn tr(text: &'static str) -> &'static str {
+    let catalog = match language() {
+        Language::English => return text,
+        Language::Spanish => {
+            static SPANISH_CATALOG: OnceLock<HashMap<&str, &str>> = OnceLock::new();
+            SPANISH_CATALOG.get_or_init(|| SPANISH.iter().copied().collect())
+        }
+    };
+    catalog.get(text).copied().unwrap_or(text)
+}
+
+// Replace each `{}` in the template with the next value
+pub fn fill(template: &str, values: &[&dyn Display]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut values = values.iter();
+    let mut rest = template;
+    while let Some(start) = rest.find("{}") {
+        text.push_str(&rest[..start]);
+        if let Some(value) = values.next() {
+            text.push_str(&value.to_string());
+        }
+        rest = &rest[start + 2..];
+    }
+    text.push_str(rest);
+    text
+}
+
+// English text and its Spanish translation
+const SPANISH: &[(&str, &str)] = &[
+    ("Load CSV", "Cargar CSV"),
+    ("Page {} of {}", "Página {} de {}"),
+    ("Count", "Recuento"),
+    ("Sum", "Suma"),
+    ("Mean", "Media"),
+    ("Distinct", "Distintos"),
+    ("Blank", "Vacías"),
+    ("Min", "Mín"),
+    ("Max", "Máx"),
+    ("All", "Todas"),
+    ("Searching {}", "Buscando en {}"),
+    ("Searching all columns", "Buscando en todas las columnas"),
+    ("Sorting by {}", "Ordenando por {}"),
+    ("Loading {}", "Cargando {}"),
+    ("Saving {}", "Guardando {}"),
+    ("Edit keyboard shortcuts", "Editar atajos de teclado"),
+    ("Clear sort", "Quitar orden"),
+    ("Sort by {} ascending", "Ordenar por {} ascendente"),
+    ("Sort by {} descending", "Ordenar por {} descendente"),
+    ("Hide column {}", "Ocultar columna {}"),
+    ("Show column {}", "Mostrar columna {}"),
+    ("Show all columns", "Mostrar todas las columnas"),
+    ("Hide all columns", "Ocultar todas las columnas"),
+    ("Hide blank columns", "Ocultar columnas vacías"),
+    ("Auto-fit column widths", "Ajustar ancho de columnas"),
+    ("Cancel", "Cancelar"),
+    ("Rename Column", "Renombrar columna"),
+    ("Rename", "Renombrar"),
+    ("{} statistics", "Estadísticas de {}"),
+    ("Close", "Cerrar"),
+    ("Computed over the rows matching the search", "Calculado sobre las filas que coinciden con la búsqueda"),
+    ("Filter {} by values", "Filtrar {} por valores"),
+    ("Find values", "Buscar valores"),
+    ("Select all", "Seleccionar todo"),
+    ("Select none", "No seleccionar nada"),
+    ("Showing the {} most common of {} values", "Mostrando los {} más frecuentes de {} valores"),
+    ("(blank) ({})", "(vacío) ({})"),
+    ("Apply", "Aplicar"),
+    ("Row {}", "Fila {}"),
+    ("Choose \"Go to row detail\" from a cell's menu or \"Statistics\" from a column header's menu.", "Elija \"Ver detalle de la fila\" en el menú de una celda o \"Estadísticas de la columna\" en el menú de un encabezado."),
+    ("Previous", "Anterior"),
+    ("Row {} of {}", "Fila {} de {}"),
+    ("Next", "Siguiente"),
+    ("Back to table", "Volver a la tabla"),
+    ("{} characters, {} lines", "{} caracteres, {} líneas"),
+    ("Copy", "Copiar"),
+    ("Performance", "Rendimiento"),
+    ("Frame", "Fotograma"),
+    ("Table layout", "Diseño de la tabla"),
+    ("Row measuring", "Medición de filas"),
+    ("Rows rendered", "Filas dibujadas"),
+    ("Last load", "Última carga"),
+    ("Last search", "Última búsqueda"),
+    ("Last sort", "Última ordenación"),
+    ("Appearance", "Apariencia"),
+    ("Language", "Idioma"),
+    ("Theme", "Tema"),
+    ("Accent", "Acento"),
+    ("Warning", "Advertencia"),
+    ("Default", "Predeterminado"),
+    ("Row density", "Densidad de filas"),
+    ("Zoom", "Zoom"),
+    ("Reset", "Restablecer"),
+    ("Keyboard Shortcuts", "Atajos de teclado"),
+    ("Press a key...", "Pulse una tecla..."),
+    ("Change", "Cambiar"),
+    ("Reset to Defaults", "Restablecer valores predeterminados"),
+    ("{} in {} values", "{} en {} valores"),
+    ("all columns", "todas las columnas"),
+    ("excluding \"{}\" in {}", "excluyendo \"{}\" en {}"),
+    ("matching \"{}\" in {}", "coincidiendo con \"{}\" en {}"),
+    ("Filter: {} ({} rows)", "Filtro: {} ({} filas)"),
+    ("Close panel", "Cerrar panel"),
+    ("Dock on the other side", "Acoplar al otro lado"),
+    ("Search", "Buscar"),
+    ("Search:", "Buscar:"),
+    ("{} rows match", "{} filas coinciden"),
+    ("Rows to preview", "Filas de la vista previa"),
+    ("Column:", "Columna:"),
+    ("All columns", "Todas las columnas"),
+    ("Exact match", "Coincidencia exacta"),
+    ("Match whole cell values, case-sensitively", "Coincidir con el valor completo de la celda, distinguiendo mayúsculas"),
+    ("Exclude matches", "Excluir coincidencias"),
+    ("Show the rows that don't match instead", "Mostrar en su lugar las filas que no coinciden"),
+    ("Clear Search", "Limpiar búsqueda"),
+    ("{} filtered to {} values", "{} filtrada a {} valores"),
+    ("Go to row:", "Ir a la fila:"),
+    ("Go", "Ir"),
+    ("Visible: {}/{}", "Visibles: {}/{}"),
+    ("Show All", "Mostrar todas"),
+    ("Hide All", "Ocultar todas"),
+    ("Hide All Blank Columns", "Ocultar columnas vacías"),
+    ("Auto-fit Widths", "Ajustar anchos"),
+    ("Fit visible columns to the first {} displayed rows", "Ajustar las columnas visibles a las primeras {} filas mostradas"),
+    ("Freeze:", "Fijar:"),
+    (" columns", " columnas"),
+    ("Keep the first visible columns in place while scrolling sideways", "Mantener fijas las primeras columnas visibles al desplazarse en horizontal"),
+    ("Toggle visibility for column: {}", "Mostrar u ocultar la columna: {}"),
+    ("Modified", "Modificado"),
+    ("There are changes that haven't been saved", "Hay cambios sin guardar"),
+    ("Rows: {}", "Filas: {}"),
+    ("Columns: {}", "Columnas: {}"),
+    ("Memory: {}", "Memoria: {}"),
+    ("Approximate memory held by the loaded rows and search results", "Memoria aproximada ocupada por las filas cargadas y los resultados de búsqueda"),
+    ("Ctrl+C copies the selected rows", "Ctrl+C copia las filas seleccionadas"),
+    ("Ctrl+C copies the selected rows and Delete removes them", "Ctrl+C copia las filas seleccionadas y Supr las elimina"),
+    ("{} rows selected", "{} filas seleccionadas"),
+    ("Cell: row {}, {}", "Celda: fila {}, {}"),
+    ("Loaded in {} s", "Cargado en {} s"),
+    ("Press {} for performance details", "Pulse {} para ver detalles de rendimiento"),
+    ("Open Large CSV", "Abrir CSV grande"),
+    ("Read pages from disk on demand instead of loading the whole file", "Leer páginas del disco según se necesiten en lugar de cargar todo el archivo"),
+    ("Preview CSV", "Vista previa de CSV"),
+    ("Load only the first rows of the file", "Cargar solo las primeras filas del archivo"),
+    (" rows", " filas"),
+    ("Save CSV", "Guardar CSV"),
+    ("Files paged from disk can't be saved", "Los archivos paginados desde el disco no se pueden guardar"),
+    ("Load the rest of the file before saving", "Cargue el resto del archivo antes de guardar"),
+    ("A save is already in progress", "Ya hay un guardado en curso"),
+    ("Export", "Exportar"),
+    ("Export current view…", "Exportar vista actual…"),
+    ("Save exactly what is shown: {} rows matching the search, in sorted order, with the {} visible columns", "Guardar exactamente lo que se muestra: {} filas que coinciden con la búsqueda, en el orden actual, con las {} columnas visibles"),
+    ("Export selection…", "Exportar selección…"),
+    ("Save the {} selected rows with only the visible columns", "Guardar las {} filas seleccionadas solo con las columnas visibles"),
+    ("Copy selection as CSV", "Copiar selección como CSV"),
+    ("Copy selection as JSON", "Copiar selección como JSON"),
+    ("Export column profile…", "Exportar perfil de columnas…"),
+    ("Save count, blanks, distinct values, sum, mean, min and max for each visible column, over the rows matching the search", "Guardar recuento, vacíos, valores distintos, suma, media, mínimo y máximo de cada columna visible, sobre las filas que coinciden con la búsqueda"),
+    ("Save these statistics for every visible column", "Guardar estas estadísticas para cada columna visible"),
+    ("Export changes…", "Exportar cambios…"),
+    ("Save a log of deleted rows and renamed columns, with row, column, old and new values, for review before sharing", "Guardar un registro de filas eliminadas y columnas renombradas, con fila, columna, valor anterior y nuevo, para revisarlo antes de compartir"),
+    ("No rows deleted or columns renamed since the file was opened or saved", "No se han eliminado filas ni renombrado columnas desde que se abrió o guardó el archivo"),
+    ("Export split by column…", "Exportar dividido por columna…"),
+    ("Export split by column", "Exportar dividido por columna"),
+    ("One file per distinct value of {}, with the rows matching the search and the visible columns.", "Un archivo por cada valor distinto de {}, con las filas que coinciden con la búsqueda y las columnas visibles."),
+    ("File names:", "Nombres de archivo:"),
+    ("{column}, {value} and {n} are filled in. The extension picks the format.", "Se rellenan {column}, {value} y {n}. La extensión determina el formato."),
+    ("Choose folder and export…", "Elegir carpeta y exportar…"),
+    ("Splitting by {}", "Dividiendo por {}"),
+    ("Export in chunks", "Exportar en partes"),
+    ("Rows per file:", "Filas por archivo:"),
+    ("Choose file name and export…", "Elegir nombre de archivo y exportar…"),
+    ("Save the rows matching the search as numbered files, data_001.csv, data_002.csv…, each with the header, for systems that limit the rows per upload", "Guardar las filas que coinciden con la búsqueda en archivos numerados, data_001.csv, data_002.csv…, cada uno con el encabezado, para sistemas que limitan las filas por carga"),
+    ("Export transposed…", "Exportar transpuesto…"),
+    ("Save the rows matching the search with rows and columns swapped, one line per visible column. The table here is not changed.", "Guardar las filas que coinciden con la búsqueda con filas y columnas intercambiadas, una línea por columna visible. La tabla no se modifica."),
+    ("Column", "Columna"),
+    ("Export with template…", "Exportar con plantilla…"),
+    ("Write a text file through each row matching the search, with {{Column name}} replaced by the row's value", "Escribir un archivo de texto por cada fila que coincide con la búsqueda, con {{Nombre de columna}} sustituido por el valor de la fila"),
+    ("Choose a row template", "Elegir una plantilla de fila"),
+    ("Save generated text", "Guardar el texto generado"),
+    ("XML options", "Opciones de XML"),
+    ("Root element:", "Elemento raíz:"),
+    ("Row element:", "Elemento de fila:"),
+    ("Write as attributes:", "Escribir como atributos:"),
+    ("Checked columns become attributes of the row element; the rest become child elements", "Las columnas marcadas se convierten en atributos del elemento de fila; el resto, en elementos hijos"),
+    ("Copy as Markdown", "Copiar como Markdown"),
+    ("Copy column", "Copiar columna"),
+    ("Save options", "Opciones de guardado"),
+    ("Save…", "Guardar…"),
+    ("CSV options", "Opciones de CSV"),
+    ("Delimiter", "Delimitador"),
+    ("Comma", "Coma"),
+    ("Semicolon", "Punto y coma"),
+    ("Tab", "Tabulador"),
+    ("Pipe", "Barra vertical"),
+    ("Quotes", "Comillas"),
+    ("When needed", "Cuando sea necesario"),
+    ("Every field", "En todos los campos"),
+    ("Line endings", "Fin de línea"),
+    ("CRLF (Windows)", "CRLF (Windows)"),
+    ("Include header row", "Incluir fila de encabezado"),
+    ("Encoding", "Codificación"),
+    ("UTF-8 with BOM", "UTF-8 con BOM"),
+    ("Windows-1252 (Western European)", "Windows-1252 (Europa occidental)"),
+    ("Plain text", "Texto sin formato"),
+    ("Markdown table", "Tabla Markdown"),
+    ("Quoted list ('a', 'b')", "Lista entre comillas ('a', 'b')"),
+    ("Copy the rows matching the search, with only the visible columns, as a Markdown table", "Copiar las filas que coinciden con la búsqueda, solo con las columnas visibles, como tabla Markdown"),
+    ("PDF in landscape", "PDF en horizontal"),
+    ("Turn PDF pages sideways to fit more columns", "Girar las páginas del PDF para que quepan más columnas"),
+    ("SQL:", "SQL:"),
+    ("Table name", "Nombre de la tabla"),
+    ("SQL table name", "Nombre de la tabla SQL"),
+    ("Used when exporting to a .sql file. Leave the table name empty to name it after the file.", "Se usa al exportar a un archivo .sql. Deje vacío el nombre de la tabla para usar el nombre del archivo."),
+    ("Numbers as JSON numbers", "Números como números JSON"),
+    ("In JSON and NDJSON exports, write cells like 42 or -1.5e3 as numbers instead of strings", "En las exportaciones JSON y NDJSON, escribir celdas como 42 o -1.5e3 como números en lugar de texto"),
+    ("Click rows to select them, with Shift or Ctrl to select several", "Haga clic en las filas para seleccionarlas; con Mayús o Ctrl para seleccionar varias"),
+    ("Shortcuts", "Atajos"),
+    ("Panels", "Paneles"),
+    ("Summary Footer", "Pie de resumen"),
+    ("Row Numbers", "Números de fila"),
+    ("Wrap Text", "Ajustar texto"),
+    ("Wrap long cells onto several lines, or cut them off at the column edge. Double-click a cell to see all of it.", "Repartir las celdas largas en varias líneas o cortarlas en el borde de la columna. Haga doble clic en una celda para verla completa."),
+    ("Previewing the first {} rows of the file.", "Vista previa de las primeras {} filas del archivo."),
+    ("Load the rest", "Cargar el resto"),
+    ("Paging {} rows from disk. Search and summaries cover the current page only.", "Paginando {} filas desde el disco. La búsqueda y los resúmenes solo abarcan la página actual."),
+    ("Showing {} of {} rows", "Mostrando {} de {} filas"),
+    ("First", "Primera"),
+    ("Last", "Última"),
+    ("Go to page:", "Ir a la página:"),
+    ("Rows per page:", "Filas por página:"),
+    ("Infinite scroll", "Desplazamiento infinito"),
+    ("Load more rows as you scroll instead of paging", "Cargar más filas al desplazarse en lugar de paginar"),
+    ("Files paged from disk are browsed a page at a time", "Los archivos paginados desde el disco se recorren página a página"),
+    ("Scroll columns:", "Desplazar columnas:"),
+    ("Shift+scroll over the table also moves through the columns", "Mayús+rueda sobre la tabla también recorre las columnas"),
+    ("Row number in the file", "Número de fila en el archivo"),
+    ("Click to sort, right-click for more", "Clic para ordenar, clic derecho para más opciones"),
+    ("Sort ascending", "Orden ascendente"),
+    ("Sort descending", "Orden descendente"),
+    ("Hide column", "Ocultar columna"),
+    ("Auto-fit width", "Ajustar ancho"),
+    ("Rename...", "Renombrar..."),
+    ("Column statistics", "Estadísticas de la columna"),
+    ("Filter by values...", "Filtrar por valores..."),
+    ("Copy cell", "Copiar celda"),
+    ("Copy row", "Copiar fila"),
+    ("Filter to this value", "Filtrar por este valor"),
+    ("Exclude this value", "Excluir este valor"),
+    ("Go to row detail", "Ver detalle de la fila"),
+    ("No columns are visible. Use the column controls to show columns.", "No hay columnas visibles. Use los controles de columnas para mostrarlas."),
+    ("Command Palette", "Paleta de comandos"),
+    ("Type a command", "Escriba un comando"),
+    ("No matching commands", "Ningún comando coincide"),
+    ("Columns", "Columnas"),
+    ("Details", "Detalles"),
+    ("Open file", "Abrir archivo"),
+    ("Save file", "Guardar archivo"),
+    ("Export current view", "Exportar vista actual"),
+    ("Export selected rows", "Exportar filas seleccionadas"),
+    ("Focus search", "Ir a la búsqueda"),
+    ("Clear search", "Limpiar búsqueda"),
+    ("Next page", "Página siguiente"),
+    ("Previous page", "Página anterior"),
+    ("Toggle column controls", "Mostrar u ocultar controles de columnas"),
+    ("Toggle summary footer", "Mostrar u ocultar pie de resumen"),
+    ("Toggle performance overlay", "Mostrar u ocultar panel de rendimiento"),
+    ("Command palette", "Paleta de comandos"),
+    ("Zoom in", "Acercar"),
+    ("Zoom out", "Alejar"),
+    ("Reset zoom", "Restablecer zoom"),
+    ("Follow system", "Según el sistema"),
+    ("Dark", "Oscuro"),
+    ("Light", "Claro"),
+    ("High contrast", "Alto contraste"),
+    ("Compact", "Compacta"),
+    ("Comfortable", "Cómoda"),
+    ("Validation", "Validación"),
+    ("Text", "Texto"),
+    ("Integer", "Entero"),
+    ("Number", "Número"),
+    ("Date (YYYY-MM-DD)", "Fecha (AAAA-MM-DD)"),
+    ("Boolean", "Booleano"),
+    ("{}: pattern: {}", "{}: patrón: {}"),
+    ("{}: minimum: {}", "{}: mínimo: {}"),
+    ("{}: maximum: {}", "{}: máximo: {}"),
+    ("booleans have no limits", "los booleanos no tienen límites"),
+    ("must be a whole number of characters", "debe ser un número entero de caracteres"),
+    ("must be a date like 2024-01-31", "debe ser una fecha como 2024-01-31"),
+    ("must be a number", "debe ser un número"),
+    ("Column missing", "Falta la columna"),
+    ("Value required", "Valor obligatorio"),
+    ("Wrong type", "Tipo incorrecto"),
+    ("Doesn't match pattern", "No coincide con el patrón"),
+    ("Below minimum", "Por debajo del mínimo"),
+    ("Above maximum", "Por encima del máximo"),
+    ("{} problems found", "{} problemas encontrados"),
+    ("Validating", "Validando"),
+    ("Edit schema…", "Editar esquema…"),
+    ("Validate", "Validar"),
+    ("Add rules to the schema first, and fix any that show an error", "Primero añada reglas al esquema y corrija las que muestren un error"),
+    ("Describe the expected columns with Edit schema, then Validate to list every cell that doesn't fit.", "Describa las columnas esperadas con Editar esquema y luego pulse Validar para ver cada celda que no encaja."),
+    ("The data has changed since it was validated. Validate again to update the list.", "Los datos han cambiado desde la validación. Valide de nuevo para actualizar la lista."),
+    ("No problems in {} rows", "Ningún problema en {} filas"),
+    ("{} problems in {} rows", "{} problemas en {} filas"),
+    ("Listing the first {}", "Se muestran los primeros {}"),
+    ("Row {}, {}: {}", "Fila {}, {}: {}"),
+    ("Value: {}", "Valor: {}"),
+    ("Schema", "Esquema"),
+    ("The columns a file should have. Limits are values for numbers, dates for dates and lengths for text. Patterns are regular expressions that must match the whole value. For numbers, \"Within σ\" flags values more than that many standard deviations from the column's mean.", "Las columnas que debe tener un archivo. Los límites son valores para los números, fechas para las fechas y longitudes para el texto. Los patrones son expresiones regulares que deben coincidir con todo el valor. Para los números, \"Dentro de σ\" señala los valores a más de esas desviaciones estándar de la media de la columna."),
+    ("Use this file's columns", "Usar las columnas de este archivo"),
+    ("Replace the schema with a rule for each column of this file, with types guessed from the first rows", "Reemplazar el esquema por una regla para cada columna de este archivo, con tipos deducidos de las primeras filas"),
+    ("Add rule", "Añadir regla"),
+    ("Remove all", "Quitar todas"),
+    ("Type", "Tipo"),
+    ("Required", "Obligatorio"),
+    ("Pattern", "Patrón"),
+    ("Minimum", "Mínimo"),
+    ("Maximum", "Máximo"),
+    ("This file has no column with this name", "Este archivo no tiene ninguna columna con este nombre"),
+    ("Remove rule", "Quitar regla"),
+    ("Filter to invalid values", "Filtrar a valores no válidos"),
+    ("Show the rows where this column isn't blank and isn't a valid {}", "Mostrar las filas donde esta columna no está vacía y no es un {} válido"),
+    ("{} values that aren't {}", "valores de {} que no son {}"),
+    ("{} filtered to values that aren't {}", "{} filtrada a valores que no son {}"),
+    ("Not a valid {}", "No es un {} válido"),
+    ("Highlight Invalid Values", "Resaltar valores no válidos"),
+    ("Mark cells that don't parse as their column's type, such as N/A in a number column. Types come from the schema, or are guessed from the first rows.", "Marcar las celdas que no se ajustan al tipo de su columna, como N/A en una columna numérica. Los tipos vienen del esquema o se deducen de las primeras filas."),
+    ("Title", "Título"),
+    ("{}. Click to sort, right-click for more", "{}. Clic para ordenar, clic derecho para más"),
+    ("Schema loaded from {}", "Esquema cargado de {}"),
+    ("Table Schema", "Table Schema"),
+    ("Load…", "Cargar…"),
+    ("Read a Frictionless Table Schema or CSVW metadata file", "Leer un archivo Frictionless Table Schema o de metadatos CSVW"),
+    ("Table Schema or CSVW", "Table Schema o CSVW"),
+    ("Write the schema as a Frictionless Table Schema. Saved next to a CSV as name.schema.json, it is loaded with the CSV.", "Escribir el esquema como Frictionless Table Schema. Guardado junto a un CSV como nombre.schema.json, se carga con el CSV."),
+    ("not a Table Schema or CSVW metadata file", "no es un archivo Table Schema ni de metadatos CSVW"),
+    ("a field has no name", "un campo no tiene nombre"),
+    ("a column has no name or title", "una columna no tiene nombre ni título"),
+    ("The columns don't match the schema", "Las columnas no coinciden con el esquema"),
+    ("Check columns when a file is opened", "Comprobar las columnas al abrir un archivo"),
+    ("Compare each file's header with the schema and open this panel if columns are missing, extra or out of order", "Comparar el encabezado de cada archivo con el esquema y abrir este panel si faltan columnas, sobran o están desordenadas"),
+    ("All {} columns in the schema are present, in order", "Las {} columnas del esquema están presentes y en orden"),
+    ("Missing:", "Faltan:"),
+    ("Extra:", "Sobran:"),
+    ("Out of order:", "Fuera de orden:"),
+    ("In the schema but not in this file", "En el esquema pero no en este archivo"),
+    ("In this file but not in the schema", "En este archivo pero no en el esquema"),
+    ("In both, but not where the schema puts them", "En ambos, pero no donde los sitúa el esquema"),
+    ("Paste expected column names", "Pegar los nombres de columna esperados"),
+    ("One per line, or separated by commas or tabs, in the order the file should have them", "Uno por línea, o separados por comas o tabulaciones, en el orden que debe tener el archivo"),
+    ("Replace the schema with these columns", "Reemplazar el esquema por estas columnas"),
+    ("Rules already in the schema for these names are kept", "Se conservan las reglas que ya hay en el esquema para estos nombres"),
+    ("Duplicate Column Names", "Nombres de columna repetidos"),
+    ("Some columns share a name. Rename the repeats so each column can be told apart?", "Algunas columnas comparten nombre. ¿Renombrar las repetidas para poder distinguir cada columna?"),
+    ("Keep names", "Conservar los nombres"),
+    ("Some column names are repeated", "Algunos nombres de columna están repetidos"),
+    ("Unusual spaces", "Espacios inusuales"),
+    ("Zero-width characters", "Caracteres de ancho cero"),
+    ("Control characters", "Caracteres de control"),
+    ("Look-alike letters", "Letras parecidas"),
+    ("invisible characters in {}", "caracteres invisibles en {}"),
+    ("Filtered to cells with invisible characters", "Filtrado a celdas con caracteres invisibles"),
+    ("{} cells with invisible characters", "{} celdas con caracteres invisibles"),
+    ("Finding invisible characters", "Buscando caracteres invisibles"),
+    ("No invisible characters to clean", "No hay caracteres invisibles que limpiar"),
+    ("Cleaned {} cells", "Se limpiaron {} celdas"),
+    ("Find invisible characters", "Buscar caracteres invisibles"),
+    ("Clean all", "Limpiar todo"),
+    ("Turn unusual spaces into plain ones, remove zero-width and control characters, and replace look-alike letters in Latin words", "Convierte los espacios inusuales en espacios normales, quita los caracteres de ancho cero y de control y reemplaza las letras parecidas en palabras latinas"),
+    ("Files paged from disk can't be edited", "Los archivos paginados desde el disco no se pueden editar"),
+    ("The data has changed since it was scanned. Scan again to update the counts.", "Los datos han cambiado desde la búsqueda. Busca de nuevo para actualizar los recuentos."),
+    ("No invisible characters found", "No se encontraron caracteres invisibles"),
+    ("{} cells in {} rows", "{} celdas en {} filas"),
+    ("Show these rows", "Mostrar estas filas"),
+    ("Show all rows with invisible characters", "Mostrar todas las filas con caracteres invisibles"),
+    ("Show Invisible Characters", "Mostrar caracteres invisibles"),
+    ("Mark cells with no-break spaces, zero-width or control characters, or look-alike letters, and show the hidden characters", "Marca las celdas con espacios de no separación, caracteres de ancho cero o de control, o letras parecidas, y muestra los caracteres ocultos"),
+    ("Keep bytes that aren't UTF-8", "Conservar los bytes que no son UTF-8"),
+    ("Write cells that had invalid UTF-8 when the file was opened back with their original bytes, unless they've been changed", "Escribe las celdas que tenían UTF-8 no válido al abrir el archivo con sus bytes originales, salvo que se hayan modificado"),
+    ("{} cells aren't valid UTF-8", "{} celdas no son UTF-8 válido"),
+    ("Bytes that couldn't be read show as �. Turn on Keep bytes that aren't UTF-8 in the save options to write them back unchanged.", "Los bytes que no se pudieron leer se muestran como �. Activa Conservar los bytes que no son UTF-8 en las opciones de guardado para escribirlos sin cambios."),
+    ("Header, column {}", "Encabezado, columna {}"),
+    ("Row {}, {}", "Fila {}, {}"),
+    ("Email addresses", "Direcciones de correo"),
+    ("Phone numbers", "Números de teléfono"),
+    ("Card numbers", "Números de tarjeta"),
+    ("National ID numbers", "Números de documento de identidad"),
+    ("Bank account numbers", "Números de cuenta bancaria"),
+    ("Find personal data…", "Buscar datos personales…"),
+    ("Look for emails, phone numbers, card and ID numbers, and mask the columns that hold them in everything exported", "Busca correos, teléfonos y números de tarjeta y de documento, y enmascara las columnas que los contienen en todo lo que se exporta"),
+    ("Personal data found in {} columns", "Datos personales encontrados en {} columnas"),
+    ("Unmask", "Desenmascarar"),
+    ("Mask for export", "Enmascarar al exportar"),
+    ("Hide all but the last few characters of this column here and in everything exported", "Oculta todo salvo los últimos caracteres de esta columna aquí y en todo lo que se exporta"),
+    ("Finding personal data", "Buscando datos personales"),
+    ("Personal Data", "Datos personales"),
+    ("Masked columns keep only their last few characters, or an email's first letter and domain, in the grid and in everything saved, exported or copied.", "Las columnas enmascaradas conservan solo sus últimos caracteres, o la primera letra y el dominio de un correo, en la tabla y en todo lo que se guarda, exporta o copia."),
+    ("Scan again", "Buscar de nuevo"),
+    ("Unmask all", "Desenmascarar todo"),
+    ("Scanning…", "Buscando…"),
+    ("No likely personal data found", "No se encontraron posibles datos personales"),
+    ("Found", "Encontrado"),
+    ("Cells", "Celdas"),
+    ("Mask", "Enmascarar"),
+    ("{} of {}", "{} de {}"),
+    ("Mask all found columns", "Enmascarar todas las columnas encontradas"),
+    ("Pseudonymize Column", "Seudonimizar columna"),
+    ("Replace every value of {} so the data can be shared without it. Equal values get equal replacements and blank cells stay blank.", "Reemplaza cada valor de {} para poder compartir los datos sin él. Los valores iguales reciben el mismo reemplazo y las celdas vacías siguen vacías."),
+    ("Salted SHA-256 hashes", "Hashes SHA-256 con sal"),
+    ("Salt:", "Sal:"),
+    ("New salt", "Nueva sal"),
+    ("Keep the salt to hash other files the same way. Without it the hashes can't be matched to values.", "Guarda la sal para aplicar el mismo hash a otros archivos. Sin ella no se pueden relacionar los hashes con los valores."),
+    ("Numbered IDs", "ID numerados"),
+    ("Prefix:", "Prefijo:"),
+    ("You'll be asked where to save the list of values and their IDs.", "Se te preguntará dónde guardar la lista de valores y sus ID."),
+    ("Replace values", "Reemplazar valores"),
+    ("Save the ID mapping", "Guardar la correspondencia de ID"),
+    ("Replaced {} distinct values in {}", "Se reemplazaron {} valores distintos en {}"),
+    ("Pseudonymize…", "Seudonimizar…"),
+    ("Normalize Values", "Normalizar valores"),
+    ("Replace values of {} using a mapping file: a CSV with a header row, the values to replace in its first column and what to replace them with in the second.", "Reemplazar valores de {} con un archivo de correspondencias: un CSV con fila de encabezado, los valores a reemplazar en la primera columna y sus reemplazos en la segunda."),
+    ("Choose mapping file…", "Elegir archivo de correspondencias…"),
+    ("{} ({} mappings)", "{} ({} correspondencias)"),
+    ("Ignore case and surrounding spaces", "Ignorar mayúsculas y espacios alrededor"),
+    ("Changed {} cells", "Se cambiaron {} celdas"),
+    ("Every value is mapped", "Todos los valores tienen correspondencia"),
+    ("{} values have no mapping:", "{} valores no tienen correspondencia:"),
+    ("Save unmapped values…", "Guardar valores sin correspondencia…"),
+    ("Save them as a mapping file to fill in, with the second column empty", "Guardarlos como archivo de correspondencias para completar, con la segunda columna vacía"),
+    ("Normalize values…", "Normalizar valores…"),
+    ("Within σ", "Dentro de σ"),
+    ("{}: standard deviations: {}", "{}: desviaciones estándar: {}"),
+    ("only numbers have a mean", "solo los números tienen media"),
+    ("must be a number above zero", "debe ser un número mayor que cero"),
+    ("Far from the mean", "Lejos de la media"),
+    ("Row rules", "Reglas de fila"),
+    ("Conditions every row must meet, like end_date >= start_date or total = price * qty. Put column names with spaces in backticks and text in double quotes. Rows where any of the rule's columns is blank are skipped.", "Condiciones que debe cumplir cada fila, como end_date >= start_date o total = price * qty. Escriba los nombres de columna con espacios entre comillas invertidas y el texto entre comillas dobles. Se omiten las filas en las que alguna de las columnas de la regla está vacía."),
+    ("Row rule", "Regla de fila"),
+    ("Add row rule", "Agregar regla de fila"),
+    ("Rule not met", "Regla no cumplida"),
+    ("In the future", "En el futuro"),
+    ("Before the earliest date", "Antes de la fecha más temprana"),
+    ("Different UTC offset", "Desfase UTC distinto"),
+    ("Day or month first?", "¿Día o mes primero?"),
+    ("no offset", "sin desfase"),
+    ("{} date columns checked, {} problems found", "{} columnas de fecha revisadas, {} problemas encontrados"),
+    ("Checking dates", "Revisando fechas"),
+    ("Check dates", "Revisar fechas"),
+    ("Look for dates in the future or before the earliest date, times with different UTC offsets, and dates that could be day or month first", "Buscar fechas en el futuro o anteriores a la fecha más temprana, horas con desfases UTC distintos y fechas que podrían tener el día o el mes primero"),
+    ("Earliest date:", "Fecha más temprana:"),
+    ("Earliest date", "Fecha más temprana"),
+    ("Use a date like 1900-01-01", "Use una fecha como 1900-01-01"),
+    ("The data has changed since the dates were checked. Check again to update the counts.", "Los datos cambiaron desde que se revisaron las fechas. Revíselas de nuevo para actualizar los recuentos."),
+    ("No date columns found", "No se encontraron columnas de fecha"),
+    ("No problems in {} dates", "Sin problemas en {} fechas"),
+    ("Go to the first one", "Ir a la primera"),
+    ("Most times in this column have {}", "La mayoría de las horas de esta columna tienen {}"),
+    ("Some dates in this column are day first and others month first", "Algunas fechas de esta columna tienen el día primero y otras el mes primero"),
+    ("Nothing in this column shows whether dates like 01/02/2023 are day or month first", "Nada en esta columna indica si fechas como 01/02/2023 tienen el día o el mes primero"),
+    ("Check Against Lookup File", "Comprobar con archivo de consulta"),
+    ("Check that every value of {} appears in a column of another CSV file, like a foreign key. Blank cells are skipped.", "Comprobar que cada valor de {} aparece en una columna de otro archivo CSV, como una clave foránea. Se omiten las celdas vacías."),
+    ("Choose lookup file…", "Elegir archivo de consulta…"),
+    ("{} ({} rows)", "{} ({} filas)"),
+    ("Lookup column", "Columna de consulta"),
+    ("Check", "Comprobar"),
+    ("Every value is in the lookup file", "Todos los valores están en el archivo de consulta"),
+    ("{} rows have {} values that aren't in the lookup file:", "{} filas tienen {} valores que no están en el archivo de consulta:"),
+    ("{} rows have values that aren't in the lookup file", "{} filas tienen valores que no están en el archivo de consulta"),
+    ("Check against lookup file…", "Comprobar con archivo de consulta…"),
+    ("the byte order mark is missing", "falta la marca de orden de bytes"),
+    ("the file ends partway through a character", "el archivo termina en medio de un carácter"),
+    ("Saved and checked {} rows", "Guardado y comprobadas {} filas"),
+    ("Saved File Differs", "El archivo guardado es distinto"),
+    ("{} doesn't read back as it was written:", "{} no se lee igual a como se escribió:"),
+    ("The disk may be full or failing. Save again, to another disk if you can.", "Puede que el disco esté lleno o fallando. Guarde de nuevo, en otro disco si es posible."),
+    ("OK", "Aceptar"),
+    ("Check the file after saving", "Comprobar el archivo después de guardar"),
+    ("Read the saved file back and compare its rows, columns and values with what was written", "Volver a leer el archivo guardado y comparar sus filas, columnas y valores con lo que se escribió"),
+    ("The header row differs", "La fila de encabezado es distinta"),
+    ("{} rows were read back instead of {}", "Se leyeron {} filas en lugar de {}"),
+    ("{} rows have a different number of columns", "{} filas tienen un número distinto de columnas"),
+    ("{} rows have different values", "{} filas tienen valores distintos"),
+    ("The first difference is in record {}", "La primera diferencia está en el registro {}"),
+    ("Every row has {} fields", "Todas las filas tienen {} campos"),
+    ("{} rows don't have {} fields", "{} filas no tienen {} campos"),
+    ("Ragged Rows", "Filas irregulares"),
+    ("The header has {} columns, but {} rows have fewer fields and {} have more.", "El encabezado tiene {} columnas, pero {} filas tienen menos campos y {} tienen más."),
+    ("Short rows are padded with empty cells.", "Las filas cortas se completan con celdas vacías."),
+    ("Move the extra fields of long rows to an overflow column", "Mover los campos sobrantes de las filas largas a una columna de desbordamiento"),
+    ("Drop the extra fields of long rows", "Descartar los campos sobrantes de las filas largas"),
+    ("Row", "Fila"),
+    ("Fields", "Campos"),
+    ("Add {} empty cells", "Agregar {} celdas vacías"),
+    ("Move {} to overflow", "Mover {} al desbordamiento"),
+    ("Drop {}", "Descartar {}"),
+    ("Repair", "Reparar"),
+    ("Not now", "Ahora no"),
+    ("Repaired {} rows", "Se repararon {} filas"),
+    ("Find ragged rows", "Buscar filas irregulares"),
+    ("Rows with more or fewer fields than the header", "Filas con más o menos campos que el encabezado"),
+    ("Remove empty rows and columns", "Eliminar filas y columnas vacías"),
+    ("Delete rows where every cell is blank, and unnamed columns where every cell is blank", "Eliminar las filas en las que todas las celdas están en blanco y las columnas sin nombre en las que todas las celdas están en blanco"),
+    ("Removed {} empty rows and {} empty columns", "Se eliminaron {} filas vacías y {} columnas vacías"),
+    ("Decimal point (1,234.5)", "Punto decimal (1,234.5)"),
+    ("Decimal comma (1.234,5)", "Coma decimal (1.234,5)"),
+    ("Standardize decimal separators…", "Unificar separadores decimales…"),
+    ("Decimal separators…", "Separadores decimales…"),
+    ("Decimal Separators", "Separadores decimales"),
+    ("No numbers with a decimal or thousands separator", "No hay números con separador decimal o de miles"),
+    ("Write numbers with", "Escribir los números con"),
+    ("Decimal point", "Punto decimal"),
+    ("Decimal comma", "Coma decimal"),
+    ("Either", "Cualquiera"),
+    ("Convert from", "Convertir desde"),
+    ("This column uses both conventions", "Esta columna usa ambas convenciones"),
+    ("Numbers like 1,234 that read either way", "Números como 1,234 que se leen de las dos formas"),
+    ("Leave as is", "Dejar como está"),
+    ("Convert", "Convertir"),
+    ("Cells that aren't numbers in the chosen convention are left as they are", "Las celdas que no son números en la convención elegida se dejan como están"),
+    ("Kilometres to miles", "Kilómetros a millas"),
+    ("Miles to kilometres", "Millas a kilómetros"),
+    ("Kilograms to pounds", "Kilogramos a libras"),
+    ("Pounds to kilograms", "Libras a kilogramos"),
+    ("°C to °F", "°C a °F"),
+    ("°F to °C", "°F a °C"),
+    ("Bytes to MB (1,000,000 bytes)", "Bytes a MB (1.000.000 bytes)"),
+    ("MB to bytes", "MB a bytes"),
+    ("Multiply by a factor", "Multiplicar por un factor"),
+    ("Convert units…", "Convertir unidades…"),
+    ("Convert Units", "Convertir unidades"),
+    ("Convert the numbers in {}. Cells may end with the unit, as in 12 km.", "Convertir los números de {}. Las celdas pueden terminar con la unidad, como en 12 km."),
+    ("Conversion", "Conversión"),
+    ("Factor", "Factor"),
+    ("Decimal places", "Decimales"),
+    ("Write to a new column", "Escribir en una columna nueva"),
+    ("Name", "Nombre"),
+    ("Replace the values", "Reemplazar los valores"),
+    ("Converted {} cells", "Se convirtieron {} celdas"),
+    ("{} filled cells aren't numbers and were left out", "{} celdas con contenido no son números y se omitieron"),
+    ("Quality", "Calidad"),
+    ("Quality score {:.0}", "Puntuación de calidad {:.0}"),
+    ("Scoring quality", "Puntuando la calidad"),
+    ("Scoring…", "Puntuando…"),
+    ("Scoring the changed data…", "Puntuando los datos modificados…"),
+    ("The mean of the parts below, out of 100", "La media de las partes de abajo, sobre 100"),
+    ("Complete", "Completo"),
+    ("Cells that aren't blank", "Celdas que no están en blanco"),
+    ("Valid", "Válido"),
+    ("Filled cells that fit the column's type, as set in the schema or guessed", "Celdas con contenido que encajan con el tipo de la columna, según el esquema o deducido"),
+    ("Unique", "Único"),
+    ("Rows that don't repeat an earlier row", "Filas que no repiten una fila anterior"),
+    ("{} ({} duplicate rows)", "{} ({} filas duplicadas)"),
+    ("Meets rules", "Cumple las reglas"),
+    ("Rows meeting the schema's rules, other than type and required", "Filas que cumplen las reglas del esquema, salvo tipo y obligatorio"),
+    ("No schema", "Sin esquema"),
+    ("Score", "Puntuación"),
+    ("Go to this column", "Ir a esta columna"),
+    ("{} cells with unknown words", "{} celdas con palabras desconocidas"),
+    ("Check spelling…", "Revisar ortografía…"),
+    ("List cells of text columns with words that aren't in a dictionary", "Listar celdas de columnas de texto con palabras que no están en un diccionario"),
+    ("Checking spelling", "Revisando la ortografía"),
+    ("Word lists", "Listas de palabras"),
+    ("Spelling", "Ortografía"),
+    ("Choose dictionary…", "Elegir diccionario…"),
+    ("A text file with one word per line, or a Hunspell .dic file", "Un archivo de texto con una palabra por línea, o un archivo .dic de Hunspell"),
+    ("{} ({} words)", "{} ({} palabras)"),
+    ("Columns to check:", "Columnas a revisar:"),
+    ("Choose a dictionary and at least one column", "Elige un diccionario y al menos una columna"),
+    ("The data has changed since it was checked. Check again to update the list.", "Los datos han cambiado desde la revisión. Vuelve a comprobar para actualizar la lista."),
+    ("No unknown words", "No hay palabras desconocidas"),
+    ("Ignore", "Ignorar"),
+    ("Accept this word from now on", "Aceptar esta palabra a partir de ahora"),
+    ("International (E.164)", "Internacional (E.164)"),
+    ("National", "Nacional"),
+    ("Format phone numbers…", "Formatear números de teléfono…"),
+    ("Format Phone Numbers", "Formatear números de teléfono"),
+    ("Rewrite the phone numbers in {} in one format. Numbers without a country code are read as numbers of the default region.", "Reescribir los números de teléfono de {} en un solo formato. Los números sin código de país se leen como números de la región predeterminada."),
+    ("Default region", "Región predeterminada"),
+    ("Numbers from other countries are written in E.164.", "Los números de otros países se escriben en E.164."),
+    ("Format", "Formatear"),
+    ("Formatted {} cells", "Se formatearon {} celdas"),
+    ("{} values aren't phone numbers and were left as they are", "{} valores no son números de teléfono y se dejaron como estaban"),
+    ("Show their rows", "Mostrar sus filas"),
+    ("Normalize amounts…", "Normalizar importes…"),
+    ("Normalize Amounts", "Normalizar importes"),
+    ("Rewrite the money amounts in {} as plain numbers, without currency symbols or thousands separators.", "Reescribir los importes de {} como números simples, sin símbolos de moneda ni separadores de miles."),
+    ("Amounts are written with", "Los importes están escritos con"),
+    ("$ stands for", "$ significa"),
+    ("Put each amount's currency in a new column", "Poner la moneda de cada importe en una columna nueva"),
+    ("Normalize", "Normalizar"),
+    ("Normalized {} amounts", "Se normalizaron {} importes"),
+    ("{} values aren't amounts and were left as they are", "{} valores no son importes y se dejaron como estaban"),
+    ("As written", "Tal como está"),
+    ("Show the JSON as it is in the file rather than indented", "Mostrar el JSON tal como está en el archivo en lugar de sangrado"),
+    ("Flatten JSON…", "Aplanar JSON…"),
+    ("Copy chosen keys of the JSON in this column to columns of their own", "Copiar las claves elegidas del JSON de esta columna a columnas propias"),
+    ("Flatten JSON", "Aplanar JSON"),
+    ("Copy the chosen keys of the JSON objects in {} to new columns. Nested keys are joined with dots.", "Copiar las claves elegidas de los objetos JSON de {} a columnas nuevas. Las claves anidadas se unen con puntos."),
+    ("The column's JSON has no object keys", "El JSON de la columna no tiene claves de objeto"),
+    ("None", "Ninguna"),
+    ("Key", "Clave"),
+    ("Rows", "Filas"),
+    ("Add columns", "Añadir columnas"),
+    ("Choose keys; files paged from disk can't be edited", "Elija claves; los archivos paginados desde el disco no se pueden editar"),
+    ("Added {} columns", "Se añadieron {} columnas"),
+    ("UUID", "UUID"),
+    ("IPv4 address", "Dirección IPv4"),
+    ("ISO date", "Fecha ISO"),
+    ("ISO date and time", "Fecha y hora ISO"),
+    ("Time (24-hour)", "Hora (24 horas)"),
+    ("Email address", "Correo electrónico"),
+    ("Web address", "Dirección web"),
+    ("US ZIP code", "Código postal de EE. UU."),
+    ("Hex color", "Color hexadecimal"),
+    ("Schema saved as {}", "Esquema guardado como {}"),
+    ("Save beside file", "Guardar junto al archivo"),
+    ("Save the schema next to this file as name.schema.json, so it's loaded and the file validated whenever it's opened", "Guardar el esquema junto a este archivo como nombre.schema.json, para que se cargue y el archivo se valide cada vez que se abra"),
+    ("Pattern library", "Biblioteca de patrones"),
+    ("Named patterns to choose from for any column. The ones added here are saved with the schema.", "Patrones con nombre para elegir en cualquier columna. Los que se añaden aquí se guardan con el esquema."),
+    ("Remove pattern", "Quitar patrón"),
+    ("Add pattern", "Añadir patrón"),
+    ("Choose from the pattern library", "Elegir de la biblioteca de patrones"),
+];
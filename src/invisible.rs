@@ -0,0 +1,179 @@
+// Characters that look like nothing, or like something else, and so stop
+// values that read the same from matching: no-break and other unusual spaces,
+// zero-width and direction marks, control characters, and Cyrillic or Greek
+// letters mixed into a Latin word (a Cyrillic 'о' in "Rоme").
+
+use crate::i18n::tr;
+use crate::jobs::{Cancelled, Progress};
+use crate::table::Table;
+use std::borrow::Cow;
+
+// Rows scanned between progress updates and cancellation checks
+const SCAN_CHUNK_ROWS: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Space,
+    ZeroWidth,
+    Control,
+    LookAlike,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 4] = [Kind::Space, Kind::ZeroWidth, Kind::Control, Kind::LookAlike];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Kind::Space => "Unusual spaces",
+            Kind::ZeroWidth => "Zero-width characters",
+            Kind::Control => "Control characters",
+            Kind::LookAlike => "Look-alike letters",
+        })
+    }
+}
+
+// Cyrillic and Greek letters drawn the same as a Latin one, and that letter
+const LOOK_ALIKES: &str = "аеорсухіјѕԁһАВЕКМНОРСТХІЈЅΑΒΕΖΗΙΚΜΝΟΡΤΥΧον";
+const LATIN_LETTERS: &str = "aeopcyxijsdhABEKMHOPCTXIJSABEZHIKMNOPTYXov";
+
+// Kind of a single character; look-alikes depend on the word around them
+fn char_kind(ch: char) -> Option<Kind> {
+    match ch {
+        '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{202f}' | '\u{205f}' | '\u{3000}' => {
+            Some(Kind::Space)
+        }
+        '\u{ad}'
+        | '\u{180e}'
+        | '\u{200b}'..='\u{200f}'
+        | '\u{202a}'..='\u{202e}'
+        | '\u{2060}'..='\u{2064}'
+        | '\u{2066}'..='\u{2069}'
+        | '\u{feff}' => Some(Kind::ZeroWidth),
+        // Line breaks and tabs are ordinary in cells
+        '\n' | '\r' | '\t' => None,
+        ch if ch.is_control() => Some(Kind::Control),
+        _ => None,
+    }
+}
+
+fn look_alike(ch: char) -> Option<char> {
+    LOOK_ALIKES
+        .chars()
+        .zip(LATIN_LETTERS.chars())
+        .find(|&(from, _)| from == ch)
+        .map(|(_, to)| to)
+}
+
+// Words with both a Latin letter and a look-alike
+fn mixed_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| !ch.is_alphanumeric()).filter(|word| {
+        word.chars().any(|ch| ch.is_ascii_alphabetic())
+            && word.chars().any(|ch| look_alike(ch).is_some())
+    })
+}
+
+// The kinds found in `text`, in the order of Kind::ALL
+pub fn kinds(text: &str) -> Vec<Kind> {
+    // Printable ASCII and line breaks, by far the common case
+    if text
+        .bytes()
+        .all(|byte| (0x20..0x7f).contains(&byte) || matches!(byte, b'\n' | b'\r' | b'\t'))
+    {
+        return Vec::new();
+    }
+    let mut found = [false; 4];
+    for ch in text.chars() {
+        if let Some(kind) = char_kind(ch) {
+            found[kind as usize] = true;
+        }
+    }
+    found[Kind::LookAlike as usize] = mixed_words(text).next().is_some();
+    Kind::ALL
+        .into_iter()
+        .filter(|&kind| found[kind as usize])
+        .collect()
+}
+
+// `text` with unusual spaces shown as '·' and zero-width and control
+// characters as their code point, for display
+pub fn reveal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match char_kind(ch) {
+            Some(Kind::Space) => out.push('·'),
+            Some(_) => out.push_str(&format!("<U+{:04X}>", ch as u32)),
+            None => out.push(ch),
+        }
+    }
+    out
+}
+
+// `text` with unusual spaces made plain, zero-width and control characters
+// removed and look-alikes in Latin words replaced by the Latin letter
+pub fn clean(text: &str) -> Cow<'_, str> {
+    if kinds(text).is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        if mixed_words(word).next().is_some() {
+            out.extend(word.chars().map(|ch| look_alike(ch).unwrap_or(ch)));
+        } else {
+            out.push_str(word);
+        }
+        word.clear();
+    };
+    for ch in text.chars() {
+        match char_kind(ch) {
+            Some(Kind::Space) => {
+                flush(&mut word, &mut out);
+                out.push(' ');
+            }
+            Some(_) => {} // Dropped without ending the word it sits in
+            None if ch.is_alphanumeric() => word.push(ch),
+            None => {
+                flush(&mut word, &mut out);
+                out.push(ch);
+            }
+        }
+    }
+    flush(&mut word, &mut out);
+    Cow::Owned(out)
+}
+
+pub struct ScanReport {
+    pub counts: Vec<[usize; 4]>, // Cells by column, then Kind as usize
+    pub cells: usize,            // Cells with any kind
+    pub rows: usize,             // Rows with any such cell
+}
+
+pub fn scan(data: &Table, columns: usize, progress: &Progress) -> Result<ScanReport, Cancelled> {
+    let mut report = ScanReport {
+        counts: vec![[0; 4]; columns],
+        cells: 0,
+        rows: 0,
+    };
+    progress.set_total(data.len());
+    for start in (0..data.len()).step_by(SCAN_CHUNK_ROWS) {
+        progress.check()?;
+        let end = (start + SCAN_CHUNK_ROWS).min(data.len());
+        for idx in start..end {
+            let mut flagged = false;
+            for (col, cell) in data.row(idx).iter().enumerate().take(columns) {
+                let kinds = kinds(cell);
+                if kinds.is_empty() {
+                    continue;
+                }
+                flagged = true;
+                report.cells += 1;
+                for kind in kinds {
+                    report.counts[col][kind as usize] += 1;
+                }
+            }
+            report.rows += usize::from(flagged);
+        }
+        progress.set_done(end);
+    }
+    Ok(report)
+}
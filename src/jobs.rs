@@ -0,0 +1,112 @@
+// Background jobs.
+//
+// Loading, saving, sorting and searching a large file can take seconds, so
+// they run on worker threads. Each job shares a `Progress` with the UI: the
+// worker reports how far it got and checks for cancellation between chunks of
+// work, and the jobs panel reads both every frame.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread::JoinHandle;
+
+#[derive(Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize, // Zero while the amount of work is unknown
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn set_done(&self, done: usize) {
+        self.done.store(done, Ordering::Relaxed);
+    }
+
+    pub fn add_done(&self, amount: usize) {
+        self.done.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    // None while the total is unknown
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total() {
+            0 => None,
+            total => Some((self.done() as f32 / total as f32).min(1.0)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Lets workers bail out with `?` once the job has been cancelled
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+pub struct Job<T> {
+    pub label: String,
+    progress: Arc<Progress>,
+    handle: JoinHandle<T>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    pub fn spawn<F>(label: String, work: F) -> Self
+    where
+        F: FnOnce(&Progress) -> T + Send + 'static,
+    {
+        let progress = Arc::new(Progress::default());
+        let worker_progress = Arc::clone(&progress);
+        let handle = std::thread::spawn(move || work(&worker_progress));
+        Job {
+            label,
+            progress,
+            handle,
+        }
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    // None if the worker panicked
+    pub fn join(self) -> Option<T> {
+        self.handle.join().ok()
+    }
+}
@@ -0,0 +1,426 @@
+// A JSON reader for the small metadata files that travel with CSVs, such as
+// Table Schemas, and for JSON held in cells. Objects keep their keys in file
+// order. Exports write JSON by hand, with export::json_string for strings;
+// `pretty` and `compact` write back values read here.
+
+use crate::export::json_string;
+
+// Rows looked at to tell whether a column holds JSON
+const DETECT_ROWS: usize = 1000;
+
+// Arrays and objects inside one another, so a cell of brackets can't run the
+// parser out of stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String), // As written, so large integers and exponents come back unchanged
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            text: text.strip_prefix('\u{feff}').unwrap_or(text),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_space();
+        if parser.pos < parser.text.len() {
+            return Err(parser.error("unexpected text after the end"));
+        }
+        Ok(value)
+    }
+
+    // An object or array in a cell; other JSON values read as plain text
+    pub fn from_cell(cell: &str) -> Option<Json> {
+        let cell = cell.trim();
+        if !(cell.starts_with('{') && cell.ends_with('}') || cell.starts_with('[') && cell.ends_with(']')) {
+            return None;
+        }
+        Json::parse(cell).ok()
+    }
+
+    // None unless this is an object with `key`
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    // Numbers and strings as they would be typed into a text field
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            Json::Number(number) => Some(number.clone()),
+            Json::String(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    // The value at a path of object keys, as listed by `leaf_paths`
+    pub fn at(&self, path: &[String]) -> Option<&Json> {
+        path.iter().try_fold(self, |value, key| value.get(key))
+    }
+
+    // Paths of keys to the values of nested objects that aren't objects
+    // themselves. Arrays are values, not followed into.
+    pub fn leaf_paths(&self) -> Vec<Vec<String>> {
+        let mut paths = Vec::new();
+        if let Json::Object(entries) = self {
+            for (key, value) in entries {
+                match value {
+                    Json::Object(_) => paths.extend(value.leaf_paths().into_iter().map(|path| [vec![key.clone()], path].concat())),
+                    _ => paths.push(vec![key.clone()]),
+                }
+            }
+        }
+        paths
+    }
+
+    // The value for a cell: text unquoted, null blank, arrays and objects as
+    // compact JSON
+    pub fn cell_text(&self) -> String {
+        match self {
+            Json::Null => String::new(),
+            Json::Bool(value) => value.to_string(),
+            Json::Number(number) => number.clone(),
+            Json::String(text) => text.clone(),
+            Json::Array(_) | Json::Object(_) => self.compact(),
+        }
+    }
+
+    pub fn compact(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, None);
+        out
+    }
+
+    // Indented by two spaces per level
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, Some(0));
+        out
+    }
+
+    // `indent` is the current level, or None for compact output
+    fn write(&self, out: &mut String, indent: Option<usize>) {
+        let newline = |out: &mut String, level: usize| {
+            if indent.is_some() {
+                out.push('\n');
+                out.push_str(&"  ".repeat(level));
+            }
+        };
+        let level = indent.unwrap_or(0);
+        let inner = indent.map(|level| level + 1);
+        match self {
+            Json::Array(items) if !items.is_empty() => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, level + 1);
+                    item.write(out, inner);
+                }
+                newline(out, level);
+                out.push(']');
+            }
+            Json::Object(entries) if !entries.is_empty() => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, level + 1);
+                    out.push_str(&json_string(key));
+                    out.push_str(if indent.is_some() { ": " } else { ":" });
+                    value.write(out, inner);
+                }
+                newline(out, level);
+                out.push('}');
+            }
+            Json::Array(_) => out.push_str("[]"),
+            Json::Object(_) => out.push_str("{}"),
+            Json::String(text) => out.push_str(&json_string(text)),
+            Json::Null => out.push_str("null"),
+            other => out.push_str(&other.cell_text()),
+        }
+    }
+}
+
+// Whether at least four in five of the filled cells among the first rows are
+// JSON objects or arrays
+pub fn is_json_column<'c>(cells: impl Iterator<Item = &'c str>) -> bool {
+    let (mut filled, mut json) = (0, 0);
+    for cell in cells.take(DETECT_ROWS).filter(|cell| !cell.trim().is_empty()) {
+        filled += 1;
+        if Json::from_cell(cell).is_some() {
+            json += 1;
+        }
+    }
+    json > 0 && json * 5 >= filled * 4
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize, // In bytes
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        let line = self.text[..self.pos.min(self.text.len())].matches('\n').count() + 1;
+        format!("{} on line {}", message, line)
+    }
+
+    fn skip_space(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_space();
+        if self.peek() != Some(byte) {
+            return Err(self.error(&format!("expected '{}'", char::from(byte))));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_space();
+        match self.peek() {
+            Some(open @ (b'{' | b'[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("arrays and objects are nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if open == b'{' { self.object() } else { self.array() };
+                self.depth -= 1;
+                value
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("text ends where a value was expected")),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        if !self.text[self.pos..].starts_with(word) {
+            return Err(self.error("expected a value"));
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        let number = &self.text[start..self.pos];
+        if !crate::export::is_json_number(number) {
+            return Err(self.error(&format!("{} is not a number", number)));
+        }
+        Ok(Json::Number(number.to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(end) = rest.find(['"', '\\']) else {
+                return Err(self.error("unclosed string"));
+            };
+            out.push_str(&rest[..end]);
+            self.pos += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Ok(out);
+            }
+            let escape = self.peek().ok_or_else(|| self.error("unclosed string"))?;
+            self.pos += 1;
+            match escape {
+                b'"' => out.push('"'),
+                b'\\' => out.push('\\'),
+                b'/' => out.push('/'),
+                b'b' => out.push('\u{8}'),
+                b'f' => out.push('\u{c}'),
+                b'n' => out.push('\n'),
+                b'r' => out.push('\r'),
+                b't' => out.push('\t'),
+                b'u' => {
+                    let mut code = self.hex4()?;
+                    if (0xD800..0xDC00).contains(&code) && self.text[self.pos..].starts_with("\\u") {
+                        let after_high = self.pos;
+                        self.pos += 2;
+                        let low = self.hex4()?;
+                        if (0xDC00..0xE000).contains(&low) {
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        } else {
+                            // Not a pair, so the next escape is read on its own
+                            self.pos = after_high;
+                        }
+                    }
+                    // Lone surrogates aren't characters
+                    out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                }
+                _ => return Err(self.error("unknown escape in string")),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.text.get(self.pos..self.pos + 4).filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        let digits = digits.ok_or_else(|| self.error("bad \\u escape"))?;
+        let code = u32::from_str_radix(digits, 16).map_err(|_| self.error("bad \\u escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_space();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_space();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        self.skip_space();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_space();
+            let key = self.string()?;
+            self.expect(b':')?;
+            entries.push((key, self.value()?));
+            self.skip_space();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surrogates() {
+        assert_eq!(Json::parse(r#""\ud83d\ude00""#), Ok(Json::String("😀".to_string())));
+        assert_eq!(Json::parse(r#""\uD834\uDD1E""#), Ok(Json::String("𝄞".to_string())));
+        assert_eq!(Json::parse(r#""\ud83dx""#), Ok(Json::String("\u{fffd}x".to_string())));
+        assert_eq!(Json::parse(r#""\ud83dA""#), Ok(Json::String("\u{fffd}A".to_string())));
+        assert_eq!(Json::parse(r#""\ude00""#), Ok(Json::String("\u{fffd}".to_string())));
+        assert!(Json::parse(r#""\u+041""#).is_err());
+        assert!(Json::parse(r#""\u00é""#).is_err());
+    }
+
+    #[test]
+    fn numbers_round_trip_as_written() {
+        let text = r#"[1e400,12345678901234567890123,-0.10,2E-3]"#;
+        let value = Json::parse(text).unwrap();
+        assert_eq!(value.compact(), text);
+        assert_eq!(value.as_array()[0].cell_text(), "1e400");
+        for bad in ["01", "1.", "-", "1e", "+1", "0x10", "1.2.3"] {
+            assert!(Json::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn keys_with_dots() {
+        let value = Json::from_cell(r#"{"a.b": 1, "a": {"b": 2, "c": {"d.e": [3]}}}"#).unwrap();
+        let paths = value.leaf_paths();
+        let key = |path: &[&str]| path.iter().map(|part| part.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, [key(&["a.b"]), key(&["a", "b"]), key(&["a", "c", "d.e"])]);
+        assert_eq!(value.at(&paths[0]).map(Json::cell_text), Some("1".to_string()));
+        assert_eq!(value.at(&paths[1]).map(Json::cell_text), Some("2".to_string()));
+        assert_eq!(value.at(&paths[2]).map(Json::cell_text), Some("[3]".to_string()));
+    }
+
+    #[test]
+    fn error_lines() {
+        assert_eq!(Json::parse("{\n  \"a\": 1,\n  \"b\": x\n}"), Err("expected a value on line 3".to_string()));
+        assert_eq!(Json::parse("[1,\n2\n"), Err("expected ',' or ']' on line 3".to_string()));
+        assert_eq!(Json::parse("\"open"), Err("unclosed string on line 1".to_string()));
+        assert_eq!(Json::parse("{} x"), Err("unexpected text after the end on line 1".to_string()));
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert!(Json::parse(&deep).is_err());
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn cells() {
+        assert!(Json::from_cell("42").is_none());
+        assert!(Json::from_cell("{broken").is_none());
+        assert!(is_json_column(["[1]", "{}", "", "{\"a\":1}", "[]", "x"].into_iter()));
+        assert!(!is_json_column(["[1]", "x", "y"].into_iter()));
+        let value = Json::parse(r#"{"a":[1,{"b":null}],"c":{}}"#).unwrap();
+        assert_eq!(value.pretty(), "{\n  \"a\": [\n    1,\n    {\n      \"b\": null\n    }\n  ],\n  \"c\": {}\n}");
+    }
+}
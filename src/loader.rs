@@ -0,0 +1,244 @@
+// Reading CSV files into a `Table`.
+//
+// Large files are parsed in parallel: the body is read in slabs that end on
+// record boundaries, each slab is split into chunks that do too, each chunk is
+// parsed on a rayon worker, and the chunk tables are stitched back together in
+// file order. Only one slab of raw bytes is held at a time. Progress is
+// reported in bytes parsed.
+//
+// Bytes that aren't valid UTF-8 don't stop a load. Each bad sequence becomes
+// U+FFFD in the table, and the cell's original bytes are returned alongside so
+// they can be listed and written back unchanged.
+//
+// Records with more or fewer fields than the header are kept as they are, so
+// a ragged file still opens and can be repaired.
+
+use crate::jobs::Progress;
+use crate::table::Table;
+use csv::{ByteRecord, ReaderBuilder};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+// Slabs smaller than this are parsed as a single chunk
+const PARALLEL_THRESHOLD: usize = 4 * 1024 * 1024;
+
+// Bytes read from disk at a time, plus the end of a record cut off
+const SLAB_BYTES: usize = 64 * 1024 * 1024;
+
+// A cell whose bytes aren't valid UTF-8
+#[derive(Clone, Debug)]
+pub struct InvalidCell {
+    pub row: Option<usize>, // Data index, or None for the header
+    pub col: usize,
+    pub bytes: Vec<u8>,
+}
+
+// Header, rows and the cells that weren't UTF-8
+pub type ParsedCsv = (Vec<String>, Table, Vec<InvalidCell>);
+
+pub fn read_csv_with_header(file_path: &str, progress: &Progress) -> Result<ParsedCsv, Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(file_path)?;
+    let mut invalid = Vec::new();
+    let header = lossy_header(rdr.byte_headers()?, &mut invalid);
+    let body_start = rdr.position().byte();
+    drop(rdr);
+
+    let mut file = File::open(file_path)?;
+    progress.set_total(file.metadata()?.len() as usize);
+    file.seek(SeekFrom::Start(body_start))?;
+    let mut records = Table::new();
+    let mut slab = Vec::new();
+    loop {
+        let read = (&mut file).take(SLAB_BYTES as u64).read_to_end(&mut slab)?;
+        let at_end = read == 0;
+        // Without a complete record yet, read on
+        let end = if at_end { slab.len() } else { record_ends(&slab).last().unwrap_or(0) };
+        if end > 0 {
+            let rest = slab.split_off(end);
+            parse_slab(&slab, &mut records, &mut invalid, progress)?;
+            slab = rest;
+        }
+        if at_end {
+            break;
+        }
+    }
+    records.intern_low_cardinality_columns();
+    Ok((header, records, invalid))
+}
+
+// Parses `slab`, which holds whole records, in parallel chunks and appends its
+// rows to `records`
+fn parse_slab(slab: &[u8], records: &mut Table, invalid: &mut Vec<InvalidCell>, progress: &Progress) -> Result<(), Box<dyn Error>> {
+    let parts = if slab.len() < PARALLEL_THRESHOLD {
+        1
+    } else {
+        rayon::current_num_threads()
+    };
+    let bounds = record_boundaries(slab, parts);
+    let chunks: Vec<&[u8]> = bounds.windows(2).map(|w| &slab[w[0]..w[1]]).collect();
+    let tables = chunks
+        .par_iter()
+        .map(|chunk| -> Result<(Table, Vec<InvalidCell>), Box<dyn Error + Send + Sync>> {
+            progress.check()?;
+            let parsed = parse_chunk(chunk)?;
+            progress.add_done(chunk.len());
+            Ok(parsed)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| err as Box<dyn Error>)?;
+
+    for (table, chunk_invalid) in tables {
+        let offset = records.len();
+        invalid.extend(chunk_invalid.into_iter().map(|cell| InvalidCell {
+            row: cell.row.map(|row| row + offset),
+            ..cell
+        }));
+        records.append(table);
+    }
+    Ok(())
+}
+
+// Read at most `max_rows` records, streaming from disk so only the start of a
+// huge file is touched. The flag is set when rows were left unread.
+pub fn read_csv_preview(file_path: &str, max_rows: usize) -> Result<(ParsedCsv, bool), Box<dyn Error>> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_path(file_path)?;
+    let mut invalid = Vec::new();
+    let header = lossy_header(rdr.byte_headers()?, &mut invalid);
+    let mut record = ByteRecord::new();
+    let mut records = Table::new();
+    while records.len() < max_rows && rdr.read_byte_record(&mut record)? {
+        push_lossy(&mut records, &record, &mut invalid);
+    }
+    let truncated = rdr.read_byte_record(&mut record)?;
+    records.intern_low_cardinality_columns();
+    Ok(((header, records, invalid), truncated))
+}
+
+fn parse_chunk(chunk: &[u8]) -> csv::Result<(Table, Vec<InvalidCell>)> {
+    let mut rdr = ReaderBuilder::new().has_headers(false).flexible(true).from_reader(chunk);
+    let mut record = ByteRecord::new();
+    let mut table = Table::new();
+    let mut invalid = Vec::new();
+    while rdr.read_byte_record(&mut record)? {
+        push_lossy(&mut table, &record, &mut invalid);
+    }
+    Ok((table, invalid))
+}
+
+fn lossy_header(record: &ByteRecord, invalid: &mut Vec<InvalidCell>) -> Vec<String> {
+    let mut header = Vec::with_capacity(record.len());
+    for (col, field) in record.iter().enumerate() {
+        let name = String::from_utf8_lossy(field);
+        if let Cow::Owned(_) = name {
+            invalid.push(InvalidCell {
+                row: None,
+                col,
+                bytes: field.to_vec(),
+            });
+        }
+        header.push(name.into_owned());
+    }
+    header
+}
+
+// Add `record` as the table's next row, noting the cells that aren't UTF-8
+fn push_lossy(table: &mut Table, record: &ByteRecord, invalid: &mut Vec<InvalidCell>) {
+    let row = table.len();
+    let mut cells = Vec::with_capacity(record.len());
+    for (col, field) in record.iter().enumerate() {
+        let cell = String::from_utf8_lossy(field);
+        if let Cow::Owned(_) = cell {
+            invalid.push(InvalidCell {
+                row: Some(row),
+                col,
+                bytes: field.to_vec(),
+            });
+        }
+        cells.push(cell);
+    }
+    table.push_row(cells.iter());
+}
+
+// Offsets just past each newline in `data` that ends a record, `data` starting
+// at the start of one. As in the csv crate, a quote only opens a quoted field
+// at the start of the field; elsewhere, like in 5" screen, it's a plain
+// character. Inside quotes, "" is an escaped quote.
+fn record_ends(data: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    let mut in_quotes = false;
+    let mut field_start = true;
+    let mut escaped = false;
+    data.iter().enumerate().filter_map(move |(idx, &byte)| {
+        if in_quotes {
+            if escaped {
+                escaped = false;
+            } else if byte == b'"' {
+                if data.get(idx + 1) == Some(&b'"') {
+                    escaped = true;
+                } else {
+                    in_quotes = false;
+                }
+            }
+            return None;
+        }
+        let starts_quote = byte == b'"' && field_start;
+        in_quotes = starts_quote;
+        field_start = matches!(byte, b',' | b'\n' | b'\r');
+        (byte == b'\n').then_some(idx + 1)
+    })
+}
+
+// Offsets splitting `data` into about `parts` chunks. A chunk only ends where a
+// record does, so no record straddles two chunks.
+fn record_boundaries(data: &[u8], parts: usize) -> Vec<usize> {
+    let target = (data.len() / parts.max(1)).max(1);
+    let mut bounds = vec![0];
+    let mut next = target;
+    for end in record_ends(data) {
+        if end >= next {
+            bounds.push(end);
+            next = end + target;
+        }
+    }
+    if bounds.last() != Some(&data.len()) {
+        bounds.push(data.len());
+    }
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Rows of every chunk, in order
+    fn parse_in_chunks(data: &[u8], parts: usize) -> Vec<Vec<String>> {
+        let bounds = record_boundaries(data, parts);
+        let mut rows = Vec::new();
+        for w in bounds.windows(2) {
+            let (table, _) = parse_chunk(&data[w[0]..w[1]]).unwrap();
+            rows.extend(table.rows().map(|row| row.iter().map(str::to_string).collect::<Vec<_>>()));
+        }
+        rows
+    }
+
+    #[test]
+    fn stray_quote_does_not_split_quoted_field() {
+        let record = b"1,5\" screen,\"first line\nsecond, \"\"quoted\"\" line\"\n";
+        let data: Vec<u8> = record.iter().copied().cycle().take(record.len() * 200).collect();
+        for parts in [1, 3, 7, 50] {
+            let rows = parse_in_chunks(&data, parts);
+            assert_eq!(rows.len(), 200, "{} parts", parts);
+            for row in rows {
+                assert_eq!(row, ["1", "5\" screen", "first line\nsecond, \"quoted\" line"]);
+            }
+        }
+    }
+
+    #[test]
+    fn record_ends_skip_newlines_in_quotes() {
+        let ends: Vec<usize> = record_ends(b"a,\"x\ny\"\nb\"c,d\n").collect();
+        assert_eq!(ends, [8, 14]);
+    }
+}
@@ -1,344 +1,6863 @@
-use csv::Reader;
-use eframe::egui::{self, Color32, FontFamily, FontId};
-use egui_extras::{Column, TableBuilder};
+// No console window behind the app when it's opened from Explorer
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use eframe::egui;
+use eframe::egui::panel::Side;
+use egui_extras::{Column, TableBuilder, TableRow};
+use rayon::prelude::*;
 use rfd::FileDialog;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use cli::LaunchArgs;
+use config::Config;
+use decimals::{ColumnConventions, Convention};
+use encoding::{Encoding, EncodingWriter};
+use export::{CopyFormat, CsvOptions, ExportFormat, ExportSettings, RowTemplate, SqlDialect};
+use i18n::{Language, tr};
+use invisible::{Kind, ScanReport};
+use json::Json;
+use jobs::{Job, Progress};
+use loader::{InvalidCell, read_csv_preview, read_csv_with_header};
+use pager::DiskPager;
+use palette::{Command, Entry, Outcome, Palette};
+use panels::{Panel, PanelLayout};
+use schema::{ColumnRule, ColumnType, HeaderCheck, Schema, ValidationReport};
+use search::SearchRequest;
+use shortcuts::{Action, Shortcuts};
+use table::{Row, Table};
+use units::Conversion;
+use theme::{Density, Theme, ThemeMode};
+use view_state::ViewState;
+
+mod a11y;
+mod cli;
+mod config;
+mod currency;
+mod dates;
+mod decimals;
+mod encoding;
+mod export;
+mod expr;
+#[macro_use]
+mod i18n;
+mod invisible;
+mod jobs;
+mod json;
+mod loader;
+mod pager;
+mod palette;
+mod panels;
+mod pdf;
+mod phones;
+mod pii;
+mod pseudonym;
+mod quality;
+mod pattern;
+mod row_index;
+mod schema;
+mod search;
+mod sha256;
+mod shortcuts;
+mod spelling;
+mod table;
+mod units;
+mod theme;
+mod view_state;
+
+// Rows and columns of a table to write, by index. `None` writes all of them
+// in file order.
+#[derive(Default)]
+struct Selection {
+    rows: Option<Arc<Vec<usize>>>,
+    columns: Option<Vec<usize>>,
+}
+
+impl Selection {
+    fn row_count(&self, data: &Table) -> usize {
+        self.rows.as_ref().map_or(data.len(), |rows| rows.len())
+    }
+
+    fn rows<'a>(&'a self, data: &'a Table) -> Box<dyn Iterator<Item = Row<'a>> + 'a> {
+        match &self.rows {
+            Some(rows) => Box::new(rows.iter().map(|&idx| data.row(idx))),
+            None => Box::new(data.rows()),
+        }
+    }
+}
+
+// Original bytes of cells that weren't UTF-8, by data index and column
+type RawCells = HashMap<(usize, usize), Vec<u8>>;
+
+// The table with the masked columns redacted, or the table itself when none
+// are. Run on the job thread, as it copies every row.
+fn masked_data(data: Arc<Table>, masked: &[usize]) -> Arc<Table> {
+    if masked.is_empty() { data } else { Arc::new(pii::mask_table(&data, masked)) }
+}
+
+// Rows per chunk between flushes, progress updates and cancellation checks
+const SAVE_CHUNK_ROWS: usize = 10_000;
+const SAVE_BUFFER_BYTES: usize = 1 << 20;
+
+// Write the table next to `path` first and rename it into place once it is
+// complete, so a crash, full disk or cancelled save never leaves a truncated
+// file behind. Returns false if the save was cancelled.
+fn save_file(
+    path: &str,
+    format: ExportFormat,
+    header: &[String],
+    data: &Table,
+    selection: &Selection,
+    raw: Option<&RawCells>,
+    progress: &Progress,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let tmp_path = format!("{}.tmp", path);
+    let written = match format {
+        ExportFormat::Csv(options) => write_csv(&tmp_path, &options, header, data, selection, raw, progress),
+        ExportFormat::Pdf { landscape } => pdf::write_pdf(&tmp_path, landscape, header, data, selection, progress),
+        _ => export::write_text(&tmp_path, format, header, data, selection, progress),
+    };
+    let result = written.and_then(|completed| {
+        if completed {
+            std::fs::rename(&tmp_path, path)?;
+        }
+        Ok(completed)
+    });
+    if !matches!(result, Ok(true)) {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
 
-fn read_csv_with_header(file_path: &str) -> Result<(Vec<String>, Vec<Vec<String>>), Box<dyn Error>> {
-    let mut rdr = Reader::from_path(file_path)?;
-    let header = rdr.headers()?.iter().map(|s| s.to_string()).collect();
-    let mut records = Vec::new();
-    for result in rdr.records() {
-        let record = result?;
-        records.push(record.iter().map(|s| s.to_string()).collect());
+// Rows are written straight from the table's buffer, one at a time, so an
+// export never holds a second copy of the data. Every SAVE_CHUNK_ROWS rows the
+// buffer is flushed, progress is published and cancellation is checked. Cells
+// in `raw` are written as their original bytes.
+fn write_csv(
+    path: &str,
+    options: &CsvOptions,
+    header: &[String],
+    data: &Table,
+    selection: &Selection,
+    raw: Option<&RawCells>,
+    progress: &Progress,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    progress.set_total(selection.row_count(data));
+    let mut wtr = csv::WriterBuilder::new()
+        .buffer_capacity(SAVE_BUFFER_BYTES)
+        .delimiter(options.delimiter)
+        .quote_style(if options.quote_all { csv::QuoteStyle::Always } else { csv::QuoteStyle::Necessary })
+        .terminator(if options.crlf { csv::Terminator::CRLF } else { csv::Terminator::Any(b'\n') })
+        .from_writer(EncodingWriter::new(File::create(path)?, options.encoding)?);
+    if options.header {
+        match &selection.columns {
+            Some(columns) => wtr.write_record(columns.iter().map(|&col| header[col].as_str()))?,
+            None => wtr.write_record(header)?,
+        }
+    }
+    for (idx, row) in selection.rows(data).enumerate() {
+        let data_idx = selection.rows.as_ref().map_or(idx, |rows| rows[idx]);
+        let field = |col: usize| match raw.and_then(|raw| raw.get(&(data_idx, col))) {
+            Some(bytes) => bytes.as_slice(),
+            None => row.get(col).unwrap_or("").as_bytes(),
+        };
+        match &selection.columns {
+            Some(columns) => wtr.write_record(columns.iter().map(|&col| field(col)))?,
+            None => wtr.write_record((0..row.len()).map(field))?,
+        }
+        if (idx + 1) % SAVE_CHUNK_ROWS == 0 {
+            wtr.flush()?;
+            progress.set_done(idx + 1);
+            if progress.is_cancelled() {
+                return Ok(false);
+            }
+        }
     }
-    Ok((header, records))
+    wtr.flush()?;
+    let file = wtr.into_inner().map_err(|err| err.into_error())?.into_inner()?;
+    file.sync_all()?;
+    progress.set_done(selection.row_count(data));
+    Ok(true)
 }
 
-fn save_csv(
+// Read a saved CSV back and compare it, record by record, with what
+// write_csv wrote: the number of rows, each row's number of columns and every
+// value. Returns the rows checked, or what differs.
+fn verify_csv(
     path: &str,
-    header: &Vec<String>,
-    data: &Vec<Vec<String>>,
-) -> Result<(), Box<dyn std::error::Error>> {
+    options: &CsvOptions,
+    header: &[String],
+    data: &Table,
+    selection: &Selection,
+    raw: Option<&RawCells>,
+    progress: &Progress,
+) -> Result<usize, String> {
+    let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+    let bytes = encoding::decode(bytes, options.encoding)?;
+    let mut records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .from_reader(bytes.as_slice())
+        .into_byte_records();
+    let mut next_record = || records.next().transpose().map_err(|err| err.to_string());
+    if options.header {
+        let expected: Vec<&str> = match &selection.columns {
+            Some(columns) => columns.iter().map(|&col| header[col].as_str()).collect(),
+            None => header.iter().map(String::as_str).collect(),
+        };
+        let matches = next_record()?.is_some_and(|record| record.iter().eq(expected.iter().map(|name| name.as_bytes())));
+        if !matches {
+            return Err(tr("The header row differs").to_string());
+        }
+    }
+    let expected_rows = selection.row_count(data);
+    progress.set_total(expected_rows);
+    let (mut rows, mut wrong_width, mut wrong_values, mut first_wrong) = (0, 0, 0, None);
+    for (idx, row) in selection.rows(data).enumerate() {
+        let Some(record) = next_record()? else {
+            break;
+        };
+        rows += 1;
+        if rows % SAVE_CHUNK_ROWS == 0 {
+            progress.set_done(rows);
+        }
+        let data_idx = selection.rows.as_ref().map_or(idx, |rows| rows[idx]);
+        let field = |col: usize| match raw.and_then(|raw| raw.get(&(data_idx, col))) {
+            Some(bytes) => bytes.as_slice(),
+            None => row.get(col).unwrap_or("").as_bytes(),
+        };
+        let expected: Vec<&[u8]> = match &selection.columns {
+            Some(columns) => columns.iter().map(|&col| field(col)).collect(),
+            None => (0..row.len()).map(field).collect(),
+        };
+        if record.len() != expected.len() {
+            wrong_width += 1;
+        } else if !record.iter().eq(expected.iter().copied()) {
+            wrong_values += 1;
+        } else {
+            continue;
+        }
+        first_wrong.get_or_insert(idx + 1 + usize::from(options.header));
+    }
+    while next_record()?.is_some() {
+        rows += 1;
+    }
+    let mut problems = Vec::new();
+    if rows != expected_rows {
+        problems.push(tr!("{} rows were read back instead of {}", rows, expected_rows));
+    }
+    if wrong_width > 0 {
+        problems.push(tr!("{} rows have a different number of columns", wrong_width));
+    }
+    if wrong_values > 0 {
+        problems.push(tr!("{} rows have different values", wrong_values));
+    }
+    if let Some(line) = first_wrong {
+        problems.push(tr!("The first difference is in record {}", line));
+    }
+    if problems.is_empty() { Ok(rows) } else { Err(problems.join(". ")) }
+}
+
+// Shared by the Save options window and the Export menu. Returns whether
+// anything changed.
+fn csv_options_ui(ui: &mut egui::Ui, options: &mut CsvOptions) -> bool {
+    let before = *options;
+    egui::Grid::new("csv_options").num_columns(2).show(ui, |ui| {
+        ui.label(tr("Delimiter"));
+        egui::ComboBox::from_id_source("csv_delimiter")
+            .selected_text(CsvOptions::delimiter_label(options.delimiter))
+            .show_ui(ui, |ui| {
+                for delimiter in CsvOptions::DELIMITERS {
+                    ui.selectable_value(&mut options.delimiter, delimiter, CsvOptions::delimiter_label(delimiter));
+                }
+            });
+        ui.end_row();
+
+        ui.label(tr("Quotes"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut options.quote_all, false, tr("When needed"));
+            ui.radio_value(&mut options.quote_all, true, tr("Every field"));
+        });
+        ui.end_row();
+
+        ui.label(tr("Line endings"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut options.crlf, false, "LF");
+            ui.radio_value(&mut options.crlf, true, tr("CRLF (Windows)"));
+        });
+        ui.end_row();
+
+        ui.label(tr("Encoding"));
+        egui::ComboBox::from_id_source("csv_encoding")
+            .selected_text(options.encoding.label())
+            .show_ui(ui, |ui| {
+                for encoding in Encoding::ALL {
+                    ui.selectable_value(&mut options.encoding, encoding, encoding.label());
+                }
+            });
+        ui.end_row();
+
+        ui.label("");
+        ui.checkbox(&mut options.header, tr("Include header row"));
+        ui.end_row();
+
+        ui.label("");
+        ui.add_enabled(
+            options.encoding == Encoding::Utf8,
+            egui::Checkbox::new(&mut options.keep_invalid_bytes, tr("Keep bytes that aren't UTF-8")),
+        )
+        .on_hover_text(tr("Write cells that had invalid UTF-8 when the file was opened back with their original bytes, unless they've been changed"));
+        ui.end_row();
+
+        ui.label("");
+        ui.checkbox(&mut options.verify, tr("Check the file after saving"))
+            .on_hover_text(tr("Read the saved file back and compare its rows, columns and values with what was written"));
+        ui.end_row();
+    });
+    *options != before
+}
+
+// Several exports in one job, stopping at the first error or cancellation
+fn save_files(
+    files: Vec<(String, Selection)>,
+    format: &ExportFormat,
+    header: &[String],
+    data: &Table,
+    progress: &Progress,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    for (path, selection) in files {
+        if !save_file(&path, format.clone(), header, data, &selection, None, progress)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// One file per distinct value of `col` in the view, in order of first
+// appearance, named by filling `{column}`, `{value}` and `{n}` into the
+// template. Names that come out the same get a number added.
+fn split_by_value(
+    dir: &Path,
+    template: &str,
+    col: usize,
+    header: &[String],
+    data: &Table,
+    view: &Selection,
+) -> Vec<(String, Selection)> {
+    let indices: Vec<usize> = match &view.rows {
+        Some(rows) => rows.to_vec(),
+        None => (0..data.len()).collect(),
+    };
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    let mut group_of: HashMap<&str, usize> = HashMap::new();
+    for idx in indices {
+        let value = data.row(idx).get(col).unwrap_or("");
+        let group = *group_of.entry(value).or_insert_with(|| {
+            groups.push((value, Vec::new()));
+            groups.len() - 1
+        });
+        groups[group].1.push(idx);
+    }
+    let mut used = HashSet::new();
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(n, (value, rows))| {
+            let value = if value.is_empty() { "blank" } else { value };
+            let name = template
+                .replace("{column}", &file_name_part(&header[col]))
+                .replace("{value}", &file_name_part(value))
+                .replace("{n}", &format!("{:03}", n + 1));
+            let path = unique_path(dir, &name, &mut used);
+            let selection = Selection {
+                rows: Some(Arc::new(rows)),
+                columns: view.columns.clone(),
+            };
+            (path, selection)
+        })
+        .collect()
+}
+
+// The view in files of at most `chunk_rows` rows each, named like `path`
+// with _001, _002... before the extension. Every chunk repeats the header.
+fn split_into_chunks(path: &Path, chunk_rows: usize, data: &Table, view: &Selection) -> Vec<(String, Selection)> {
+    let indices: Vec<usize> = match &view.rows {
+        Some(rows) => rows.to_vec(),
+        None => (0..data.len()).collect(),
+    };
+    let chunk_count = indices.len().div_ceil(chunk_rows).max(1);
+    let digits = chunk_count.to_string().len().max(3);
+    let stem = path.file_stem().map_or_else(|| "data".into(), |stem| stem.to_string_lossy());
+    let extension = path.extension().map_or_else(String::new, |extension| format!(".{}", extension.to_string_lossy()));
+    let mut chunks: Vec<&[usize]> = indices.chunks(chunk_rows).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(n, rows)| {
+            let name = format!("{}_{:0width$}{}", stem, n + 1, extension, width = digits);
+            let selection = Selection {
+                rows: Some(Arc::new(rows.to_vec())),
+                columns: view.columns.clone(),
+            };
+            (path.with_file_name(name).to_string_lossy().into_owned(), selection)
+        })
+        .collect()
+}
+
+// Characters that aren't allowed in file names on some system become '_'
+fn file_name_part(text: &str) -> String {
+    text.trim()
+        .chars()
+        .map(|ch| if ch.is_control() || "/\\:*?\"<>|".contains(ch) { '_' } else { ch })
+        .collect()
+}
+
+// `name` in `dir`, with _2, _3... before the extension if it was already
+// used. Compared ignoring case, for case-insensitive file systems.
+fn unique_path(dir: &Path, name: &str, used: &mut HashSet<String>) -> String {
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    let mut candidate = name.to_string();
+    let mut copy = 1;
+    while !used.insert(candidate.to_lowercase()) {
+        copy += 1;
+        candidate = format!("{}_{}{}", stem, copy, extension);
+    }
+    dir.join(candidate).to_string_lossy().into_owned()
+}
+
+// New names for the second and later columns of each repeated name: name_2,
+// name_3 and so on, skipping names already in use
+fn disambiguate_header(header: &[String]) -> Vec<(usize, String)> {
+    let mut used: HashSet<String> = header.iter().cloned().collect();
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut renames = Vec::new();
+    for (col, name) in header.iter().enumerate() {
+        let count = seen.entry(name).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            continue;
+        }
+        let mut suffix = *count;
+        let new = loop {
+            let candidate = format!("{}_{}", name, suffix);
+            if !used.contains(&candidate) {
+                break candidate;
+            }
+            suffix += 1;
+        };
+        used.insert(new.clone());
+        renames.push((col, new));
+    }
+    renames
+}
+
+// The first two columns of a CSV file with a header row
+fn read_pairs(path: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new().flexible(true).from_path(path)?;
+    let mut pairs = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        if let (Some(from), Some(to)) = (record.get(0), record.get(1)) {
+            pairs.push((from.to_string(), to.to_string()));
+        }
+    }
+    Ok(pairs)
+}
+
+// A two-column CSV file of value pairs, such as a mapping
+fn write_pairs(path: &Path, header: [&str; 2], pairs: &[(String, String)]) -> Result<(), Box<dyn Error>> {
     let mut wtr = csv::Writer::from_path(path)?;
     wtr.write_record(header)?;
-    for row in data {
-        wtr.write_record(row)?;
+    for (from, to) in pairs {
+        wtr.write_record([from, to])?;
     }
     wtr.flush()?;
     Ok(())
 }
 
+// Last component of a path, for labels
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|name| name.to_str()).unwrap_or(path)
+}
+
+fn pick_csv_file() -> Option<String> {
+    let path = FileDialog::new().add_filter("CSV", &["csv"]).pick_file()?;
+    let path_str = path.to_str();
+    if path_str.is_none() {
+        eprintln!("Selected file path is not valid UTF-8");
+    }
+    path_str.map(str::to_string)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CellAction {
+    CopyCell(CopyFormat),
+    CopyRow(CopyFormat),
+    FilterTo, // Exact search for the cell's value in its column
+    Exclude,  // Hide the rows holding the cell's value in that column
+    RowDetail,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HeaderAction {
+    SortAscending,
+    SortDescending,
+    Hide,
+    AutoFit,
+    Rename,
+    Statistics,
+    FilterByValues,
+    SplitExport,
+    FilterInvalid,
+    ToggleMask,
+    Pseudonymize,
+    Normalize,
+    CheckReferences,
+    DecimalSeparators,
+    ConvertUnits,
+    FormatPhones,
+    NormalizeCurrency,
+    FlattenJson,
+    CopyColumn(CopyFormat),
+}
+
+// Most distinct values listed by "Filter by values"
+const MAX_PICKER_VALUES: usize = 1000;
+
+// State of the "Pseudonymize" window for one column
+struct Pseudonymizing {
+    col: usize,
+    hash: bool, // Salted hashes rather than surrogate IDs
+    salt: String,
+    prefix: String,
+}
+
+// State of the "Normalize values" window for one column
+struct Normalizing {
+    col: usize,
+    mapping_path: String,
+    mapping: Vec<(String, String)>, // From and to, as read from the mapping file
+    ignore_case: bool,              // Also match ignoring case and surrounding spaces
+    result: Option<(usize, Vec<(String, usize)>)>, // Cells changed, and values left unmapped with their counts
+}
+
+// State of the "Check against lookup file" window for one column
+struct ReferenceCheck {
+    col: usize,
+    path: String,
+    header: Vec<String>, // Of the lookup file
+    data: Table,
+    ref_col: usize,   // Lookup file column holding the valid values
+    ignore_case: bool, // Also match ignoring case and surrounding spaces
+    result: Option<(usize, Vec<(String, usize)>)>, // Rows with a value missing from the lookup, and those values with their counts
+}
+
+// State of the "Decimal Separators" window, for one column or the whole table
+struct DecimalSeparators {
+    columns: Vec<ColumnConventions>, // Those with numbers that have a separator
+    from: Vec<Option<Convention>>,   // For each of `columns`, None to leave it
+    to: Convention,
+    changed: Option<usize>, // Cells changed by the last conversion
+}
+
+// State of the "Convert Units" window for one column
+struct UnitConversion {
+    col: usize,
+    conversion: Conversion,
+    factor: f64, // For Conversion::Factor
+    decimals: usize,
+    new_column: bool, // Write the results to a new last column rather than over the values
+    name: String,     // Of the new column
+    result: Option<(usize, usize)>, // Cells converted and cells that aren't numbers
+}
+
+// State of the "Format Phone Numbers" window for one column
+struct PhoneFormatting {
+    col: usize,
+    region: &'static phones::Region, // For numbers without a country calling code
+    format: phones::Format,
+    new_column: bool, // Write the results to a new last column rather than over the values
+    name: String,     // Of the new column
+    result: Option<(usize, HashSet<String>)>, // Cells formatted, and the values that aren't phone numbers
+}
+
+// State of the "Normalize Amounts" window for one column
+struct CurrencyNormalizing {
+    col: usize,
+    convention: Convention, // Of the amounts as written
+    dollar: String,         // Currency code "$" stands for
+    decimals: usize,
+    split_code: bool, // Write each amount's currency code to a new column
+    code_name: String, // Of that column
+    result: Option<(usize, HashSet<String>)>, // Amounts normalized, and the values that aren't amounts
+}
+
+// State of the "Flatten JSON" window for one column
+struct JsonFlattening {
+    col: usize,
+    keys: Vec<(Vec<String>, usize, bool)>, // Key paths in order of first use, with the rows that have them and whether chosen
+    result: Option<usize>,            // Columns added
+}
+
+// State of the "Spelling" window
+struct Spelling {
+    dictionary_path: String,
+    dictionary: Option<Arc<spelling::Dictionary>>,
+    columns: HashSet<usize>,  // Checked
+    ignored: HashSet<String>, // Lowercase words accepted besides the dictionary's
+    report: Option<(u64, spelling::SpellReport)>, // Data generation it was computed for
+}
+
+// State of the "Ragged Rows" window
+struct RaggedRepair {
+    short: Vec<usize>, // Data indices of rows with fewer fields than the header
+    long: Vec<usize>,  // and with more
+    spill: bool,       // Move the extra fields of long rows to an overflow column instead of dropping them
+}
+
+// Affected rows listed before a ragged row repair
+const RAGGED_PREVIEW_ROWS: usize = 100;
+
+// State of the "Filter by values" window for one column
+struct ValuePicker {
+    col: usize,
+    values: Vec<(String, usize)>, // Distinct values with their row counts, most common first
+    distinct: usize,              // Distinct values in the column, listed or not
+    checked: HashSet<String>,
+    filter: String, // Narrows the listed values
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum JobKind {
+    Load,
+    Save,
+    Sort,
+    Search,
+    Validate,
+    Scan,
+    FindPersonalData,
+    CheckDates,
+    Score,
+    CheckSpelling,
+}
+
+// A file read by a load job, ready to be shown
+struct OpenedFile {
+    header: Vec<String>,
+    data: Table,
+    pager: Option<DiskPager>,
+    is_preview: bool,
+    invalid_utf8: Vec<InvalidCell>,
+}
+
+// What a finished job hands back to the UI thread. Sort and search results
+// carry the data generation they were computed from and are dropped if the
+// data has been replaced since.
+enum JobResult {
+    Opened {
+        path: String,
+        file: Result<OpenedFile, String>,
+        elapsed: Duration,
+    },
+    Saved {
+        path: String,
+        whole_file: bool,   // Not an export of a selection
+        edit_generation: u64, // Edits included in what was written
+        result: Result<bool, String>,
+        verification: Option<Result<usize, String>>, // Rows read back and found as written, or what differs
+    },
+    Sorted {
+        generation: u64,
+        order: Vec<usize>,
+        elapsed: Duration,
+    },
+    Searched {
+        generation: u64,
+        last_search: Option<(String, Option<usize>)>,
+        results: Vec<usize>,
+        elapsed: Duration,
+    },
+    Validated {
+        generation: u64,
+        report: ValidationReport,
+    },
+    Scanned {
+        generation: u64,
+        report: ScanReport,
+    },
+    FoundPersonalData {
+        generation: u64,
+        report: pii::ScanReport,
+    },
+    CheckedDates {
+        generation: u64,
+        report: dates::DateReport,
+    },
+    Scored {
+        generation: u64,
+        report: quality::QualityReport,
+    },
+    CheckedSpelling {
+        generation: u64,
+        report: spelling::SpellReport,
+    },
+    Cancelled,
+}
+
+// Aggregate shown for a column in the summary footer
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Aggregate {
+    #[default]
+    Count,
+    Sum,
+    Mean,
+    Distinct,
+}
+
+impl Aggregate {
+    const ALL: [Aggregate; 4] = [Aggregate::Count, Aggregate::Sum, Aggregate::Mean, Aggregate::Distinct];
+
+    fn label(self) -> &'static str {
+        match self {
+            Aggregate::Count => tr("Count"),
+            Aggregate::Sum => tr("Sum"),
+            Aggregate::Mean => tr("Mean"),
+            Aggregate::Distinct => tr("Distinct"),
+        }
+    }
+
+    // Reduce a column's cells to a display value, in parallel across rows.
+    // Count skips blank cells, Sum and Mean only consider cells that parse as
+    // numbers.
+    fn apply(self, rows: &[Row<'_>], col: usize) -> String {
+        let cells = || rows.par_iter().filter_map(move |row| row.get(col));
+        match self {
+            Aggregate::Count => cells().filter(|cell| !cell.trim().is_empty()).count().to_string(),
+            Aggregate::Distinct => cells().collect::<HashSet<_>>().len().to_string(),
+            Aggregate::Sum | Aggregate::Mean => {
+                let (sum, count) = cells()
+                    .filter_map(|cell| cell.trim().parse::<f64>().ok())
+                    .fold(|| (0.0, 0usize), |(sum, count), value| (sum + value, count + 1))
+                    .reduce(|| (0.0, 0), |(sum_a, count_a), (sum_b, count_b)| (sum_a + sum_b, count_a + count_b));
+                if count == 0 {
+                    "-".to_string()
+                } else if self == Aggregate::Sum {
+                    format_number(sum)
+                } else {
+                    format_number(sum / count as f64)
+                }
+            }
+        }
+    }
+}
+
+// Count, blanks, distinct values and, for numeric cells, sum, mean, min and max
+fn column_statistics(rows: &[Row<'_>], col: usize) -> Vec<(&'static str, String)> {
+    let mut stats: Vec<(&'static str, String)> =
+        Aggregate::ALL.iter().map(|aggregate| (aggregate.label(), aggregate.apply(rows, col))).collect();
+    let blank = rows.iter().filter(|row| row.get(col).is_none_or(|cell| cell.trim().is_empty())).count();
+    stats.insert(1, (tr("Blank"), blank.to_string()));
+    let numbers = rows.iter().filter_map(|row| row.get(col)?.trim().parse::<f64>().ok());
+    let (min, max) = numbers.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if min <= max {
+        stats.push((tr("Min"), format_number(min)));
+        stats.push((tr("Max"), format_number(max)));
+    }
+    stats
+}
+
+// Field names of the change log export
+const CHANGE_FIELDS: [&str; 5] = ["change", "row", "column", "old", "new"];
+
+// A change made in the app, kept for the change log export
+enum Edit {
+    DeletedRow {
+        row_num: usize,               // In the original file, where the header is row 1
+        cells: Vec<(String, String)>, // Column name and value
+    },
+    RenamedColumn {
+        old: String,
+        new: String,
+    },
+    EditedCell {
+        row_num: usize,
+        column: String,
+        old: String,
+        new: String,
+    },
+    TransformedColumn {
+        column: String,
+        how: &'static str,
+    },
+    RepairedRow {
+        row_num: usize,
+        fields: usize,  // As read
+        columns: usize, // The header's
+    },
+    DeletedColumn {
+        column: String,
+    },
+}
+
+// Field names of the column profile export, fixed so scripts tracking the
+// numbers over time can rely on them whatever the UI language
+const PROFILE_FIELDS: [&str; 9] = ["column", "count", "blank", "distinct", "numeric", "sum", "mean", "min", "max"];
+
+// One row per column of the view with the numbers the statistics panel
+// shows, unrounded. Numeric fields are empty when no cell parses as a number,
+// and for `masked` columns, whose sum, mean, min and max could give values
+// away.
+fn column_profile(header: &[String], data: &Table, view: &Selection, masked: &[usize]) -> (Vec<String>, Table) {
+    let rows: Vec<Row<'_>> = view.rows(data).collect();
+    let columns: Vec<usize> = match &view.columns {
+        Some(columns) => columns.clone(),
+        None => (0..header.len()).collect(),
+    };
+    let profiles: Vec<Vec<String>> = columns
+        .par_iter()
+        .map(|&col| {
+            let cells = || rows.iter().map(|row| row.get(col).unwrap_or(""));
+            let blank = cells().filter(|cell| cell.trim().is_empty()).count();
+            let distinct = cells().collect::<HashSet<_>>().len();
+            let numbers: Vec<f64> = cells().filter_map(|cell| cell.trim().parse().ok()).collect();
+            let sum: f64 = numbers.iter().sum();
+            let numeric = |value: f64| if numbers.is_empty() || masked.contains(&col) { String::new() } else { value.to_string() };
+            vec![
+                header[col].clone(),
+                rows.len().to_string(),
+                blank.to_string(),
+                distinct.to_string(),
+                numbers.len().to_string(),
+                numeric(sum),
+                numeric(sum / numbers.len() as f64),
+                numeric(numbers.iter().copied().fold(f64::INFINITY, f64::min)),
+                numeric(numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+            ]
+        })
+        .collect();
+    let mut table = Table::new();
+    for profile in profiles {
+        table.push_row(profile);
+    }
+    (PROFILE_FIELDS.map(str::to_string).to_vec(), table)
+}
+
+// The view with rows and columns swapped: one row per visible column,
+// starting with its name, and one column per row, headed by its row number
+fn transpose(header: &[String], data: &Table, view: &Selection, row_offset: usize) -> (Vec<String>, Table) {
+    let indices: Vec<usize> = match &view.rows {
+        Some(rows) => rows.to_vec(),
+        None => (0..data.len()).collect(),
+    };
+    let columns: Vec<usize> = match &view.columns {
+        Some(columns) => columns.clone(),
+        None => (0..header.len()).collect(),
+    };
+    let mut transposed_header = vec![tr("Column").to_string()];
+    transposed_header.extend(indices.iter().map(|idx| tr!("Row {}", idx + row_offset + 2)));
+    let mut table = Table::new();
+    for col in columns {
+        let mut cells = vec![header[col].as_str()];
+        cells.extend(indices.iter().map(|&idx| data.row(idx).get(col).unwrap_or("")));
+        table.push_row(cells);
+    }
+    (transposed_header, table)
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+// Height needed to show a row's visible cells wrapped at their column widths
+fn measure_row_height(
+    ctx: &egui::Context,
+    row: Row<'_>,
+    visible_columns: &[usize],
+    widths: &[f32],
+    min_height: f32,
+) -> f32 {
+    let style = ctx.style();
+    let font = egui::TextStyle::Body.resolve(&style);
+    let color = style.visuals.text_color();
+    visible_columns
+        .iter()
+        .zip(widths)
+        .filter_map(|(&col, &width)| row.get(col).map(|cell| (cell, width)))
+        .fold(min_height, |max_height, (cell, width)| {
+            let galley = ctx.fonts(|f| f.layout(cell.to_string(), font.clone(), color, width));
+            max_height.max(galley.size().y)
+        })
+}
+
+// Cells longer than this, or spanning several lines, open in the cell viewer
+// on a single click
+const LONG_CELL_CHARS: usize = 200;
+
+fn is_long_cell(cell: &str) -> bool {
+    cell.len() > LONG_CELL_CHARS || cell.contains('\n')
+}
+
+// Auto-fit measures at most this many of the displayed rows, so fitting a
+// column on a huge file costs the same as on a small one
+const AUTO_FIT_SAMPLE_ROWS: usize = 200;
+const DEFAULT_COLUMN_WIDTH: f32 = 150.0;
+// Zoom changes by this factor per step, within these bounds
+const ZOOM_STEP: f32 = 1.1;
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+// Sideways scroll distance, in points, that moves past one column when columns are frozen
+const COLUMN_SCROLL_STEP: f32 = 50.0;
+
+// Width that fits `cells` on one line, within sensible bounds
+fn fit_column_width<'a>(ctx: &egui::Context, cells: impl Iterator<Item = &'a str>) -> f32 {
+    let style = ctx.style();
+    let font = egui::TextStyle::Body.resolve(&style);
+    let color = style.visuals.text_color();
+    let widest = cells.fold(0.0f32, |widest, cell| {
+        let galley = ctx.fonts(|f| f.layout_no_wrap(cell.to_string(), font.clone(), color));
+        widest.max(galley.size().x)
+    });
+    (widest + 16.0).clamp(40.0, 600.0)
+}
+
+// Whether a single-line cell is cut off at the given width
+fn is_truncated(ui: &egui::Ui, cell: &str, width: f32) -> bool {
+    if cell.contains('\n') {
+        return true;
+    }
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let galley = ui.fonts(|f| f.layout_no_wrap(cell.to_string(), font, color));
+    galley.size().x > width
+}
+
+// The first line of `cell`, cut off with an ellipsis where it would overflow
+// `width`
+fn elide(ui: &egui::Ui, cell: &str, width: f32) -> String {
+    let line = cell.lines().next().unwrap_or("");
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let color = ui.visuals().text_color();
+    let fits = |text: String| ui.fonts(|f| f.layout_no_wrap(text, font.clone(), color)).size().x <= width;
+    if !cell.contains('\n') && fits(line.to_string()) {
+        return line.to_string();
+    }
+    // Most characters kept that still fit with the ellipsis
+    let ends: Vec<usize> = line.char_indices().map(|(i, _)| i).chain([line.len()]).collect();
+    let (mut low, mut high) = (0, ends.len() - 1);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if fits(format!("{}…", &line[..ends[mid]])) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    format!("{}…", &line[..ends[low]])
+}
+
+// Tooltips stop after this many characters; the cell viewer shows the rest
+const MAX_TOOLTIP_CHARS: usize = 2000;
+
+fn tooltip_text(cell: &str) -> String {
+    match cell.char_indices().nth(MAX_TOOLTIP_CHARS) {
+        Some((end, _)) => format!("{}…", &cell[..end]),
+        None => cell.to_string(),
+    }
+}
+
+// Measured heights for the rows currently displayed. Laying out every cell is
+// the most expensive part of a frame, so heights are only re-measured when the
+// displayed rows, the visible columns or the column widths change.
+//
+// Rows appended by infinite scroll keep the view generation, so only the new
+// rows are measured.
+//
+// While a column edge is being dragged the widths change every frame, so the
+// old heights are reused and refreshed at most every RESIZE_REMEASURE_INTERVAL,
+// with a final re-measure once the mouse button is released.
+#[derive(Default)]
+struct RowHeightCache {
+    view_generation: u64,
+    visible_columns: Vec<usize>,
+    widths: Vec<f32>,
+    min_height: f32, // Row density's minimum the heights were measured with
+    heights: Vec<f32>,
+    measured_at: Option<Instant>,
+    measure_time: Duration, // How long the last re-measure took
+}
+
+const RESIZE_REMEASURE_INTERVAL: Duration = Duration::from_millis(250);
+
+impl RowHeightCache {
+    fn heights(
+        &mut self,
+        ctx: &egui::Context,
+        view_generation: u64,
+        rows: &[Row<'_>],
+        visible_columns: &[usize],
+        widths: &[f32],
+        min_height: f32,
+    ) -> &[f32] {
+        let same_rows = self.view_generation == view_generation
+            && self.visible_columns == visible_columns
+            && self.min_height == min_height
+            && self.heights.len() == rows.len();
+        let widths_changed = self.widths != widths;
+        let appended = self.view_generation == view_generation
+            && self.visible_columns == visible_columns
+            && self.min_height == min_height
+            && !widths_changed
+            && self.heights.len() < rows.len();
+        if appended {
+            let started = Instant::now();
+            let new_rows = &rows[self.heights.len()..];
+            self.heights
+                .extend(new_rows.iter().map(|row| measure_row_height(ctx, *row, visible_columns, widths, min_height)));
+            self.measure_time = started.elapsed();
+            return &self.heights;
+        }
+        let resizing = same_rows && widths_changed && ctx.input(|i| i.pointer.any_down());
+        let throttled = resizing
+            && self
+                .measured_at
+                .is_some_and(|measured_at| measured_at.elapsed() < RESIZE_REMEASURE_INTERVAL);
+        if (!same_rows || widths_changed) && !throttled {
+            let started = Instant::now();
+            self.view_generation = view_generation;
+            self.visible_columns = visible_columns.to_vec();
+            self.widths = widths.to_vec();
+            self.min_height = min_height;
+            self.heights = rows
+                .iter()
+                .map(|row| measure_row_height(ctx, *row, visible_columns, widths, min_height))
+                .collect();
+            self.measured_at = Some(Instant::now());
+            self.measure_time = started.elapsed();
+        }
+        &self.heights
+    }
+}
+
+// Choices for the page size selector; ALL_ROWS puts every row on one page
+const ALL_ROWS: usize = usize::MAX;
+const PAGE_SIZES: [usize; 4] = [100, 500, 1000, ALL_ROWS];
+
+// Infinite scroll loads another page once the last rendered row is this close
+// to the end of the loaded rows
+const INFINITE_SCROLL_MARGIN: usize = 20;
+
+// Rows PageUp/PageDown move the focus by when there are no pages to turn
+const PAGE_JUMP_ROWS: usize = 20;
+
+const GRID_NAV_KEYS: [egui::Key; 8] = [
+    egui::Key::ArrowUp,
+    egui::Key::ArrowDown,
+    egui::Key::ArrowLeft,
+    egui::Key::ArrowRight,
+    egui::Key::PageUp,
+    egui::Key::PageDown,
+    egui::Key::Home,
+    egui::Key::End,
+];
+
+fn page_size_label(size: usize) -> String {
+    if size == ALL_ROWS {
+        tr("All").to_string()
+    } else {
+        size.to_string()
+    }
+}
+
+// Timings for the performance overlay
+#[derive(Default)]
+struct PerfStats {
+    frame_time: Duration, // Time spent in the last update
+    table_time: Duration, // Building the table, row measuring included
+    rows_rendered: usize, // Rows laid out in the viewport last frame
+    search_time: Option<Duration>,
+    sort_time: Option<Duration>,
+}
+
 #[derive(Default)]
 struct MyApp {
     csv_header: Vec<String>,
-    csv_data: Vec<Vec<String>>,
+    csv_data: Arc<Table>, // Shared with background saves
     current_page: usize,
     rows_per_page: usize,
+    infinite_scroll: bool, // Grow one long list instead of paging
+    scroll_rows: usize,    // Rows loaded so far in infinite scroll mode
     search_query: String,
-    search_header: u8,
-    search_results: Option<Vec<Vec<String>>>,
+    search_column: Option<usize>, // None searches every column
+    search_exact: bool, // Match whole cells, case-sensitively
+    search_invert: bool, // Show the rows that don't match
+    value_filter: Option<(usize, Arc<HashSet<String>>)>, // Column and values picked in "Filter by values"
+    invalid_filter: Option<usize>, // Column filtered to values its type rejects
+    invisible_filter: bool, // Filter the search column to cells with invisible characters
+    column_types: Vec<ColumnType>, // Declared in the schema or guessed from the first rows
+    json_columns: Vec<bool>,       // Whether the first rows of each column hold JSON
+    header_check: HeaderCheck,
+    check_headers: bool, // Compare each opened file's header with the schema
+    header_list_input: String,
+    highlight_invalid: bool,
+    show_invisible: bool, // Reveal and mark invisible characters in cells
+    last_search: Option<(String, Option<usize>)>, // Lowercased query and column behind search_results
+    search_results: Option<Arc<Vec<usize>>>, // Indices into csv_data, in display order
+    sort: Option<(usize, bool)>,  // Sorted column and whether it is descending
+    sort_order: Arc<Vec<usize>>, // Data index shown at each position; empty when unsorted
     row_number_input: String,
-    selected_row: Option<Vec<String>>,
+    page_number_input: String, // 1-based page typed into the page jump field
+    selected_row: Option<usize>, // 1-based file row number, row 1 being the header
+    focused_cell: Option<(usize, usize)>, // Position in the displayed rows and column index
+    scroll_to_focus: bool,                // Bring the focused cell into view next frame
+    pending_launch: Option<LaunchArgs>,   // Command line location, applied once the file is open
+    pending_focus: Option<(Option<usize>, Option<usize>)>, // File row number and column to focus when the view settles
+    cell_viewer: Option<(String, String, Option<String>)>, // Column name, full text of the opened cell, and the text indented if it's JSON
+    cell_viewer_raw: bool,                                  // Show JSON cells as written
+    row_detail: Option<(usize, Vec<String>)>, // File row number and cells of the row shown in detail
+    renaming_column: Option<(usize, String)>, // Column being renamed and the name typed so far
+    pseudonymizing: Option<Pseudonymizing>,
+    normalizing: Option<Normalizing>,
+    reference_check: Option<ReferenceCheck>,
+    decimal_separators: Option<DecimalSeparators>,
+    unit_conversion: Option<UnitConversion>,
+    spelling: Option<Spelling>,
+    phone_formatting: Option<PhoneFormatting>,
+    currency_normalizing: Option<CurrencyNormalizing>,
+    json_flattening: Option<JsonFlattening>,
+    duplicate_headers: Option<Vec<(usize, String)>>, // Renames offered for repeated column names
+    ragged_repair: Option<RaggedRepair>,
+    save_problem: Option<(String, String)>,            // A saved file that didn't read back as written, and how
+    column_stats: Option<(String, Vec<(&'static str, String)>)>, // Column name and its statistics
+    value_picker: Option<ValuePicker>,
+    split_export: Option<usize>, // Column whose values the "Export split by column" window splits on
+    schema: Schema,
+    show_schema: bool,
+    show_personal_data: bool,
+    personal_data: Option<(u64, pii::ScanReport)>, // Data generation it was computed for
+    masked_columns: HashSet<usize>,                 // Redacted in the grid and in everything written
+    validation: Option<(u64, ValidationReport)>, // Data generation it was computed for
+    invisible_scan: Option<(u64, ScanReport)>,  // Data generation it was computed for
+    date_check: Option<(u64, dates::DateReport)>, // Data generation it was computed for
+    earliest_date: String, // Dates before this are flagged by the date check
+    quality: Option<(u64, quality::QualityReport)>, // Data generation it was computed for
+    quality_started: Option<u64>, // Data generation last scored, so the panel scores each version once; cleared when the schema changes
+    invalid_utf8: Arc<Vec<InvalidCell>>, // Cells of the opened file that weren't UTF-8, by current data index
     visible_columns: Vec<bool>, // Track which columns are visible
-    show_column_controls: bool, // Toggle for showing/hiding column controls
+    layout: PanelLayout, // Which side panels are open and where they are docked
+    selected_rows: HashSet<usize>,   // Data indices of the rows picked by clicking
+    selection_anchor: Option<usize>, // Data index that Shift+click and dragging extend from
+    dragging_rows: bool,             // A drag that began on a cell is selecting rows
+    announcement: String, // Latest page change or match count, read out by screen readers
+    show_summary_footer: bool,
+    show_row_numbers: bool, // Gutter with each row's number in the file
+    wrap_cells: bool,       // Wrap long cells, or cut them off with an ellipsis
+    export_settings: ExportSettings,
+    column_aggregates: Vec<Aggregate>, // Aggregate chosen per column for the footer
+    summary_cache: Option<Vec<String>>, // Footer values, cleared when the filter changes
+    view_generation: u64, // Bumped whenever the displayed rows change
+    row_height_cache: RowHeightCache,
+    column_widths: Vec<f32>, // Last width of each column, seeds the table when it is rebuilt
+    frozen_columns: usize,      // Leading visible columns kept in place while scrolling sideways
+    first_scroll_column: usize, // First of the remaining visible columns shown after the frozen ones
+    column_scroll_delta: f32,   // Sideways scrolling not yet turned into a column step
+    table_generation: u64,   // Part of the table id; bumping it applies column_widths
+    pager: Option<DiskPager>, // Set when the file is paged from disk; csv_data then holds one page
+    jobs: Vec<(JobKind, Job<JobResult>)>,
+    data_generation: u64, // Bumped whenever csv_data is replaced
+    load_duration: Option<Duration>, // How long the last file took to open
+    perf: PerfStats,
+    show_perf_overlay: bool,
+    config: Config,
+    shortcuts: Shortcuts,
+    show_shortcuts: bool, // Keyboard shortcuts window
+    capturing_shortcut: Option<Action>, // Action whose new binding is being recorded
+    theme: Theme,
+    zoom: f32,             // UI scale, 1.0 being the display's native scale
+    show_appearance: bool, // Appearance settings window
+    show_save_options: bool, // Delimiter, quoting and line endings, asked before saving
+    palette: Option<Palette>,           // Open command palette
+    focus_search: bool,                 // Give the search field focus next frame
+    file_path: Option<String>,
+    edit_generation: u64,  // Bumped by every change to the loaded data or header
+    saved_generation: u64, // edit_generation as of the last load or full save
+    edits: Vec<Edit>,      // Changes since then, for the change log
+    original_rows: Vec<usize>, // Original index of each row once rows have been deleted, else empty
+    preview_limit: usize, // Row cap for "Preview CSV"
+    is_preview: bool,     // Loaded rows stop short of the end of the file
 }
 
-impl MyApp {
-    fn total_pages(&self) -> usize {
-        if self.csv_data.is_empty() {
-            1
-        } else {
-            (self.csv_data.len() + self.rows_per_page - 1) / self.rows_per_page
+impl MyApp {
+    fn total_pages(&self) -> usize {
+        if let Some(pager) = &self.pager {
+            pager.total_pages()
+        } else if self.csv_data.is_empty() {
+            1
+        } else {
+            self.csv_data.len().div_ceil(self.rows_per_page)
+        }
+    }
+
+    // Data rows in the file, not just those loaded
+    fn total_rows(&self) -> usize {
+        match &self.pager {
+            Some(pager) => pager.total_rows(),
+            None => self.csv_data.len(),
+        }
+    }
+
+    // Show one row as a record card, loading its page first for files paged
+    // from disk. Clears any search, which the card replaces.
+    fn select_row(&mut self, row_num: usize) {
+        if let Some(pager) = &self.pager
+            && row_num >= 2
+            && row_num - 2 < pager.total_rows()
+        {
+            self.go_to_page((row_num - 2) / pager.page_size());
+        }
+        if row_num == 1 || self.data_index_for_row_number(row_num).is_some() {
+            self.selected_row = Some(row_num);
+        } else {
+            self.selected_row = None;
+        }
+        self.cancel_jobs(JobKind::Search);
+        self.search_results = None;
+        self.last_search = None;
+        self.summary_cache = None;
+        self.view_changed();
+    }
+
+    // A substring search that only narrows the previous one (same column, the
+    // old query contained in the new) can filter the previous matches instead
+    // of rescanning every row.
+    fn can_refine_search(&self, query: &str) -> bool {
+        match &self.last_search {
+            Some((last_query, column)) => {
+                self.search_results.is_some() && *column == self.search_column && query.contains(last_query.as_str())
+            }
+            None => false,
+        }
+    }
+
+    // Start a search job, replacing any search still running
+    fn run_search(&mut self) {
+        let query = self.search_query.to_lowercase();
+        // Previous results are already in display order
+        let filtering_values = self.value_filter.is_some() || self.invalid_filter.is_some() || self.invisible_filter;
+        let order = if !self.search_exact && !self.search_invert && !filtering_values && self.can_refine_search(&query) {
+            self.search_results.clone()
+        } else if self.sort_order.is_empty() {
+            None
+        } else {
+            Some(Arc::clone(&self.sort_order))
+        };
+        // Exact and excluding matches aren't narrowed by extending the query,
+        // so they never refine
+        let last_search = if self.search_exact || self.search_invert || filtering_values {
+            None
+        } else {
+            Some((query, self.search_column))
+        };
+        let request = SearchRequest {
+            data: Arc::clone(&self.csv_data),
+            col: self.invalid_filter.or(self.value_filter.as_ref().map_or(self.search_column, |(col, _)| Some(*col))),
+            query: self.search_query.clone(),
+            exact: self.search_exact,
+            invert: self.search_invert,
+            values: self.value_filter.as_ref().map(|(_, values)| Arc::clone(values)),
+            invalid: self.invalid_filter.and_then(|col| self.column_types.get(col).copied()),
+            invisible: self.invisible_filter,
+            order,
+        };
+        let generation = self.data_generation;
+        let label = match request.col.and_then(|col| self.csv_header.get(col)) {
+            Some(name) => tr!("Searching {}", name),
+            None => tr("Searching all columns").to_string(),
+        };
+        self.cancel_jobs(JobKind::Search);
+        self.spawn_job(JobKind::Search, label, move |progress| {
+            let started = Instant::now();
+            match request.run(progress) {
+                Ok(results) => JobResult::Searched {
+                    generation,
+                    last_search,
+                    results,
+                    elapsed: started.elapsed(),
+                },
+                Err(_) => JobResult::Cancelled,
+            }
+        });
+    }
+
+    // Data index of the row shown at `position` in the current sort order
+    fn sorted_index(&self, position: usize) -> usize {
+        self.sort_order.get(position).copied().unwrap_or(position)
+    }
+
+    // Clicking a header cycles ascending, descending, unsorted
+    fn toggle_sort(&mut self, col: usize) {
+        self.sort = match self.sort {
+            Some((sorted, false)) if sorted == col => Some((col, true)),
+            Some((sorted, true)) if sorted == col => None,
+            _ => Some((col, false)),
+        };
+        self.apply_sort();
+    }
+
+    // Rebuild the sort permutation for the current csv_data. Sorting runs as a
+    // job; the rows keep their previous order until it finishes.
+    fn apply_sort(&mut self) {
+        self.cancel_jobs(JobKind::Sort);
+        let Some((col, descending)) = self.sort else {
+            self.sort_order = Arc::default();
+            self.sort_order_changed();
+            return;
+        };
+        let data = Arc::clone(&self.csv_data);
+        let generation = self.data_generation;
+        let label = tr!("Sorting by {}", self.csv_header.get(col).map_or("", String::as_str));
+        self.spawn_job(JobKind::Sort, label, move |_| {
+            let started = Instant::now();
+            let order = data.sorted_order(col, descending);
+            JobResult::Sorted {
+                generation,
+                order,
+                elapsed: started.elapsed(),
+            }
+        });
+    }
+
+    // Search results follow the sort order, so they are refreshed with it
+    fn sort_order_changed(&mut self) {
+        if self.search_results.is_some() {
+            self.last_search = None;
+            self.run_search();
+        }
+        self.view_changed();
+    }
+
+    // File row index of the first row in csv_data (non-zero when paging from disk)
+    fn row_offset(&self) -> usize {
+        match &self.pager {
+            Some(pager) => self.current_page * pager.page_size(),
+            None => 0,
+        }
+    }
+
+    // Data index for a 1-based file row number (row 1 is the header)
+    fn data_index_for_row_number(&self, row_num: usize) -> Option<usize> {
+        let idx = row_num.checked_sub(2)?.checked_sub(self.row_offset())?;
+        if idx < self.csv_data.len() {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+    // Replace the loaded data and reset everything that referred to the old file
+    fn set_data(&mut self, header: Vec<String>, data: Table) {
+        self.cancel_jobs(JobKind::Sort);
+        self.cancel_jobs(JobKind::Search);
+        self.csv_header = header;
+        self.csv_data = Arc::new(data);
+        self.data_generation += 1;
+        self.clear_row_selection();
+        self.announcement.clear();
+        self.current_page = 0;
+        self.scroll_rows = self.rows_per_page;
+        self.search_query.clear();
+        self.search_column = None;
+        self.value_filter = None;
+        self.invalid_filter = None;
+        self.invisible_filter = false;
+        self.search_results = None;
+        self.last_search = None;
+        self.sort = None;
+        self.sort_order = Arc::default();
+        self.row_number_input.clear();
+        self.page_number_input.clear();
+        self.selected_row = None;
+        self.summary_cache = None;
+        self.saved_generation = self.edit_generation;
+        self.edits.clear();
+        self.original_rows.clear();
+        self.masked_columns.clear();
+        self.initialize_visible_columns();
+        self.apply_schema();
+        self.view_changed();
+    }
+
+    // Recompute what depends on the schema and the header
+    fn apply_schema(&mut self) {
+        self.column_types = self.schema.column_types(&self.csv_header, &self.csv_data);
+        self.json_columns = (0..self.csv_header.len())
+            .map(|col| json::is_json_column(self.csv_data.rows().map(|row| row.get(col).unwrap_or(""))))
+            .collect();
+        self.header_check = self.schema.check_header(&self.csv_header);
+        // Types and rules feed the score
+        self.quality_started = None;
+    }
+
+    // Opens the Validation panel when a newly opened file's header doesn't
+    // match the schema
+    fn report_header_check(&mut self) {
+        if !self.check_headers || self.schema.columns.is_empty() || self.header_check.is_ok() {
+            return;
+        }
+        self.announcement = tr("The columns don't match the schema").to_string();
+        self.set_panel_open(Panel::Validation, true);
+    }
+
+    // Rows whose value in `col` doesn't parse as the column's type
+    fn filter_invalid(&mut self, col: usize) {
+        self.selected_row = None;
+        self.value_filter = None;
+        self.invalid_filter = Some(col);
+        self.invisible_filter = false;
+        self.search_invert = false;
+        self.run_search();
+    }
+
+    fn open_csv(&mut self, path: &str) {
+        self.start_load(path, |path, progress| {
+            let (header, data, invalid_utf8) = read_csv_with_header(path, progress)?;
+            Ok(OpenedFile {
+                header,
+                data,
+                pager: None,
+                is_preview: false,
+                invalid_utf8,
+            })
+        });
+    }
+
+    fn open_preview(&mut self, path: &str) {
+        let limit = self.preview_limit;
+        self.start_load(path, move |path, _| {
+            let ((header, data, invalid_utf8), truncated) = read_csv_preview(path, limit)?;
+            Ok(OpenedFile {
+                header,
+                data,
+                pager: None,
+                is_preview: truncated,
+                invalid_utf8,
+            })
+        });
+    }
+
+    fn open_paged(&mut self, path: &str) {
+        // A single page would read the whole file into memory
+        if self.rows_per_page == ALL_ROWS {
+            self.rows_per_page = PAGE_SIZES[0];
+        }
+        let page_size = self.rows_per_page;
+        self.start_load(path, move |path, progress| {
+            let mut pager = DiskPager::open(path, page_size, progress)?;
+            let data = pager.page(0)?;
+            Ok(OpenedFile {
+                header: pager.header().to_vec(),
+                data,
+                pager: Some(pager),
+                is_preview: false,
+                invalid_utf8: Vec::new(),
+            })
+        });
+    }
+
+    // Read a file on a worker thread, replacing any load still running
+    fn start_load<F>(&mut self, path: &str, load: F)
+    where
+        F: FnOnce(&str, &Progress) -> Result<OpenedFile, Box<dyn Error>> + Send + 'static,
+    {
+        self.cancel_jobs(JobKind::Load);
+        let path = path.to_string();
+        self.spawn_job(JobKind::Load, tr!("Loading {}", file_name(&path)), move |progress| {
+            let started = Instant::now();
+            let file = load(&path, progress).map_err(|err| err.to_string());
+            JobResult::Opened {
+                path,
+                file,
+                elapsed: started.elapsed(),
+            }
+        });
+    }
+
+    fn show_file(&mut self, path: String, file: OpenedFile, elapsed: Duration) {
+        self.remember_view();
+        if file.pager.is_some() {
+            self.infinite_scroll = false;
+        }
+        self.pager = file.pager;
+        self.set_data(file.header, file.data);
+        self.load_duration = Some(elapsed);
+        self.file_path = Some(path);
+        self.is_preview = file.is_preview;
+        self.restore_view();
+        self.invalid_utf8 = Arc::new(file.invalid_utf8);
+        if !self.invalid_utf8.is_empty() {
+            self.announcement = tr!("{} cells aren't valid UTF-8", self.invalid_utf8.len());
+            self.set_panel_open(Panel::Validation, true);
+        }
+        self.load_schema_beside_file();
+        self.report_header_check();
+        let renames = disambiguate_header(&self.csv_header);
+        if !renames.is_empty() {
+            self.announcement = tr("Some column names are repeated").to_string();
+        }
+        self.duplicate_headers = (!renames.is_empty()).then_some(renames);
+        if self.pager.is_none() {
+            self.find_ragged_rows();
+        }
+        if let Some(launch) = self.pending_launch.take() {
+            self.apply_launch(launch);
+        }
+    }
+
+    fn launch(&mut self, launch: LaunchArgs) {
+        if let Some(file) = &launch.file {
+            self.open_csv(file);
+            self.pending_launch = Some(launch);
+        }
+    }
+
+    // Filter first, then focus the row once the filter and any restored sort
+    // have been applied
+    fn apply_launch(&mut self, launch: LaunchArgs) {
+        let find = |name: &str| {
+            let position = self.csv_header.iter().position(|header| header == name);
+            let found = position.or_else(|| self.csv_header.iter().position(|header| header.eq_ignore_ascii_case(name)));
+            if found.is_none() {
+                eprintln!("No column named {}", name);
+            }
+            found
+        };
+        let column = launch.column.as_deref().and_then(find);
+        if let Some((name, value)) = launch.filter
+            && let Some(col) = find(&name)
+        {
+            self.value_filter = None;
+            self.invalid_filter = None;
+            self.invisible_filter = false;
+            self.search_query = value;
+            self.search_column = Some(col);
+            self.search_exact = true;
+            self.search_invert = false;
+            self.run_search();
+        }
+        if launch.row.is_some() || column.is_some() {
+            self.pending_focus = Some((launch.row, column));
+        }
+    }
+
+    // Move to the page holding a file row and focus its cell in `col`, or the
+    // first visible column
+    fn focus_location(&mut self, row_num: Option<usize>, col: Option<usize>) {
+        let Some(col) = col.or_else(|| self.visible_column_indices().first().copied()) else {
+            return;
+        };
+        self.visible_columns[col] = true;
+        if let (Some(pager), Some(row_num)) = (&self.pager, row_num) {
+            let page = (row_num - 2) / pager.page_size();
+            if row_num - 2 < pager.total_rows() && page != self.current_page {
+                // The page's rows may need sorting before the row can be found
+                self.go_to_page(page);
+                self.pending_focus = Some((Some(row_num), Some(col)));
+                return;
+            }
+        }
+        let pos = match row_num {
+            None => 0,
+            Some(row_num) => {
+                let Some(idx) = self.data_index_for_row_number(row_num) else {
+                    eprintln!("Row {} is not in the file", row_num);
+                    return;
+                };
+                let order = self.search_results.as_ref().unwrap_or(&self.sort_order);
+                match order.iter().position(|&i| i == idx) {
+                    Some(pos) => pos,
+                    None if order.is_empty() && self.search_results.is_none() => idx,
+                    None => {
+                        eprintln!("Row {} doesn't match the filter", row_num);
+                        return;
+                    }
+                }
+            }
+        };
+        let mut row = pos;
+        if self.search_results.is_none() && self.pager.is_none() {
+            if self.infinite_scroll {
+                self.scroll_rows = self.scroll_rows.max(pos + 1);
+            } else {
+                if pos / self.rows_per_page != self.current_page {
+                    self.go_to_page(pos / self.rows_per_page);
+                }
+                row = pos % self.rows_per_page;
+            }
+        }
+        self.focused_cell = Some((row, col));
+        self.scroll_to_focus = true;
+    }
+
+    // Save how the current file is arranged, for when it is opened again
+    fn remember_view(&mut self) {
+        let Some(path) = &self.file_path else {
+            return;
+        };
+        let view = ViewState {
+            widths: self.column_widths.clone(),
+            visible: self.visible_columns.clone(),
+            sort: self.sort,
+            page: self.current_page,
+        };
+        view.save_to(&mut self.config, path);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+    }
+
+    // Put back the arrangement saved for the current file, if there is one
+    // and the file still has the same columns
+    fn restore_view(&mut self) {
+        let Some(view) = self.file_path.as_deref().and_then(|path| ViewState::load(&self.config, path)) else {
+            return;
+        };
+        if view.widths.len() != self.csv_header.len() {
+            return;
+        }
+        self.column_widths = view.widths;
+        self.visible_columns = view.visible;
+        self.table_generation += 1;
+        if let Some((col, _)) = view.sort
+            && col < self.csv_header.len()
+        {
+            self.sort = view.sort;
+            self.apply_sort();
+        }
+        if view.page > 0 && view.page < self.total_pages() {
+            self.go_to_page(view.page);
+        }
+    }
+
+    // When paging from disk, swap the current page's rows into csv_data
+    fn load_disk_page(&mut self) {
+        if let Some(pager) = self.pager.as_mut() {
+            match pager.page(self.current_page) {
+                Ok(page) => {
+                    self.csv_data = Arc::new(page);
+                    self.data_generation += 1;
+                    self.clear_row_selection();
+                    self.sort_order = Arc::default();
+                }
+                Err(err) => eprintln!("Error reading page: {}", err),
+            }
+            self.apply_sort();
+        }
+    }
+
+    fn start_save(&mut self, path: String, format: ExportFormat, selection: Selection) {
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let label = tr!("Saving {}", file_name(&path));
+        let masked = self.masked_columns();
+        // Without its header or with redacted cells the file can't be
+        // reopened as the same table
+        let whole_file = matches!(format, ExportFormat::Csv(options) if options.header)
+            && selection.rows.is_none()
+            && selection.columns.is_none()
+            && masked.is_empty();
+        let edit_generation = self.edit_generation;
+        let raw = match &format {
+            ExportFormat::Csv(options) if options.keep_invalid_bytes && options.encoding == Encoding::Utf8 => {
+                Some(self.raw_cells())
+            }
+            _ => None,
+        };
+        let verify = match &format {
+            ExportFormat::Csv(options) if options.verify => Some(*options),
+            _ => None,
+        };
+        self.spawn_job(JobKind::Save, label, move |progress| {
+            let data = masked_data(data, &masked);
+            let result = save_file(&path, format, &header, &data, &selection, raw.as_ref(), progress)
+                .map_err(|err| err.to_string());
+            let verification = verify
+                .filter(|_| result == Ok(true))
+                .map(|options| verify_csv(&path, &options, &header, &data, &selection, raw.as_ref(), progress));
+            JobResult::Saved {
+                path,
+                whole_file,
+                edit_generation,
+                result,
+                verification,
+            }
+        });
+    }
+
+    // Cells read with invalid UTF-8 that still hold the text they were read as
+    fn raw_cells(&self) -> RawCells {
+        self.invalid_utf8
+            .iter()
+            .filter_map(|cell| {
+                let row = cell.row.filter(|&row| row < self.csv_data.len())?;
+                let text = self.csv_data.row(row).get(cell.col)?;
+                (text == String::from_utf8_lossy(&cell.bytes)).then(|| ((row, cell.col), cell.bytes.clone()))
+            })
+            .collect()
+    }
+
+    // Statistics for every column of the view, written in the background
+    fn export_profile(&mut self) {
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let view = self.view_selection();
+        let masked = self.masked_columns();
+        self.export_derived_table(move || column_profile(&header, &data, &view, &masked));
+    }
+
+    fn export_transposed(&mut self) {
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let view = self.view_selection();
+        let row_offset = self.row_offset();
+        let masked = self.masked_columns();
+        self.export_derived_table(move || transpose(&header, &masked_data(data, &masked), &view, row_offset));
+    }
+
+    // The edits since the file was opened or last saved in full, one line per
+    // changed cell or header, with file row numbers of the original file.
+    // Values of masked columns are masked, under any name they had.
+    fn export_changes(&mut self) {
+        let mut masked_names: HashSet<&str> = self.masked_columns.iter().map(|&col| self.csv_header[col].as_str()).collect();
+        for edit in self.edits.iter().rev() {
+            if let Edit::RenamedColumn { old, new } = edit
+                && masked_names.contains(new.as_str())
+            {
+                masked_names.insert(old);
+            }
+        }
+        let value = |column: &str, value: &str| if masked_names.contains(column) { pii::mask(value) } else { value.to_string() };
+        let mut changes = Table::new();
+        for edit in &self.edits {
+            match edit {
+                Edit::DeletedRow { row_num, cells } => {
+                    for (column, cell) in cells {
+                        changes.push_row(["delete", &row_num.to_string(), column, &value(column, cell), ""]);
+                    }
+                }
+                Edit::RenamedColumn { old, new } => changes.push_row(["rename", "1", old, old, new]),
+                Edit::EditedCell { row_num, column, old, new } => {
+                    changes.push_row(["edit", &row_num.to_string(), column, &value(column, old), &value(column, new)])
+                }
+                Edit::TransformedColumn { column, how } => changes.push_row(["transform", "", column, "", how]),
+                Edit::DeletedColumn { column } => changes.push_row(["delete column", "", column, "", ""]),
+                Edit::RepairedRow { row_num, fields, columns } => {
+                    changes.push_row(["repair", &row_num.to_string(), "", &fields.to_string(), &columns.to_string()])
+                }
+            }
+        }
+        let header = CHANGE_FIELDS.map(str::to_string).to_vec();
+        self.export_derived_table(move || (header, changes));
+    }
+
+    // Ask for a file and write a table computed on the job thread to it, in
+    // the format of the file's extension. Never marks the file as saved.
+    fn export_derived_table<F>(&mut self, table: F)
+    where
+        F: FnOnce() -> (Vec<String>, Table) + Send + 'static,
+    {
+        let Some(path) = ExportFormat::add_filters(FileDialog::new()).save_file() else {
+            return;
+        };
+        let Some(path) = path.to_str().map(str::to_string) else {
+            return;
+        };
+        let format = ExportFormat::from_path(&path, &self.export_settings);
+        let edit_generation = self.edit_generation;
+        let label = tr!("Saving {}", file_name(&path));
+        self.spawn_job(JobKind::Save, label, move |progress| {
+            let (header, data) = table();
+            let result =
+                save_file(&path, format, &header, &data, &Selection::default(), None, progress)
+                    .map_err(|err| err.to_string());
+            JobResult::Saved {
+                path,
+                whole_file: false,
+                edit_generation,
+                result,
+                verification: None,
+            }
+        });
+    }
+
+    // The chosen file name is numbered for each chunk
+    fn export_chunks(&mut self) {
+        let dialog = ExportFormat::add_filters(FileDialog::new()).set_file_name("data.csv");
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let format = ExportFormat::from_path(&path.to_string_lossy(), &self.export_settings);
+        let chunk_rows = self.export_settings.chunk_rows.max(1);
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let view = self.view_selection();
+        let edit_generation = self.edit_generation;
+        let masked = self.masked_columns();
+        let label = tr!("Saving {}", file_name(&path.to_string_lossy()));
+        self.spawn_job(JobKind::Save, label, move |progress| {
+            let data = masked_data(data, &masked);
+            let files = split_into_chunks(&path, chunk_rows, &data, &view);
+            let result = save_files(files, &format, &header, &data, progress).map_err(|err| err.to_string());
+            JobResult::Saved {
+                path: path.to_string_lossy().into_owned(),
+                whole_file: false,
+                edit_generation,
+                result,
+                verification: None,
+            }
+        });
+    }
+
+    // Each row of the view through a template file, for generating config
+    // blocks, YAML stanzas, emails and the like
+    fn export_with_template(&mut self) {
+        let mut dialog = FileDialog::new().set_title(tr("Choose a row template"));
+        if let Some(dir) = Path::new(&self.export_settings.template_path).parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(template_path) = dialog.pick_file() else {
+            return;
+        };
+        self.export_settings.template_path = template_path.to_string_lossy().into_owned();
+        self.save_export_settings();
+        let Some(path) = FileDialog::new().set_title(tr("Save generated text")).save_file() else {
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let view = self.view_selection();
+        let edit_generation = self.edit_generation;
+        let masked = self.masked_columns();
+        let label = tr!("Saving {}", file_name(&path));
+        self.spawn_job(JobKind::Save, label, move |progress| {
+            let data = masked_data(data, &masked);
+            let result = std::fs::read_to_string(&template_path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| RowTemplate::parse(&text, &header))
+                .and_then(|template| {
+                    save_file(&path, ExportFormat::Template(template), &header, &data, &view, None, progress)
+                        .map_err(|err| err.to_string())
+                });
+            JobResult::Saved {
+                path,
+                whole_file: false,
+                edit_generation,
+                result,
+                verification: None,
+            }
+        });
+    }
+
+    // The format is picked by the extension of the chosen file
+    fn export_as(&mut self, selection: Selection) {
+        let dialog = ExportFormat::add_filters(FileDialog::new());
+        if let Some(path) = dialog.save_file()
+            && let Some(path_str) = path.to_str()
+        {
+            let format = ExportFormat::from_path(path_str, &self.export_settings);
+            self.start_save(path_str.to_string(), format, selection);
+        }
+    }
+
+    fn can_save(&self) -> bool {
+        self.pager.is_none() && !self.is_preview && !self.has_job(JobKind::Save)
+    }
+
+    fn can_export(&self) -> bool {
+        !self.csv_header.is_empty() && !self.has_job(JobKind::Save)
+    }
+
+    fn clear_search(&mut self) {
+        self.cancel_jobs(JobKind::Search);
+        self.search_query.clear();
+        self.value_filter = None;
+        self.invalid_filter = None;
+        self.invisible_filter = false;
+        self.search_results = None;
+        self.last_search = None;
+        self.summary_cache = None;
+        self.view_changed();
+    }
+
+    // Shared by the toolbar buttons and the keyboard shortcuts
+    fn run_action(&mut self, action: Action) {
+        // Paging only applies while pages are showing
+        let paging = !self.infinite_scroll && self.search_results.is_none() && self.selected_row.is_none();
+        match action {
+            Action::OpenFile => {
+                if let Some(path) = pick_csv_file() {
+                    self.open_csv(&path);
+                }
+            }
+            Action::SaveFile => {
+                if self.can_save() {
+                    self.show_save_options = true;
+                }
+            }
+            Action::ExportView => {
+                if self.can_export() {
+                    self.export_as(self.view_selection());
+                }
+            }
+            Action::ExportSelection => {
+                if self.can_export() && !self.selected_rows.is_empty() {
+                    self.export_as(self.row_selection());
+                }
+            }
+            Action::FocusSearch => {
+                self.set_panel_open(Panel::Search, true);
+                self.focus_search = true;
+            }
+            Action::ClearSearch => self.clear_search(),
+            Action::NextPage => {
+                if paging && self.current_page + 1 < self.total_pages() {
+                    self.go_to_page(self.current_page + 1);
+                }
+            }
+            Action::PreviousPage => {
+                if paging && self.current_page > 0 {
+                    self.go_to_page(self.current_page - 1);
+                }
+            }
+            Action::ToggleColumnControls => {
+                let open = self.layout.is_open(Panel::Columns);
+                self.set_panel_open(Panel::Columns, !open);
+            }
+            Action::ToggleSummaryFooter => self.show_summary_footer = !self.show_summary_footer,
+            Action::TogglePerfOverlay => self.show_perf_overlay = !self.show_perf_overlay,
+            Action::CommandPalette => {
+                self.palette = match self.palette {
+                    Some(_) => None,
+                    None => Some(Palette::default()),
+                };
+            }
+            Action::ZoomIn => self.set_zoom(self.zoom * ZOOM_STEP),
+            Action::ZoomOut => self.set_zoom(self.zoom / ZOOM_STEP),
+            Action::ResetZoom => self.set_zoom(1.0),
+        }
+    }
+
+    // Scale of the whole UI, table text included, on top of the display's own
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = ((zoom * 100.0).round() / 100.0).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.config.set("zoom", self.zoom.to_string());
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+    }
+
+    // Everything the command palette offers, in the order listed when the
+    // query is empty
+    fn palette_entries(&self) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = Action::ALL
+            .into_iter()
+            .filter(|&action| action != Action::CommandPalette)
+            .map(|action| Entry {
+                label: action.label().to_string(),
+                hint: Some(shortcuts::format_shortcut(&self.shortcuts.get(action))),
+                command: Command::Run(action),
+            })
+            .collect();
+        entries.push(Entry {
+            label: tr("Edit keyboard shortcuts").to_string(),
+            hint: None,
+            command: Command::EditShortcuts,
+        });
+        if self.csv_header.is_empty() {
+            return entries;
+        }
+
+        let entry = |label: String, command: Command| Entry {
+            label,
+            hint: None,
+            command,
+        };
+        if self.sort.is_some() {
+            entries.push(entry(tr("Clear sort").to_string(), Command::ClearSort));
+        }
+        for (col, name) in self.csv_header.iter().enumerate() {
+            entries.push(entry(tr!("Sort by {} ascending", name), Command::Sort(col, false)));
+            entries.push(entry(tr!("Sort by {} descending", name), Command::Sort(col, true)));
+        }
+        for (col, name) in self.csv_header.iter().enumerate() {
+            let label = if self.visible_columns.get(col).copied().unwrap_or(false) {
+                tr!("Hide column {}", name)
+            } else {
+                tr!("Show column {}", name)
+            };
+            entries.push(entry(label, Command::ToggleColumn(col)));
+        }
+        entries.push(entry(tr("Show all columns").to_string(), Command::ShowAllColumns));
+        entries.push(entry(tr("Hide all columns").to_string(), Command::HideAllColumns));
+        entries.push(entry(tr("Hide blank columns").to_string(), Command::HideBlankColumns));
+        entries.push(entry(tr("Remove empty rows and columns").to_string(), Command::RemoveEmpty));
+        entries.push(entry(tr("Standardize decimal separators…").to_string(), Command::DecimalSeparators));
+        entries.push(entry(tr("Check spelling…").to_string(), Command::CheckSpelling));
+        entries.push(entry(tr("Auto-fit column widths").to_string(), Command::AutoFitWidths));
+        entries
+    }
+
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::Run(action) => self.run_action(action),
+            Command::Sort(col, descending) => {
+                self.sort = Some((col, descending));
+                self.apply_sort();
+            }
+            Command::ClearSort => {
+                self.sort = None;
+                self.apply_sort();
+            }
+            Command::ToggleColumn(col) => {
+                if let Some(visible) = self.visible_columns.get_mut(col) {
+                    *visible = !*visible;
+                }
+            }
+            Command::ShowAllColumns => self.toggle_all_columns(true),
+            Command::HideAllColumns => self.toggle_all_columns(false),
+            Command::HideBlankColumns => self.hide_blank_columns(),
+            Command::RemoveEmpty => self.remove_empty(),
+            Command::DecimalSeparators => self.open_decimal_separators(None),
+            Command::CheckSpelling => self.open_spelling(),
+            Command::AutoFitWidths => {
+                let columns = self.visible_column_indices();
+                self.auto_fit_columns(ctx, &columns);
+            }
+            Command::EditShortcuts => self.show_shortcuts = true,
+        }
+    }
+
+    fn spawn_job<F>(&mut self, kind: JobKind, label: String, work: F)
+    where
+        F: FnOnce(&Progress) -> JobResult + Send + 'static,
+    {
+        self.jobs.push((kind, Job::spawn(label, work)));
+    }
+
+    fn has_job(&self, kind: JobKind) -> bool {
+        self.jobs.iter().any(|(job_kind, _)| *job_kind == kind)
+    }
+
+    // Ask the jobs of `kind` to stop and forget them; whatever they were
+    // computing is never applied
+    fn cancel_jobs(&mut self, kind: JobKind) {
+        self.jobs.retain(|(job_kind, job)| {
+            if *job_kind == kind {
+                job.progress().cancel();
+            }
+            *job_kind != kind
+        });
+    }
+
+    // Apply the results of jobs that have finished
+    fn poll_jobs(&mut self) {
+        let (finished, running): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.jobs).into_iter().partition(|(_, job)| job.is_finished());
+        self.jobs = running;
+        for (_, job) in finished {
+            let label = job.label.clone();
+            match job.join() {
+                Some(result) => self.finish_job(result),
+                None => eprintln!("Error: {} failed unexpectedly", label),
+            }
+        }
+    }
+
+    fn finish_job(&mut self, result: JobResult) {
+        match result {
+            JobResult::Opened {
+                path,
+                file: Ok(file),
+                elapsed,
+            } => self.show_file(path, file, elapsed),
+            JobResult::Opened { file: Err(err), .. } => eprintln!("Error opening CSV: {}", err),
+            JobResult::Saved {
+                path,
+                whole_file,
+                edit_generation,
+                result: Ok(completed),
+                verification,
+            } => {
+                let verified = match verification {
+                    Some(Ok(rows)) => {
+                        self.announcement = tr!("Saved and checked {} rows", rows);
+                        true
+                    }
+                    Some(Err(problem)) => {
+                        eprintln!("Error checking {}: {}", path, problem);
+                        self.save_problem = Some((path.clone(), problem));
+                        false
+                    }
+                    None => true,
+                };
+                // A file that doesn't read back as written isn't counted as saved
+                if completed && whole_file && verified {
+                    self.saved_generation = edit_generation;
+                    self.file_path = Some(path);
+                    // The saved file is the new original, unless edits were made meanwhile
+                    if edit_generation == self.edit_generation {
+                        self.edits.clear();
+                        self.original_rows.clear();
+                    }
+                }
+            }
+            JobResult::Saved { result: Err(err), .. } => eprintln!("Error saving CSV: {}", err),
+            JobResult::Sorted {
+                generation,
+                order,
+                elapsed,
+            } => {
+                if generation == self.data_generation {
+                    self.sort_order = Arc::new(order);
+                    self.perf.sort_time = Some(elapsed);
+                    self.sort_order_changed();
+                }
+            }
+            JobResult::Searched {
+                generation,
+                last_search,
+                results,
+                elapsed,
+            } => {
+                if generation == self.data_generation {
+                    self.announcement = tr!("{} rows match", results.len());
+                    self.search_results = Some(Arc::new(results));
+                    self.last_search = last_search;
+                    self.perf.search_time = Some(elapsed);
+                    self.selected_row = None;
+                    self.summary_cache = None;
+                    self.view_changed();
+                }
+            }
+            JobResult::Validated { generation, report } => {
+                if generation == self.data_generation {
+                    self.announcement = tr!("{} problems found", report.total);
+                    self.validation = Some((generation, report));
+                    self.set_panel_open(Panel::Validation, true);
+                }
+            }
+            JobResult::Scanned { generation, report } => {
+                if generation == self.data_generation {
+                    self.announcement = tr!("{} cells with invisible characters", report.cells);
+                    self.invisible_scan = Some((generation, report));
+                    self.set_panel_open(Panel::Validation, true);
+                }
+            }
+            JobResult::CheckedDates { generation, report } => {
+                if generation == self.data_generation {
+                    let problems: usize = report.columns.iter().map(|column| column.counts.iter().sum::<usize>()).sum();
+                    self.announcement = tr!("{} date columns checked, {} problems found", report.columns.len(), problems);
+                    self.date_check = Some((generation, report));
+                    self.set_panel_open(Panel::Validation, true);
+                }
+            }
+            JobResult::Scored { generation, report } => {
+                if generation == self.data_generation {
+                    self.announcement = tr!("Quality score {:.0}", report.score());
+                    self.quality = Some((generation, report));
+                }
+            }
+            JobResult::CheckedSpelling { generation, report } => {
+                if let Some(state) = &mut self.spelling
+                    && generation == self.data_generation
+                {
+                    self.announcement = tr!("{} cells with unknown words", report.suspect_cells);
+                    state.report = Some((generation, report));
+                }
+            }
+            JobResult::FoundPersonalData { generation, report } => {
+                if generation == self.data_generation {
+                    let columns = report.counts.iter().filter(|counts| counts.iter().any(|&count| count > 0)).count();
+                    self.announcement = tr!("Personal data found in {} columns", columns);
+                    self.personal_data = Some((generation, report));
+                }
+            }
+            JobResult::Cancelled => {}
+        }
+    }
+
+    fn show_jobs_panel(&mut self, ctx: &egui::Context) {
+        let mut cancelled = None;
+        egui::TopBottomPanel::bottom("jobs_panel").show(ctx, |ui| {
+            for (idx, (_, job)) in self.jobs.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(&job.label);
+                    let bar = match job.progress().fraction() {
+                        Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+                        None => egui::ProgressBar::new(0.0).animate(true),
+                    };
+                    ui.add(bar.desired_width(200.0));
+                    if ui.button(tr("Cancel")).clicked() {
+                        cancelled = Some(idx);
+                    }
+                });
+            }
+        });
+        if let Some(idx) = cancelled {
+            let (_, job) = self.jobs.remove(idx);
+            job.progress().cancel();
+        }
+    }
+
+    // The selected rows in view order, visible columns only
+    fn row_selection(&self) -> Selection {
+        Selection {
+            rows: Some(Arc::new(self.selected_in_view_order())),
+            columns: self.view_selection().columns,
+        }
+    }
+
+    // The rows and columns on screen: search matches in sort order, visible
+    // columns only. Only indices are copied, never cells.
+    fn view_selection(&self) -> Selection {
+        let rows = match &self.search_results {
+            Some(results) => Some(Arc::clone(results)),
+            None if !self.sort_order.is_empty() => Some(Arc::clone(&self.sort_order)),
+            None => None,
+        };
+        let columns = if self.visible_column_count() == self.csv_header.len() {
+            None
+        } else {
+            Some(self.visible_column_indices())
+        };
+        Selection { rows, columns }
+    }
+
+    // Keep the first row of the current page in view when the size changes
+    fn set_page_size(&mut self, size: usize) {
+        let first_row = self.current_page.saturating_mul(self.rows_per_page);
+        self.rows_per_page = size;
+        self.scroll_rows = size;
+        if let Some(pager) = self.pager.as_mut() {
+            pager.set_page_size(size);
+        }
+        self.go_to_page(first_row / size);
+    }
+
+    fn go_to_page(&mut self, page: usize) {
+        self.current_page = page;
+        self.load_disk_page();
+        self.view_changed();
+        self.announcement = tr!("Page {} of {}", page + 1, self.total_pages());
+    }
+
+    // Mark the displayed rows as changed so cached row heights are re-measured
+    fn view_changed(&mut self) {
+        self.view_generation += 1;
+        self.focused_cell = None;
+    }
+
+    fn clear_row_selection(&mut self) {
+        self.selected_rows.clear();
+        self.selection_anchor = None;
+        self.dragging_rows = false;
+    }
+
+    // A click selects one row, Ctrl+click adds or removes a row and
+    // Shift+click selects the range from the last clicked row
+    fn click_row(&mut self, pos: usize, modifiers: egui::Modifiers) {
+        let Some(idx) = self.displayed_index(pos) else {
+            return;
+        };
+        if modifiers.shift && self.select_range_to(pos) {
+            return;
+        }
+        if modifiers.command {
+            if !self.selected_rows.remove(&idx) {
+                self.selected_rows.insert(idx);
+            }
+        } else {
+            self.selected_rows = HashSet::from([idx]);
+        }
+        self.selection_anchor = Some(idx);
+    }
+
+    // Replace the selection with the displayed rows from the anchor to `pos`.
+    // False when the anchor isn't displayed, e.g. after turning the page.
+    fn select_range_to(&mut self, pos: usize) -> bool {
+        let displayed = self.displayed_indices();
+        let Some(anchor) = self.selection_anchor.and_then(|anchor| displayed.iter().position(|&idx| idx == anchor))
+        else {
+            return false;
+        };
+        let end = pos.max(anchor).min(displayed.len().saturating_sub(1));
+        self.selected_rows = displayed[pos.min(anchor)..=end].iter().copied().collect();
+        true
+    }
+
+    // Selected data indices in the order the table shows them
+    fn selected_in_view_order(&self) -> Vec<usize> {
+        if self.sort_order.is_empty() {
+            let mut rows: Vec<usize> = self.selected_rows.iter().copied().collect();
+            rows.sort_unstable();
+            rows
+        } else {
+            self.sort_order.iter().copied().filter(|idx| self.selected_rows.contains(idx)).collect()
+        }
+    }
+
+    // Selected rows as tab-separated lines, for pasting into a spreadsheet
+    fn selected_rows_text(&self) -> String {
+        let lines: Vec<String> = self
+            .selected_in_view_order()
+            .into_iter()
+            .map(|idx| self.csv_data.row(idx).iter().collect::<Vec<_>>().join("\t"))
+            .collect();
+        lines.join("\n")
+    }
+
+    // Rebuild the table without the selected rows. The sort order and search
+    // results are renumbered rather than recomputed, so the view stays put.
+    fn delete_selected_rows(&mut self) {
+        if self.pager.is_some() || self.selected_rows.is_empty() {
+            return;
+        }
+        let mut deleted: Vec<usize> = self.selected_rows.iter().copied().collect();
+        deleted.sort_unstable();
+        self.delete_rows(&deleted);
+    }
+
+    // `deleted` holds data indices in ascending order
+    fn delete_rows(&mut self, deleted: &[usize]) {
+        let original = |idx: usize| self.original_rows.get(idx).copied().unwrap_or(idx);
+        for &idx in deleted {
+            let cells = self.csv_header.iter().cloned().zip(self.csv_data.row(idx).iter().map(str::to_string));
+            self.edits.push(Edit::DeletedRow {
+                row_num: original(idx) + 2,
+                cells: cells.collect(),
+            });
+        }
+        self.original_rows = (0..self.csv_data.len())
+            .filter(|idx| deleted.binary_search(idx).is_err())
+            .map(original)
+            .collect();
+        let mut data = Table::new();
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            if deleted.binary_search(&idx).is_err() {
+                data.push_row(row.iter());
+            }
+        }
+        data.intern_low_cardinality_columns();
+        let renumber = |indices: &[usize]| -> Vec<usize> {
+            indices
+                .iter()
+                .filter(|idx| deleted.binary_search(idx).is_err())
+                .map(|&idx| idx - deleted.partition_point(|&gone| gone < idx))
+                .collect()
+        };
+        if !self.sort_order.is_empty() {
+            self.sort_order = Arc::new(renumber(&self.sort_order));
+        }
+        if let Some(results) = &self.search_results {
+            self.search_results = Some(Arc::new(renumber(results)));
+        }
+        if !self.invalid_utf8.is_empty() {
+            let kept = self.invalid_utf8.iter().filter_map(|cell| match cell.row {
+                Some(idx) if deleted.binary_search(&idx).is_ok() => None,
+                Some(idx) => Some(InvalidCell {
+                    row: Some(idx - deleted.partition_point(|&gone| gone < idx)),
+                    ..cell.clone()
+                }),
+                None => Some(cell.clone()),
+            });
+            self.invalid_utf8 = Arc::new(kept.collect());
+        }
+        // Jobs still running were started on the old rows, so start them again
+        let resort = self.has_job(JobKind::Sort);
+        let research = self.has_job(JobKind::Search);
+        self.csv_data = Arc::new(data);
+        self.data_generation += 1;
+        self.edit_generation += 1;
+        self.summary_cache = None;
+        self.clear_row_selection();
+        self.current_page = self.current_page.min(self.total_pages() - 1);
+        if resort {
+            self.apply_sort();
+        }
+        if research {
+            self.run_search();
+        }
+        self.view_changed();
+    }
+
+    // `removed` holds column indices in ascending order. Windows and filters
+    // that refer to a removed column are closed; the rest are renumbered.
+    fn delete_columns(&mut self, removed: &[usize]) {
+        let remap = |col: usize| removed.binary_search(&col).is_err().then(|| col - removed.partition_point(|&gone| gone < col));
+        let mut data = Table::new();
+        for row in self.csv_data.rows() {
+            data.push_row(row.iter().enumerate().filter(|(col, _)| removed.binary_search(col).is_err()).map(|(_, cell)| cell));
+        }
+        for &col in removed {
+            self.edits.push(Edit::DeletedColumn {
+                column: self.csv_header[col].clone(),
+            });
+        }
+        let keep = |col: &usize| removed.binary_search(col).is_err();
+        self.csv_header = self.csv_header.iter().enumerate().filter(|(col, _)| keep(col)).map(|(_, name)| name.clone()).collect();
+        self.visible_columns = self.visible_columns.iter().enumerate().filter(|(col, _)| keep(col)).map(|(_, &visible)| visible).collect();
+        self.column_aggregates =
+            self.column_aggregates.iter().enumerate().filter(|(col, _)| keep(col)).map(|(_, &aggregate)| aggregate).collect();
+        self.column_widths = self.column_widths.iter().enumerate().filter(|(col, _)| keep(col)).map(|(_, &width)| width).collect();
+        self.masked_columns = self.masked_columns.iter().filter_map(|&col| remap(col)).collect();
+        self.search_column = self.search_column.and_then(remap);
+        self.invalid_filter = self.invalid_filter.and_then(remap);
+        self.value_filter = self.value_filter.take().and_then(|(col, values)| Some((remap(col)?, values)));
+        self.sort = self.sort.and_then(|(col, descending)| Some((remap(col)?, descending)));
+        if self.sort.is_none() {
+            self.sort_order = Arc::default();
+        }
+        self.focused_cell = self.focused_cell.and_then(|(row, col)| Some((row, remap(col)?)));
+        if !self.invalid_utf8.is_empty() {
+            let kept = self.invalid_utf8.iter().filter_map(|cell| Some(InvalidCell { col: remap(cell.col)?, ..cell.clone() }));
+            self.invalid_utf8 = Arc::new(kept.collect());
+        }
+        self.row_detail = None;
+        self.renaming_column = None;
+        self.pseudonymizing = None;
+        self.normalizing = None;
+        self.reference_check = None;
+        self.decimal_separators = None;
+        self.unit_conversion = None;
+        self.spelling = None;
+        self.phone_formatting = None;
+        self.currency_normalizing = None;
+        self.json_flattening = None;
+        self.value_picker = None;
+        self.split_export = None;
+        self.ragged_repair = None;
+        self.first_scroll_column = 0;
+        self.frozen_columns = self.frozen_columns.min(self.csv_header.len());
+        self.table_generation += 1;
+        self.replace_data(data);
+    }
+
+    // Rows with nothing but blank cells, and columns with no name and nothing
+    // but blank cells, as left by spreadsheets that export trailing commas. A
+    // named column is kept even if empty, since the schema may expect it.
+    fn remove_empty(&mut self) {
+        if self.pager.is_some() {
+            return;
+        }
+        let mut filled = vec![false; self.csv_header.len()];
+        let mut empty_rows = Vec::new();
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            let mut empty = true;
+            for (col, cell) in row.iter().enumerate() {
+                if !cell.trim().is_empty() {
+                    empty = false;
+                    if let Some(filled) = filled.get_mut(col) {
+                        *filled = true;
+                    }
+                }
+            }
+            if empty {
+                empty_rows.push(idx);
+            }
+        }
+        let empty_columns: Vec<usize> = (0..filled.len()).filter(|&col| !filled[col] && self.csv_header[col].trim().is_empty()).collect();
+        if !empty_rows.is_empty() {
+            self.delete_rows(&empty_rows);
+        }
+        if !empty_columns.is_empty() {
+            self.delete_columns(&empty_columns);
+        }
+        self.announcement = tr!("Removed {} empty rows and {} empty columns", empty_rows.len(), empty_columns.len());
+    }
+
+    // Arrow keys move the focused cell, PageUp/PageDown turn pages, Home/End
+    // jump to the ends of the row (with Ctrl, of the data) and Enter opens the
+    // focused cell. With rows selected, Ctrl+C copies and Delete removes them.
+    fn handle_grid_keys(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() || self.cell_viewer.is_some() {
+            return;
+        }
+        let columns = self.visible_column_indices();
+        let row_count = self.displayed_rows().len();
+        if columns.is_empty() || row_count == 0 {
+            return;
+        }
+        let pressed = |key| ctx.input(|i| i.key_pressed(key));
+        if !self.selected_rows.is_empty() {
+            if ctx.input(|i| i.events.iter().any(|event| matches!(event, egui::Event::Copy))) {
+                let text = self.selected_rows_text();
+                ctx.output_mut(|o| o.copied_text = text);
+                return;
+            }
+            if pressed(egui::Key::Delete) {
+                self.delete_selected_rows();
+                return;
+            }
+            if pressed(egui::Key::Escape) && self.focused_cell.is_none() {
+                self.clear_row_selection();
+                return;
+            }
+        }
+        let (mut row, mut col_pos) = match self.focused_cell {
+            Some((row, col)) => (row.min(row_count - 1), columns.iter().position(|&c| c == col).unwrap_or(0)),
+            None => (0, 0),
+        };
+
+        if let Some((row, col)) = self.focused_cell {
+            if pressed(egui::Key::Enter) {
+                self.open_cell_viewer(row, col);
+                return;
+            }
+            if pressed(egui::Key::Escape) {
+                self.focused_cell = None;
+                self.clear_row_selection();
+                return;
+            }
+        }
+        if !GRID_NAV_KEYS.iter().any(|&key| pressed(key)) {
+            return;
+        }
+
+        let ctrl = ctx.input(|i| i.modifiers.command);
+        let paging = !self.infinite_scroll && self.search_results.is_none() && self.selected_row.is_none();
+        let last_page = self.total_pages() - 1;
+        let mut page = self.current_page;
+        if pressed(egui::Key::ArrowDown) {
+            if row + 1 < row_count {
+                row += 1;
+            } else if paging && page < last_page {
+                page += 1;
+                row = 0;
+            }
+        }
+        if pressed(egui::Key::ArrowUp) {
+            if row > 0 {
+                row -= 1;
+            } else if paging && page > 0 {
+                page -= 1;
+                row = usize::MAX;
+            }
+        }
+        if pressed(egui::Key::ArrowRight) {
+            col_pos = (col_pos + 1).min(columns.len() - 1);
+        }
+        if pressed(egui::Key::ArrowLeft) {
+            col_pos = col_pos.saturating_sub(1);
+        }
+        if pressed(egui::Key::PageDown) {
+            if paging {
+                page = (page + 1).min(last_page);
+            } else {
+                row += PAGE_JUMP_ROWS;
+            }
+        }
+        if pressed(egui::Key::PageUp) {
+            if paging {
+                page = page.saturating_sub(1);
+            } else {
+                row = row.saturating_sub(PAGE_JUMP_ROWS);
+            }
+        }
+        if pressed(egui::Key::Home) {
+            col_pos = 0;
+            if ctrl {
+                row = 0;
+                if paging {
+                    page = 0;
+                }
+            }
+        }
+        if pressed(egui::Key::End) {
+            col_pos = columns.len() - 1;
+            if ctrl {
+                row = usize::MAX;
+                if paging {
+                    page = last_page;
+                }
+            }
+        }
+
+        if page != self.current_page {
+            self.go_to_page(page);
+        }
+        let row_count = self.displayed_rows().len();
+        self.focused_cell = Some((row.min(row_count.saturating_sub(1)), columns[col_pos]));
+        self.scroll_to_focus = true;
+    }
+
+    fn open_cell_viewer(&mut self, row: usize, col: usize) {
+        let mut text = self.displayed_rows().get(row).and_then(|r| r.get(col)).unwrap_or("").to_string();
+        if self.masked_columns.contains(&col) {
+            text = pii::mask(&text);
+        }
+        let pretty = Json::from_cell(&text).map(|json| json.pretty());
+        self.cell_viewer = Some((self.csv_header[col].clone(), text, pretty));
+    }
+
+    // Right-click menu actions on a displayed cell
+    fn run_cell_action(&mut self, ctx: &egui::Context, row: usize, col: usize, action: CellAction) {
+        let Some(idx) = self.displayed_indices().get(row).copied() else {
+            return;
+        };
+        let value = self.csv_data.row(idx).get(col).unwrap_or("").to_string();
+        // Copied and shown as the grid shows them
+        let cells = self.masked_row(idx);
+        match action {
+            CellAction::CopyCell(format) => {
+                let text = export::copy_cell(format, &self.csv_header[col], cells.get(col).map_or("", String::as_str));
+                ctx.output_mut(|o| o.copied_text = text);
+            }
+            CellAction::CopyRow(format) => {
+                let header: Vec<&str> = self.csv_header.iter().map(String::as_str).collect();
+                let cells: Vec<&str> = cells.iter().map(String::as_str).collect();
+                let text = export::copy_row(format, &header, &cells);
+                ctx.output_mut(|o| o.copied_text = text);
+            }
+            CellAction::FilterTo | CellAction::Exclude => {
+                self.selected_row = None;
+                self.value_filter = None;
+                self.invalid_filter = None;
+                self.invisible_filter = false;
+                self.search_query = value;
+                self.search_column = Some(col);
+                self.search_exact = true;
+                self.search_invert = action == CellAction::Exclude;
+                self.run_search();
+            }
+            CellAction::RowDetail => {
+                let row_num = idx + self.row_offset() + 2;
+                self.row_detail = Some((row_num, cells));
+                self.set_panel_open(Panel::Details, true);
+            }
+        }
+    }
+
+    // Right-click menu actions on a column header
+    fn run_header_action(&mut self, ctx: &egui::Context, col: usize, action: HeaderAction) {
+        match action {
+            HeaderAction::SortAscending | HeaderAction::SortDescending => {
+                self.sort = Some((col, action == HeaderAction::SortDescending));
+                self.apply_sort();
+            }
+            HeaderAction::Hide => self.visible_columns[col] = false,
+            HeaderAction::AutoFit => self.auto_fit_columns(ctx, &[col]),
+            HeaderAction::Rename => self.renaming_column = Some((col, self.csv_header[col].clone())),
+            HeaderAction::Statistics => {
+                let stats = column_statistics(&self.filtered_rows(), col);
+                self.column_stats = Some((self.csv_header[col].clone(), stats));
+                self.set_panel_open(Panel::Details, true);
+            }
+            HeaderAction::FilterByValues => self.open_value_picker(col),
+            HeaderAction::SplitExport => self.split_export = Some(col),
+            HeaderAction::FilterInvalid => self.filter_invalid(col),
+            HeaderAction::CheckReferences => {
+                self.reference_check = Some(ReferenceCheck {
+                    col,
+                    path: String::new(),
+                    header: Vec::new(),
+                    data: Table::new(),
+                    ref_col: 0,
+                    ignore_case: false,
+                    result: None,
+                })
+            }
+            HeaderAction::Normalize => {
+                self.normalizing = Some(Normalizing {
+                    col,
+                    mapping_path: String::new(),
+                    mapping: Vec::new(),
+                    ignore_case: false,
+                    result: None,
+                })
+            }
+            HeaderAction::DecimalSeparators => self.open_decimal_separators(Some(col)),
+            HeaderAction::ConvertUnits => {
+                let conversion = Conversion::KmToMi;
+                self.unit_conversion = Some(UnitConversion {
+                    col,
+                    conversion,
+                    factor: 1.0,
+                    decimals: 2,
+                    new_column: true,
+                    name: format!("{}_{}", self.csv_header[col], conversion.target_unit()),
+                    result: None,
+                })
+            }
+            HeaderAction::FormatPhones => {
+                let region = self.config.get("phones.region").and_then(phones::region).unwrap_or(&phones::REGIONS[0]);
+                self.phone_formatting = Some(PhoneFormatting {
+                    col,
+                    region,
+                    format: phones::Format::E164,
+                    new_column: false,
+                    name: format!("{}_formatted", self.csv_header[col]),
+                    result: None,
+                })
+            }
+            HeaderAction::NormalizeCurrency => {
+                let dollar = self.config.get("currency.dollar").unwrap_or("USD").to_string();
+                let convention = currency::detect_convention(self.csv_data.rows().map(|row| row.get(col).unwrap_or("")), &dollar);
+                self.currency_normalizing = Some(CurrencyNormalizing {
+                    col,
+                    convention,
+                    dollar,
+                    decimals: 2,
+                    split_code: true,
+                    code_name: format!("{}_currency", self.csv_header[col]),
+                    result: None,
+                })
+            }
+            HeaderAction::FlattenJson => self.open_json_flattening(col),
+            HeaderAction::Pseudonymize => {
+                self.pseudonymizing = Some(Pseudonymizing {
+                    col,
+                    hash: true,
+                    salt: pseudonym::new_salt(),
+                    prefix: "ID".to_string(),
+                })
+            }
+            HeaderAction::ToggleMask => {
+                if !self.masked_columns.remove(&col) {
+                    self.masked_columns.insert(col);
+                }
+            }
+            // Every row of the view, not just the page on screen
+            HeaderAction::CopyColumn(format) => {
+                let (data, view) = self.masked_copy(self.view_selection());
+                let values = view.rows(&data).map(|row| row.get(col).unwrap_or(""));
+                let text = export::copy_column(format, &self.csv_header[col], values);
+                ctx.output_mut(|o| o.copied_text = text);
+            }
+        }
+    }
+
+    fn show_rename_column(&mut self, ctx: &egui::Context) {
+        let Some((col, name)) = &mut self.renaming_column else {
+            return;
+        };
+        let col = *col;
+        let mut done = false;
+        let mut apply = false;
+        egui::Window::new(tr("Rename Column")).resizable(false).collapsible(false).show(ctx, |ui| {
+            let field = ui.text_edit_singleline(name);
+            field.request_focus();
+            apply = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            ui.horizontal(|ui| {
+                apply |= ui.button(tr("Rename")).clicked();
+                done = ui.button(tr("Cancel")).clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape));
+            });
+        });
+        if apply {
+            let name = name.trim().to_string();
+            if !name.is_empty() && name != self.csv_header[col] {
+                self.rename_column(col, name);
+                self.apply_schema();
+            }
+            done = true;
+        }
+        if done {
+            self.renaming_column = None;
+        }
+    }
+
+    fn rename_column(&mut self, col: usize, name: String) {
+        let old = std::mem::replace(&mut self.csv_header[col], name.clone());
+        self.edits.push(Edit::RenamedColumn { old, new: name });
+        self.edit_generation += 1;
+    }
+
+    // Shown when a saved file, read back, doesn't match what was written
+    fn show_save_problem(&mut self, ctx: &egui::Context) {
+        let Some((path, problem)) = &self.save_problem else {
+            return;
+        };
+        let mut close = false;
+        egui::Window::new(tr("Saved File Differs")).resizable(false).collapsible(false).show(ctx, |ui| {
+            ui.colored_label(ui.visuals().error_fg_color, tr!("{} doesn't read back as it was written:", file_name(path)));
+            ui.label(problem);
+            ui.label(tr("The disk may be full or failing. Save again, to another disk if you can."));
+            ui.separator();
+            close = ui.button(tr("OK")).clicked();
+        });
+        if close {
+            self.save_problem = None;
+        }
+    }
+
+    // Rows whose field count differs from the header's, offered for repair
+    fn find_ragged_rows(&mut self) {
+        let width = self.csv_header.len();
+        let (mut short, mut long) = (Vec::new(), Vec::new());
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            match row.len().cmp(&width) {
+                std::cmp::Ordering::Less => short.push(idx),
+                std::cmp::Ordering::Greater => long.push(idx),
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        if short.is_empty() && long.is_empty() {
+            self.ragged_repair = None;
+            self.announcement = tr!("Every row has {} fields", width);
+            return;
+        }
+        self.announcement = tr!("{} rows don't have {} fields", short.len() + long.len(), width);
+        self.ragged_repair = Some(RaggedRepair { short, long, spill: true });
+    }
+
+    fn show_ragged_repair(&mut self, ctx: &egui::Context) {
+        let row_offset = self.row_offset();
+        let Some(state) = &mut self.ragged_repair else {
+            return;
+        };
+        let width = self.csv_header.len();
+        let mut repair = false;
+        let mut close = false;
+        egui::Window::new(tr("Ragged Rows")).resizable(false).collapsible(false).show(ctx, |ui| {
+            ui.label(tr!(
+                "The header has {} columns, but {} rows have fewer fields and {} have more.",
+                width,
+                state.short.len(),
+                state.long.len()
+            ));
+            if !state.short.is_empty() {
+                ui.label(tr("Short rows are padded with empty cells."));
+            }
+            if !state.long.is_empty() {
+                ui.radio_value(&mut state.spill, true, tr("Move the extra fields of long rows to an overflow column"));
+                ui.radio_value(&mut state.spill, false, tr("Drop the extra fields of long rows"));
+            }
+            ui.separator();
+            let mut affected: Vec<usize> = state.short.iter().chain(&state.long).copied().collect();
+            affected.sort_unstable();
+            affected.truncate(RAGGED_PREVIEW_ROWS);
+            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                egui::Grid::new("ragged_rows").num_columns(3).striped(true).show(ui, |ui| {
+                    for heading in ["Row", "Fields", "Change"] {
+                        ui.strong(tr(heading));
+                    }
+                    ui.end_row();
+                    for idx in affected {
+                        let row = self.csv_data.row(idx);
+                        let extra: Vec<&str> = row.iter().skip(width).collect();
+                        let change = if row.len() < width {
+                            tr!("Add {} empty cells", width - row.len())
+                        } else if state.spill {
+                            tr!("Move {} to overflow", extra.join(","))
+                        } else {
+                            tr!("Drop {}", extra.join(","))
+                        };
+                        ui.label((idx + row_offset + 2).to_string());
+                        ui.label(row.len().to_string());
+                        ui.add(egui::Label::new(change).wrap(false));
+                        ui.end_row();
+                    }
+                });
+            });
+            let total = state.short.len() + state.long.len();
+            if total > RAGGED_PREVIEW_ROWS {
+                ui.weak(tr!("Listing the first {}", RAGGED_PREVIEW_ROWS));
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                repair = ui
+                    .add_enabled(self.pager.is_none(), egui::Button::new(tr("Repair")))
+                    .on_disabled_hover_text(tr("Files paged from disk can't be edited"))
+                    .clicked();
+                close = ui.button(tr("Not now")).clicked();
+            });
+        });
+        if repair {
+            self.repair_ragged_rows();
+        } else if close {
+            self.ragged_repair = None;
+        }
+    }
+
+    // Pad short rows and cut long ones to the header's width, keeping the
+    // cut fields, joined with commas, in a new last column if chosen
+    fn repair_ragged_rows(&mut self) {
+        let Some(state) = self.ragged_repair.take() else {
+            return;
+        };
+        let width = self.csv_header.len();
+        let spill = state.spill && !state.long.is_empty();
+        let mut data = Table::new();
+        let mut edits = Vec::new();
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            let cells: Vec<&str> = row.iter().collect();
+            if cells.len() != width {
+                edits.push(Edit::RepairedRow {
+                    row_num: self.original_rows.get(idx).copied().unwrap_or(idx) + 2,
+                    fields: cells.len(),
+                    columns: width,
+                });
+            }
+            let kept = cells.iter().copied().chain(std::iter::repeat("")).take(width);
+            if spill {
+                let overflow = cells.get(width..).map_or_else(String::new, |extra| extra.join(","));
+                data.push_row(kept.chain([overflow.as_str()]));
+            } else {
+                data.push_row(kept);
+            }
+        }
+        if spill {
+            edits.push(Edit::TransformedColumn {
+                column: self.add_column("overflow"),
+                how: "overflow of long rows",
+            });
+        }
+        self.announcement = tr!("Repaired {} rows", state.short.len() + state.long.len());
+        self.edits.extend(edits);
+        self.replace_data(data);
+    }
+
+    // Appends a column named `name`, numbered if taken, and returns the name.
+    // The caller gives every row a cell for it before calling replace_data.
+    fn add_column(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut copy = 1;
+        while self.csv_header.contains(&unique) {
+            copy += 1;
+            unique = format!("{}_{}", name, copy);
+        }
+        self.csv_header.push(unique.clone());
+        self.visible_columns.push(true);
+        self.column_aggregates.push(Aggregate::default());
+        self.column_widths.push(DEFAULT_COLUMN_WIDTH);
+        self.table_generation += 1;
+        unique
+    }
+
+    // Offered when a file opens with the same name on several columns, since
+    // anything that finds columns by name would only ever see the first
+    fn show_duplicate_headers(&mut self, ctx: &egui::Context) {
+        let Some(renames) = &self.duplicate_headers else {
+            return;
+        };
+        let mut apply = false;
+        let mut keep = false;
+        egui::Window::new(tr("Duplicate Column Names")).resizable(false).collapsible(false).show(ctx, |ui| {
+            ui.label(tr("Some columns share a name. Rename the repeats so each column can be told apart?"));
+            ui.separator();
+            egui::Grid::new("duplicate_headers").num_columns(3).striped(true).show(ui, |ui| {
+                for (col, new) in renames {
+                    ui.label(tr!("Column {}", col + 1));
+                    ui.label(&self.csv_header[*col]);
+                    ui.label(format!("→ {}", new));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                apply = ui.button(tr("Rename")).clicked();
+                keep = ui.button(tr("Keep names")).clicked();
+            });
+        });
+        if apply {
+            for (col, new) in self.duplicate_headers.take().unwrap_or_default() {
+                self.rename_column(col, new);
+            }
+            self.apply_schema();
+        } else if keep {
+            self.duplicate_headers = None;
+        }
+    }
+
+    fn show_column_stats(&mut self, ui: &mut egui::Ui) {
+        let Some((name, stats)) = &self.column_stats else {
+            return;
+        };
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.strong(tr!("{} statistics", name));
+            close = ui.small_button(tr("Close")).clicked();
+        });
+        egui::Grid::new("column_stats_grid").num_columns(2).show(ui, |ui| {
+            for (label, value) in stats {
+                ui.label(*label);
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+        if self.search_results.is_some() {
+            ui.weak(tr("Computed over the rows matching the search"));
+        }
+        let export = ui
+            .add_enabled(self.can_export(), egui::Button::new(tr("Export column profile…")))
+            .on_hover_text(tr("Save these statistics for every visible column"));
+        if export.clicked() {
+            self.export_profile();
+        }
+        if close {
+            self.column_stats = None;
+        }
+    }
+
+    // List the column's distinct values, pre-checking those of the filter
+    // already applied to it
+    fn open_value_picker(&mut self, col: usize) {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for row in self.csv_data.rows() {
+            *counts.entry(row.get(col).unwrap_or("")).or_default() += 1;
+        }
+        let distinct = counts.len();
+        let mut values: Vec<(String, usize)> =
+            counts.into_iter().map(|(value, count)| (value.to_string(), count)).collect();
+        values.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        values.truncate(MAX_PICKER_VALUES);
+        let checked = match &self.value_filter {
+            Some((filtered, values)) if *filtered == col => values.as_ref().clone(),
+            _ => values.iter().map(|(value, _)| value.clone()).collect(),
+        };
+        self.value_picker = Some(ValuePicker {
+            col,
+            values,
+            distinct,
+            checked,
+            filter: String::new(),
+        });
+    }
+
+    fn show_split_export(&mut self, ctx: &egui::Context) {
+        let Some(col) = self.split_export else {
+            return;
+        };
+        let mut open = true;
+        let mut choose_folder = false;
+        egui::Window::new(tr("Export split by column"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr!("One file per distinct value of {}, with the rows matching the search and the visible columns.", self.csv_header[col]));
+                ui.horizontal(|ui| {
+                    let label = ui.label(tr("File names:"));
+                    ui.text_edit_singleline(&mut self.export_settings.split_template).labelled_by(label.id);
+                });
+                ui.weak(tr("{column}, {value} and {n} are filled in. The extension picks the format."));
+                ui.separator();
+                choose_folder = ui
+                    .add_enabled(self.can_export(), egui::Button::new(tr("Choose folder and export…")))
+                    .clicked();
+            });
+        if !open {
+            self.split_export = None;
+        }
+        if !choose_folder {
+            return;
+        }
+        self.split_export = None;
+        self.save_export_settings();
+        let Some(dir) = FileDialog::new().pick_folder() else {
+            return;
+        };
+        let template = self.export_settings.split_template.clone();
+        let format = ExportFormat::from_path(&template, &self.export_settings);
+        let header = self.csv_header.clone();
+        let data = Arc::clone(&self.csv_data);
+        let view = self.view_selection();
+        let edit_generation = self.edit_generation;
+        let masked = self.masked_columns();
+        let label = tr!("Splitting by {}", self.csv_header[col]);
+        self.spawn_job(JobKind::Save, label, move |progress| {
+            let data = masked_data(data, &masked);
+            let files = split_by_value(&dir, &template, col, &header, &data, &view);
+            let result = save_files(files, &format, &header, &data, progress).map_err(|err| err.to_string());
+            JobResult::Saved {
+                path: dir.to_string_lossy().into_owned(),
+                whole_file: false,
+                edit_generation,
+                result,
+                verification: None,
+            }
+        });
+    }
+
+    fn show_value_picker(&mut self, ctx: &egui::Context) {
+        let Some(picker) = &mut self.value_picker else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new(tr!("Filter {} by values", self.csv_header[picker.col]))
+            .id(egui::Id::new("value_picker"))
+            .open(&mut open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                let filter_field = ui.add(egui::TextEdit::singleline(&mut picker.filter).hint_text(tr("Find values")));
+                a11y::set_name(&filter_field, tr("Find values"));
+                let filter = picker.filter.to_lowercase();
+                let listed = || {
+                    picker
+                        .values
+                        .iter()
+                        .filter(|(value, _)| value.to_lowercase().contains(&filter))
+                };
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Select all")).clicked() {
+                        let values: Vec<String> = listed().map(|(value, _)| value.clone()).collect();
+                        picker.checked.extend(values);
+                    }
+                    if ui.button(tr("Select none")).clicked() {
+                        let values: Vec<String> = listed().map(|(value, _)| value.clone()).collect();
+                        for value in values {
+                            picker.checked.remove(&value);
+                        }
+                    }
+                });
+                if picker.distinct > picker.values.len() {
+                    ui.weak(tr!(
+                        "Showing the {} most common of {} values",
+                        picker.values.len(),
+                        picker.distinct
+                    ));
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (value, count) in picker.values.iter().filter(|(value, _)| value.to_lowercase().contains(&filter)) {
+                        let mut checked = picker.checked.contains(value);
+                        let label = if value.is_empty() {
+                            tr!("(blank) ({})", count)
+                        } else {
+                            format!("{} ({})", value, count)
+                        };
+                        if ui.checkbox(&mut checked, label).changed() {
+                            if checked {
+                                picker.checked.insert(value.clone());
+                            } else {
+                                picker.checked.remove(value);
+                            }
+                        }
+                    }
+                });
+                ui.separator();
+                apply = ui.button(tr("Apply")).clicked();
+            });
+
+        if !open {
+            self.value_picker = None;
+        } else if apply && let Some(picker) = self.value_picker.take() {
+            self.selected_row = None;
+            self.search_query.clear();
+            self.search_invert = false;
+            self.value_filter = Some((picker.col, Arc::new(picker.checked)));
+            self.invalid_filter = None;
+            self.invisible_filter = false;
+            self.run_search();
+        }
+    }
+
+    // Every column of one row as a list, for rows too wide to read in the grid
+    fn show_row_detail(&mut self, ui: &mut egui::Ui) {
+        let Some((row_num, cells)) = &self.row_detail else {
+            return;
+        };
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.strong(tr!("Row {}", row_num));
+            close = ui.small_button(tr("Close")).clicked();
+        });
+        egui::Grid::new("row_detail_grid").num_columns(2).striped(true).show(ui, |ui| {
+            for (header, cell) in self.csv_header.iter().zip(cells) {
+                ui.strong(header);
+                match Json::from_cell(cell) {
+                    Some(json) => ui.add(egui::Label::new(egui::RichText::new(json.pretty()).monospace())),
+                    None => ui.add(egui::Label::new(cell.as_str()).wrap(true)),
+                };
+                ui.end_row();
+            }
+        });
+        if close {
+            self.row_detail = None;
+        }
+    }
+
+    // An open file and a schema with rules, all of which parse
+    fn can_validate(&self) -> bool {
+        !self.csv_header.is_empty()
+            && (!self.schema.columns.is_empty() || !self.schema.row_rules.is_empty())
+            && self.schema.errors().is_empty()
+    }
+
+    fn run_validation(&mut self) {
+        let validator = match self.schema.validator(&self.csv_header) {
+            Ok(validator) => validator,
+            Err(err) => {
+                eprintln!("Error in schema: {}", err);
+                return;
+            }
+        };
+        let data = Arc::clone(&self.csv_data);
+        let generation = self.data_generation;
+        self.cancel_jobs(JobKind::Validate);
+        self.spawn_job(JobKind::Validate, tr("Validating").to_string(), move |progress| match validator.run(&data, progress) {
+            Ok(report) => JobResult::Validated { generation, report },
+            Err(_) => JobResult::Cancelled,
+        });
+    }
+
+    // With the schema's rules if it has any and they compile
+    fn score_quality(&mut self) {
+        let types = self.schema.column_types(&self.csv_header, &self.csv_data);
+        let validator = if self.can_validate() { self.schema.validator(&self.csv_header).ok() } else { None };
+        let data = Arc::clone(&self.csv_data);
+        let generation = self.data_generation;
+        self.quality_started = Some(generation);
+        self.cancel_jobs(JobKind::Score);
+        self.spawn_job(JobKind::Score, tr("Scoring quality").to_string(), move |progress| {
+            let validation = match validator.map(|validator| validator.run(&data, progress)).transpose() {
+                Ok(validation) => validation,
+                Err(_) => return JobResult::Cancelled,
+            };
+            match quality::score(&data, &types, validation.as_ref(), progress) {
+                Ok(report) => JobResult::Scored { generation, report },
+                Err(_) => JobResult::Cancelled,
+            }
+        });
+    }
+
+    // In column order, for export jobs
+    fn masked_columns(&self) -> Vec<usize> {
+        let mut masked: Vec<usize> = self.masked_columns.iter().copied().collect();
+        masked.sort_unstable();
+        masked
+    }
+
+    // What a copy to the clipboard reads: the table, or with columns masked,
+    // a masked copy of only the selected rows
+    fn masked_copy(&self, selection: Selection) -> (Arc<Table>, Selection) {
+        let masked = self.masked_columns();
+        if masked.is_empty() {
+            return (Arc::clone(&self.csv_data), selection);
+        }
+        let mut rows = Table::new();
+        for row in selection.rows(&self.csv_data) {
+            rows.push_row(row.iter());
+        }
+        let selection = Selection {
+            rows: None,
+            columns: selection.columns,
+        };
+        (Arc::new(pii::mask_table(&rows, &masked)), selection)
+    }
+
+    // One row's cells as a copy to the clipboard reads them
+    fn masked_row(&self, idx: usize) -> Vec<String> {
+        let (data, selection) = self.masked_copy(Selection {
+            rows: Some(Arc::new(vec![idx])),
+            columns: None,
+        });
+        selection.rows(&data).next().map_or_else(Vec::new, |row| row.iter().map(str::to_string).collect())
+    }
+
+    fn find_personal_data(&mut self) {
+        let data = Arc::clone(&self.csv_data);
+        let columns = self.csv_header.len();
+        let generation = self.data_generation;
+        self.cancel_jobs(JobKind::FindPersonalData);
+        let label = tr("Finding personal data").to_string();
+        self.spawn_job(JobKind::FindPersonalData, label, move |progress| match pii::scan(&data, columns, progress) {
+            Ok(report) => JobResult::FoundPersonalData { generation, report },
+            Err(_) => JobResult::Cancelled,
+        });
+    }
+
+    fn show_personal_data_window(&mut self, ctx: &egui::Context) {
+        if !self.show_personal_data {
+            return;
+        }
+        let mut open = true;
+        let mut scan = false;
+        egui::Window::new(tr("Personal Data")).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr("Masked columns keep only their last few characters, or an email's first letter and domain, in the grid and in everything saved, exported or copied."));
+            ui.horizontal(|ui| {
+                scan = ui.add_enabled(!self.has_job(JobKind::FindPersonalData), egui::Button::new(tr("Scan again"))).clicked();
+                if ui.button(tr("Unmask all")).clicked() {
+                    self.masked_columns.clear();
+                }
+            });
+            ui.separator();
+            let Some((generation, report)) = &self.personal_data else {
+                ui.weak(tr("Scanning…"));
+                return;
+            };
+            if *generation != self.data_generation {
+                ui.weak(tr("The data has changed since it was scanned. Scan again to update the counts."));
+            }
+            let flagged: Vec<usize> =
+                (0..report.counts.len()).filter(|&col| report.counts[col].iter().any(|&count| count > 0)).collect();
+            if flagged.is_empty() {
+                ui.label(tr("No likely personal data found"));
+                return;
+            }
+            egui::Grid::new("personal_data").num_columns(4).striped(true).show(ui, |ui| {
+                ui.strong(tr("Column"));
+                ui.strong(tr("Found"));
+                ui.strong(tr("Cells"));
+                ui.strong(tr("Mask"));
+                ui.end_row();
+                for &col in &flagged {
+                    let found: Vec<&str> = pii::Kind::ALL
+                        .into_iter()
+                        .filter(|&kind| report.counts[col][kind as usize] > 0)
+                        .map(|kind| kind.label())
+                        .collect();
+                    let cells = report.counts[col].iter().max().copied().unwrap_or(0);
+                    ui.label(&self.csv_header[col]);
+                    ui.label(found.join(", "));
+                    ui.label(tr!("{} of {}", cells, report.filled[col]));
+                    let mut masked = self.masked_columns.contains(&col);
+                    if ui.checkbox(&mut masked, "").changed() {
+                        if masked {
+                            self.masked_columns.insert(col);
+                        } else {
+                            self.masked_columns.remove(&col);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+            if ui.button(tr("Mask all found columns")).clicked() {
+                self.masked_columns.extend(flagged);
+            }
+        });
+        if !open {
+            self.show_personal_data = false;
+        }
+        if scan {
+            self.find_personal_data();
+        }
+    }
+
+    // Checks the text columns with the dictionary last used
+    fn open_spelling(&mut self) {
+        if self.spelling.is_some() {
+            return;
+        }
+        let types = self.schema.column_types(&self.csv_header, &self.csv_data);
+        let columns = (0..types.len()).filter(|&col| types[col] == ColumnType::Text).collect();
+        let ignored = self.config.get("spelling.ignored").unwrap_or("").split(',').filter(|word| !word.is_empty()).map(str::to_string).collect();
+        let dictionary_path = self.config.get("spelling.dictionary").unwrap_or("").to_string();
+        let dictionary = if dictionary_path.is_empty() {
+            None
+        } else {
+            match spelling::Dictionary::load(&dictionary_path) {
+                Ok(dictionary) => Some(Arc::new(dictionary)),
+                Err(err) => {
+                    eprintln!("Error reading dictionary: {}", err);
+                    None
+                }
+            }
+        };
+        self.spelling = Some(Spelling {
+            dictionary_path,
+            dictionary,
+            columns,
+            ignored,
+            report: None,
+        });
+        self.check_spelling();
+    }
+
+    fn check_spelling(&mut self) {
+        let Some(state) = &self.spelling else {
+            return;
+        };
+        let Some(dictionary) = state.dictionary.clone() else {
+            return;
+        };
+        let mut columns: Vec<usize> = state.columns.iter().copied().collect();
+        columns.sort_unstable();
+        let ignored = state.ignored.clone();
+        let data = Arc::clone(&self.csv_data);
+        let generation = self.data_generation;
+        self.cancel_jobs(JobKind::CheckSpelling);
+        let label = tr("Checking spelling").to_string();
+        self.spawn_job(JobKind::CheckSpelling, label, move |progress| {
+            match spelling::check(&data, &columns, &dictionary, &ignored, progress) {
+                Ok(report) => JobResult::CheckedSpelling { generation, report },
+                Err(_) => JobResult::Cancelled,
+            }
+        });
+    }
+
+    fn choose_dictionary(&mut self) {
+        let Some(state) = &mut self.spelling else {
+            return;
+        };
+        let mut dialog = FileDialog::new().add_filter(tr("Word lists"), &["txt", "dic"]);
+        if let Some(dir) = Path::new(&state.dictionary_path).parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.pick_file() else {
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        match spelling::Dictionary::load(&path) {
+            Ok(dictionary) => {
+                state.dictionary = Some(Arc::new(dictionary));
+                state.report = None;
+                self.config.set("spelling.dictionary", &path);
+                state.dictionary_path = path;
+                if let Err(err) = self.config.save() {
+                    eprintln!("Error saving settings: {}", err);
+                }
+            }
+            Err(err) => eprintln!("Error reading dictionary: {}", err),
+        }
+    }
+
+    // Accepts `word` from now on and drops it from the listed results
+    fn ignore_word(&mut self, word: &str) {
+        let Some(state) = &mut self.spelling else {
+            return;
+        };
+        state.ignored.insert(word.to_string());
+        if let Some((_, report)) = &mut state.report {
+            report.words.retain(|(unknown, _)| unknown != word);
+            for (_, _, words) in &mut report.cells {
+                words.retain(|unknown| unknown.to_lowercase() != word);
+            }
+            let before = report.cells.len();
+            report.cells.retain(|(_, _, words)| !words.is_empty());
+            report.suspect_cells -= before - report.cells.len();
+        }
+        let mut ignored: Vec<&str> = state.ignored.iter().map(String::as_str).collect();
+        ignored.sort_unstable();
+        self.config.set("spelling.ignored", ignored.join(","));
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+    }
+
+    fn show_spelling(&mut self, ctx: &egui::Context) {
+        let row_offset = self.row_offset();
+        let Some(state) = &mut self.spelling else {
+            return;
+        };
+        let mut open = true;
+        let mut choose = false;
+        let mut check = false;
+        let mut ignore = None;
+        let mut jump = None;
+        egui::Window::new(tr("Spelling")).open(&mut open).default_width(420.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                choose = ui
+                    .button(tr("Choose dictionary…"))
+                    .on_hover_text(tr("A text file with one word per line, or a Hunspell .dic file"))
+                    .clicked();
+                if let Some(dictionary) = &state.dictionary {
+                    ui.label(tr!("{} ({} words)", file_name(&state.dictionary_path), dictionary.len()));
+                }
+            });
+            ui.label(tr("Columns to check:"));
+            ui.horizontal_wrapped(|ui| {
+                for (col, name) in self.csv_header.iter().enumerate() {
+                    let mut checked = state.columns.contains(&col);
+                    if ui.checkbox(&mut checked, name).changed() {
+                        if checked {
+                            state.columns.insert(col);
+                        } else {
+                            state.columns.remove(&col);
+                        }
+                    }
+                }
+            });
+            ui.separator();
+            check = ui
+                .add_enabled(state.dictionary.is_some() && !state.columns.is_empty(), egui::Button::new(tr("Check")))
+                .on_disabled_hover_text(tr("Choose a dictionary and at least one column"))
+                .clicked();
+            let Some((generation, report)) = &state.report else {
+                return;
+            };
+            if *generation != self.data_generation {
+                ui.weak(tr("The data has changed since it was checked. Check again to update the list."));
+            }
+            if report.suspect_cells == 0 {
+                ui.label(tr("No unknown words"));
+                return;
+            }
+            ui.label(tr!("{} cells with unknown words", report.suspect_cells));
+            ui.columns(2, |columns| {
+                egui::ScrollArea::vertical().id_source("unknown_words").max_height(300.0).show(&mut columns[0], |ui| {
+                    egui::Grid::new("unknown_words").num_columns(3).striped(true).show(ui, |ui| {
+                        for (word, count) in &report.words {
+                            ui.label(word);
+                            ui.label(count.to_string());
+                            if ui.small_button(tr("Ignore")).on_hover_text(tr("Accept this word from now on")).clicked() {
+                                ignore = Some(word.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                let row_height = columns[1].spacing().interact_size.y;
+                egui::ScrollArea::vertical().id_source("suspect_cells").max_height(300.0).show_rows(
+                    &mut columns[1],
+                    row_height,
+                    report.cells.len(),
+                    |ui, range| {
+                        for (idx, col, words) in &report.cells[range] {
+                            let row_num = idx + row_offset + 2;
+                            let text = tr!("Row {}, {}: {}", row_num, self.csv_header[*col], words.join(", "));
+                            if ui.selectable_label(false, text).clicked() {
+                                jump = Some((row_num, *col));
+                            }
+                        }
+                    },
+                );
+            });
+            if report.cells.len() < report.suspect_cells {
+                ui.weak(tr!("Listing the first {}", report.cells.len()));
+            }
+        });
+        if !open {
+            self.spelling = None;
+            self.cancel_jobs(JobKind::CheckSpelling);
+            return;
+        }
+        if choose {
+            self.choose_dictionary();
+        }
+        if check || choose {
+            self.check_spelling();
+        }
+        if let Some(word) = ignore {
+            self.ignore_word(&word);
+        }
+        if let Some((row_num, col)) = jump {
+            self.focus_location(Some(row_num), Some(col));
+        }
+    }
+
+    fn scan_invisible(&mut self) {
+        let data = Arc::clone(&self.csv_data);
+        let columns = self.csv_header.len();
+        let generation = self.data_generation;
+        self.cancel_jobs(JobKind::Scan);
+        let label = tr("Finding invisible characters").to_string();
+        self.spawn_job(JobKind::Scan, label, move |progress| match invisible::scan(&data, columns, progress) {
+            Ok(report) => JobResult::Scanned { generation, report },
+            Err(_) => JobResult::Cancelled,
+        });
+    }
+
+    fn check_dates(&mut self) {
+        let Some(epoch) = dates::parse_iso(&self.earliest_date) else {
+            return;
+        };
+        let data = Arc::clone(&self.csv_data);
+        let columns = self.csv_header.len();
+        let generation = self.data_generation;
+        let today = dates::today();
+        self.cancel_jobs(JobKind::CheckDates);
+        let label = tr("Checking dates").to_string();
+        self.spawn_job(JobKind::CheckDates, label, move |progress| match dates::scan(&data, columns, today, epoch, progress) {
+            Ok(report) => JobResult::CheckedDates { generation, report },
+            Err(_) => JobResult::Cancelled,
+        });
+    }
+
+    // Rows with invisible characters in `col`, or in any column
+    fn filter_invisible(&mut self, col: Option<usize>) {
+        self.selected_row = None;
+        self.value_filter = None;
+        self.invalid_filter = None;
+        self.invisible_filter = true;
+        self.search_column = col;
+        self.search_invert = false;
+        self.run_search();
+    }
+
+    // Rewrite every cell with invisible characters in its cleaned form. Row
+    // positions don't change, so the sort and search are only run again.
+    fn clean_invisible(&mut self) {
+        if self.pager.is_some() {
+            return;
+        }
+        let mut data = Table::new();
+        let mut cleaned = 0;
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            let cells: Vec<Cow<'_, str>> = row.iter().map(invisible::clean).collect();
+            for (col, cell) in cells.iter().enumerate() {
+                if let Cow::Owned(new) = cell {
+                    cleaned += 1;
+                    self.edits.push(Edit::EditedCell {
+                        row_num: self.original_rows.get(idx).copied().unwrap_or(idx) + 2,
+                        column: self.csv_header.get(col).cloned().unwrap_or_default(),
+                        old: row.get(col).unwrap_or("").to_string(),
+                        new: new.clone(),
+                    });
+                }
+            }
+            data.push_row(cells.iter());
+        }
+        if cleaned == 0 {
+            self.announcement = tr("No invisible characters to clean").to_string();
+            return;
+        }
+        self.replace_data(data);
+        self.announcement = tr!("Cleaned {} cells", cleaned);
+    }
+
+    // Swap in edited rows, in the same order as before, so the sort and
+    // search are only run again
+    fn replace_data(&mut self, mut data: Table) {
+        data.intern_low_cardinality_columns();
+        let resort = self.sort.is_some();
+        let research = self.search_results.is_some();
+        self.csv_data = Arc::new(data);
+        self.data_generation += 1;
+        self.edit_generation += 1;
+        self.summary_cache = None;
+        self.apply_schema();
+        if resort {
+            self.apply_sort();
+        }
+        if research {
+            // The old matches can't be narrowed, since edited rows may match now
+            self.last_search = None;
+            self.run_search();
+        }
+        self.view_changed();
+    }
+
+    fn show_pseudonymize(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.pseudonymizing else {
+            return;
+        };
+        let col = state.col;
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new(tr("Pseudonymize Column"))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr!("Replace every value of {} so the data can be shared without it. Equal values get equal replacements and blank cells stay blank.", self.csv_header[col]));
+                ui.radio_value(&mut state.hash, true, tr("Salted SHA-256 hashes"));
+                ui.add_enabled_ui(state.hash, |ui| {
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("Salt:"));
+                        ui.text_edit_singleline(&mut state.salt).labelled_by(label.id);
+                        if ui.button(tr("New salt")).clicked() {
+                            state.salt = pseudonym::new_salt();
+                        }
+                    });
+                    ui.weak(tr("Keep the salt to hash other files the same way. Without it the hashes can't be matched to values."));
+                });
+                ui.radio_value(&mut state.hash, false, tr("Numbered IDs"));
+                ui.add_enabled_ui(!state.hash, |ui| {
+                    ui.horizontal(|ui| {
+                        let label = ui.label(tr("Prefix:"));
+                        ui.text_edit_singleline(&mut state.prefix).labelled_by(label.id);
+                    });
+                    ui.weak(tr("You'll be asked where to save the list of values and their IDs."));
+                });
+                ui.separator();
+                apply = ui.add_enabled(self.pager.is_none(), egui::Button::new(tr("Replace values"))).clicked();
+            });
+        if !open {
+            self.pseudonymizing = None;
+        }
+        if apply && let Some(state) = self.pseudonymizing.take() {
+            let method = if state.hash {
+                pseudonym::Method::Hash { salt: state.salt }
+            } else {
+                pseudonym::Method::Surrogate { prefix: state.prefix }
+            };
+            self.pseudonymize(state.col, method);
+        }
+    }
+
+    fn show_normalize(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.normalizing else {
+            return;
+        };
+        let col = state.col;
+        let mut open = true;
+        let mut choose = false;
+        let mut apply = false;
+        let mut save_unmapped = false;
+        egui::Window::new(tr("Normalize Values")).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!("Replace values of {} using a mapping file: a CSV with a header row, the values to replace in its first column and what to replace them with in the second.", self.csv_header[col]));
+            ui.horizontal(|ui| {
+                choose = ui.button(tr("Choose mapping file…")).clicked();
+                if !state.mapping_path.is_empty() {
+                    ui.label(tr!("{} ({} mappings)", file_name(&state.mapping_path), state.mapping.len()));
+                }
+            });
+            ui.checkbox(&mut state.ignore_case, tr("Ignore case and surrounding spaces"));
+            ui.separator();
+            apply = ui
+                .add_enabled(!state.mapping.is_empty() && self.pager.is_none(), egui::Button::new(tr("Replace values")))
+                .clicked();
+            let Some((changed, unmapped)) = &state.result else {
+                return;
+            };
+            ui.label(tr!("Changed {} cells", changed));
+            if unmapped.is_empty() {
+                ui.label(tr("Every value is mapped"));
+                return;
+            }
+            ui.label(tr!("{} values have no mapping:", unmapped.len()));
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("unmapped_values").num_columns(2).striped(true).show(ui, |ui| {
+                    for (value, count) in unmapped {
+                        ui.label(value);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+            save_unmapped = ui
+                .button(tr("Save unmapped values…"))
+                .on_hover_text(tr("Save them as a mapping file to fill in, with the second column empty"))
+                .clicked();
+        });
+        if !open {
+            self.normalizing = None;
+            return;
+        }
+        if choose {
+            self.choose_mapping_file();
+        }
+        if apply {
+            self.normalize_values();
+        }
+        if save_unmapped {
+            self.save_unmapped_values();
+        }
+    }
+
+    fn choose_mapping_file(&mut self) {
+        let Some(state) = &mut self.normalizing else {
+            return;
+        };
+        let mut dialog = FileDialog::new().add_filter("CSV", &["csv"]);
+        if let Some(dir) = self.config.get("normalize.mapping_path").and_then(|path| Path::new(path).parent()) {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.pick_file() else {
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        match read_pairs(&path) {
+            Ok(mapping) => {
+                state.mapping = mapping;
+                state.result = None;
+                self.config.set("normalize.mapping_path", &path);
+                state.mapping_path = path;
+                if let Err(err) = self.config.save() {
+                    eprintln!("Error saving settings: {}", err);
+                }
+            }
+            Err(err) => eprintln!("Error reading mapping file: {}", err),
+        }
+    }
+
+    // Values already equal to a mapping's target count as mapped
+    fn normalize_values(&mut self) {
+        let Some(state) = &mut self.normalizing else {
+            return;
+        };
+        let col = state.col;
+        let fold = |value: &str| if state.ignore_case { value.trim().to_lowercase() } else { value.to_string() };
+        let mut lookup: HashMap<String, &str> = HashMap::new();
+        for (from, to) in &state.mapping {
+            lookup.entry(fold(from)).or_insert(to);
+        }
+        let targets: HashSet<&str> = state.mapping.iter().map(|(_, to)| to.as_str()).collect();
+        let mut unmapped: HashMap<&str, usize> = HashMap::new();
+        let mut data = Table::new();
+        let mut edits = Vec::new();
+        for (idx, row) in self.csv_data.rows().enumerate() {
+            let value = row.get(col).unwrap_or("");
+            let new = match lookup.get(&fold(value)) {
+                Some(&to) => to,
+                None => {
+                    if !value.is_empty() && !targets.contains(value) {
+                        *unmapped.entry(value).or_insert(0) += 1;
+                    }
+                    value
+                }
+            };
+            if new != value {
+                edits.push(Edit::EditedCell {
+                    row_num: self.original_rows.get(idx).copied().unwrap_or(idx) + 2,
+                    column: self.csv_header[col].clone(),
+                    old: value.to_string(),
+                    new: new.to_string(),
+                });
+            }
+            data.push_row(row.iter().enumerate().map(|(i, cell)| if i == col { new } else { cell }));
+        }
+        let mut unmapped: Vec<(String, usize)> =
+            unmapped.into_iter().map(|(value, count)| (value.to_string(), count)).collect();
+        unmapped.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let changed = edits.len();
+        state.result = Some((changed, unmapped));
+        if changed > 0 {
+            self.edits.extend(edits);
+            self.replace_data(data);
+        }
+        self.announcement = tr!("Changed {} cells", changed);
+    }
+
+    fn save_unmapped_values(&mut self) {
+        let Some(Normalizing { col, result: Some((_, unmapped)), .. }) = &self.normalizing else {
+            return;
+        };
+        let dialog = FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format!("{}_mapping.csv", file_name_part(&self.csv_header[*col])));
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        let pairs: Vec<(String, String)> = unmapped.iter().map(|(value, _)| (value.clone(), String::new())).collect();
+        if let Err(err) = write_pairs(&path, ["from", "to"], &pairs) {
+            eprintln!("Error saving unmapped values: {}", err);
+        }
+    }
+
+    fn show_reference_check(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.reference_check else {
+            return;
+        };
+        let col = state.col;
+        let mut open = true;
+        let mut choose = false;
+        let mut check = false;
+        let mut show_rows = false;
+        egui::Window::new(tr("Check Against Lookup File")).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!("Check that every value of {} appears in a column of another CSV file, like a foreign key. Blank cells are skipped.", self.csv_header[col]));
+            ui.horizontal(|ui| {
+                choose = ui.button(tr("Choose lookup file…")).clicked();
+                if !state.path.is_empty() {
+                    ui.label(tr!("{} ({} rows)", file_name(&state.path), state.data.len()));
+                }
+            });
+            if state.header.is_empty() {
+                return;
+            }
+            let before = (state.ref_col, state.ignore_case);
+            egui::ComboBox::from_label(tr("Lookup column"))
+                .selected_text(state.header.get(state.ref_col).map_or("", String::as_str))
+                .show_ui(ui, |ui| {
+                    for (idx, name) in state.header.iter().enumerate() {
+                        ui.selectable_value(&mut state.ref_col, idx, name);
+                    }
+                });
+            ui.checkbox(&mut state.ignore_case, tr("Ignore case and surrounding spaces"));
+            if (state.ref_col, state.ignore_case) != before {
+                state.result = None;
+            }
+            ui.separator();
+            check = ui.button(tr("Check")).clicked();
+            let Some((rows, missing)) = &state.result else {
+                return;
+            };
+            if missing.is_empty() {
+                ui.label(tr("Every value is in the lookup file"));
+                return;
+            }
+            ui.label(tr!("{} rows have {} values that aren't in the lookup file:", rows, missing.len()));
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("missing_references").num_columns(2).striped(true).show(ui, |ui| {
+                    for (value, count) in missing {
+                        ui.label(value);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+            show_rows = ui.button(tr("Show these rows")).clicked();
+        });
+        if !open {
+            self.reference_check = None;
+            return;
+        }
+        if choose {
+            self.choose_lookup_file();
+        }
+        if check {
+            self.check_references();
+        }
+        if show_rows {
+            self.filter_missing_references();
+        }
+    }
+
+    fn choose_lookup_file(&mut self) {
+        let Some(state) = &mut self.reference_check else {
+            return;
+        };
+        let Some(path) = pick_csv_file() else {
+            return;
+        };
+        match read_csv_with_header(&path, &Progress::default()) {
+            Ok((header, data, _)) => {
+                // A column of the same name is most likely the key
+                let name = &self.csv_header[state.col];
+                state.ref_col = header.iter().position(|column| column == name).unwrap_or(0);
+                state.header = header;
+                state.data = data;
+                state.path = path;
+                state.result = None;
+            }
+            Err(err) => eprintln!("Error reading lookup file: {}", err),
+        }
+    }
+
+    fn check_references(&mut self) {
+        let Some(state) = &mut self.reference_check else {
+            return;
+        };
+        let fold = |value: &str| if state.ignore_case { value.trim().to_lowercase() } else { value.to_string() };
+        let known: HashSet<String> = state.data.rows().map(|row| fold(row.get(state.ref_col).unwrap_or(""))).collect();
+        let mut missing: HashMap<&str, usize> = HashMap::new();
+        let mut rows = 0;
+        for row in self.csv_data.rows() {
+            let value = row.get(state.col).unwrap_or("");
+            if !value.trim().is_empty() && !known.contains(&fold(value)) {
+                *missing.entry(value).or_insert(0) += 1;
+                rows += 1;
+            }
+        }
+        let mut missing: Vec<(String, usize)> = missing.into_iter().map(|(value, count)| (value.to_string(), count)).collect();
+        missing.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        self.announcement = tr!("{} rows have values that aren't in the lookup file", rows);
+        state.result = Some((rows, missing));
+    }
+
+    // A value filter on the missing values, like one picked in "Filter by values"
+    fn filter_missing_references(&mut self) {
+        let Some(ReferenceCheck { col, result: Some((_, missing)), .. }) = &self.reference_check else {
+            return;
+        };
+        let values = missing.iter().map(|(value, _)| value.clone()).collect();
+        self.selected_row = None;
+        self.search_query.clear();
+        self.search_invert = false;
+        self.value_filter = Some((*col, Arc::new(values)));
+        self.invalid_filter = None;
+        self.invisible_filter = false;
+        self.run_search();
+    }
+
+    // `only` limits the window to one column
+    fn open_decimal_separators(&mut self, only: Option<usize>) {
+        let to = match self.config.get("decimals.to") {
+            Some("comma") => Convention::Comma,
+            _ => Convention::Point,
+        };
+        let columns: Vec<usize> = only.map_or_else(|| (0..self.csv_header.len()).collect(), |col| vec![col]);
+        let columns = decimals::detect(&self.csv_data, &columns);
+        let from = columns.iter().map(|column| column.convention().filter(|&from| from != to)).collect();
+        self.decimal_separators = Some(DecimalSeparators {
+            columns,
+            from,
+            to,
+            changed: None,
+        });
+    }
+
+    fn show_decimal_separators(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.decimal_separators else {
+            return;
+        };
+        let mut open = true;
+        let mut convert = false;
+        egui::Window::new(tr("Decimal Separators")).open(&mut open).resizable(false).show(ctx, |ui| {
+            if state.columns.is_empty() {
+                ui.label(tr("No numbers with a decimal or thousands separator"));
+                return;
+            }
+            ui.label(tr("Write numbers with"));
+            let before = state.to;
+            ui.radio_value(&mut state.to, Convention::Point, Convention::Point.label());
+            ui.radio_value(&mut state.to, Convention::Comma, Convention::Comma.label());
+            if state.to != before {
+                for (from, column) in state.from.iter_mut().zip(&state.columns) {
+                    *from = column.convention().filter(|&from| from != state.to);
+                }
+            }
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("decimal_separators").num_columns(5).striped(true).show(ui, |ui| {
+                    for heading in ["Column", "Decimal point", "Decimal comma", "Either", "Convert from"] {
+                        ui.strong(tr(heading));
+                    }
+                    ui.end_row();
+                    for (idx, column) in state.columns.iter().enumerate() {
+                        let name = ui.label(&self.csv_header[column.col]);
+                        if column.is_mixed() {
+                            name.on_hover_text(tr("This column uses both conventions"));
+                        }
+                        ui.label(column.point.to_string());
+                        ui.label(column.comma.to_string());
+                        ui.label(column.either.to_string()).on_hover_text(tr("Numbers like 1,234 that read either way"));
+                        let label = |from: Option<Convention>| from.map_or(tr("Leave as is"), Convention::label);
+                        egui::ComboBox::from_id_source(("decimal_from", idx))
+                            .selected_text(label(state.from[idx]))
+                            .show_ui(ui, |ui| {
+                                for from in [None, Some(Convention::Point), Some(Convention::Comma)] {
+                                    if from != Some(state.to) {
+                                        ui.selectable_value(&mut state.from[idx], from, label(from));
+                                    }
+                                }
+                            });
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.separator();
+            let any = state.from.iter().any(Option::is_some);
+            convert = ui
+                .add_enabled(any && self.pager.is_none(), egui::Button::new(tr("Convert")))
+                .on_hover_text(tr("Cells that aren't numbers in the chosen convention are left as they are"))
+                .clicked();
+            if let Some(changed) = state.changed {
+                ui.label(tr!("Changed {} cells", changed));
+            }
+        });
+        if !open {
+            self.decimal_separators = None;
+            return;
+        }
+        if convert {
+            self.convert_decimal_separators();
+        }
+    }
+
+    fn convert_decimal_separators(&mut self) {
+        let Some(mut state) = self.decimal_separators.take() else {
+            return;
+        };
+        let to = state.to;
+        let mut from: Vec<Option<Convention>> = vec![None; self.csv_header.len()];
+        for (column, &convention) in state.columns.iter().zip(&state.from) {
+            from[column.col] = convention;
+        }
+        let mut changed_in = vec![0; self.csv_header.len()];
+        let mut data = Table::new();
+        for row in self.csv_data.rows() {
+            let cells: Vec<Cow<str>> = row
+                .iter()
+                .enumerate()
+                .map(|(col, cell)| match from.get(col).copied().flatten().and_then(|from| decimals::convert(cell, from, to)) {
+                    Some(new) => {
+                        changed_in[col] += 1;
+                        Cow::Owned(new)
+                    }
+                    None => Cow::Borrowed(cell),
+                })
+                .collect();
+            data.push_row(cells.iter().map(|cell| cell.as_ref()));
+        }
+        for (col, &changed) in changed_in.iter().enumerate() {
+            if changed > 0 {
+                self.edits.push(Edit::TransformedColumn {
+                    column: self.csv_header[col].clone(),
+                    how: to.description(),
+                });
+            }
+        }
+        let changed = changed_in.iter().sum();
+        self.config.set("decimals.to", if to == Convention::Comma { "comma" } else { "point" });
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+        if changed > 0 {
+            self.replace_data(data);
+        }
+        // Counted again so the list shows the columns as they are now
+        let columns: Vec<usize> = state.columns.iter().map(|column| column.col).collect();
+        state.columns = decimals::detect(&self.csv_data, &columns);
+        state.from = vec![None; state.columns.len()];
+        state.changed = Some(changed);
+        self.decimal_separators = Some(state);
+        self.announcement = tr!("Changed {} cells", changed);
+    }
+
+    fn show_unit_conversion(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.unit_conversion else {
+            return;
+        };
+        let mut open = true;
+        let mut convert = false;
+        egui::Window::new(tr("Convert Units")).open(&mut open).resizable(false).show(ctx, |ui| {
+            let source = &self.csv_header[state.col];
+            ui.label(tr!("Convert the numbers in {}. Cells may end with the unit, as in 12 km.", source));
+            let before = state.conversion;
+            egui::ComboBox::from_label(tr("Conversion")).selected_text(state.conversion.label()).show_ui(ui, |ui| {
+                for conversion in Conversion::ALL {
+                    ui.selectable_value(&mut state.conversion, conversion, conversion.label());
+                }
+            });
+            if state.conversion != before {
+                // Keep a name the user typed
+                if state.name == format!("{}_{}", source, before.target_unit()) {
+                    state.name = format!("{}_{}", source, state.conversion.target_unit());
+                }
+                state.result = None;
+            }
+            if state.conversion == Conversion::Factor {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Factor"));
+                    ui.add(egui::DragValue::new(&mut state.factor).speed(0.01));
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("Decimal places"));
+                ui.add(egui::DragValue::new(&mut state.decimals).clamp_range(0..=10));
+            });
+            ui.radio_value(&mut state.new_column, true, tr("Write to a new column"));
+            if state.new_column {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Name"));
+                    ui.text_edit_singleline(&mut state.name);
+                });
+            }
+            ui.radio_value(&mut state.new_column, false, tr("Replace the values"));
+            ui.separator();
+            let named = !state.new_column || !state.name.trim().is_empty();
+            convert = ui
+                .add_enabled(named && self.pager.is_none(), egui::Button::new(tr("Convert")))
+                .on_disabled_hover_text(tr("Files paged from disk can't be edited"))
+                .clicked();
+            if let Some((converted, skipped)) = state.result {
+                ui.label(tr!("Converted {} cells", converted));
+                if skipped > 0 {
+                    ui.label(tr!("{} filled cells aren't numbers and were left out", skipped));
+                }
+            }
+        });
+        if !open {
+            self.unit_conversion = None;
+            return;
+        }
+        if convert {
+            self.convert_units();
+        }
+    }
+
+    // Writes the Some of `values`, one per row, over `col` or to a new column
+    // named `new_column`. Cells with None keep their value, or are left blank
+    // in a new column.
+    fn write_column(&mut self, col: usize, new_column: Option<&str>, values: &[Option<String>], how: &'static str) {
+        let mut data = Table::new();
+        for (row, new) in self.csv_data.rows().zip(values) {
+            if new_column.is_some() {
+                let cells = row.iter().chain(std::iter::repeat("")).take(self.csv_header.len());
+                data.push_row(cells.chain([new.as_deref().unwrap_or("")]));
+            } else {
+                let new = new.as_deref().unwrap_or(row.get(col).unwrap_or(""));
+                data.push_row(row.iter().enumerate().map(|(i, cell)| if i == col { new } else { cell }));
+            }
+        }
+        let column = match new_column {
+            Some(name) => self.add_column(name),
+            None => self.csv_header[col].clone(),
+        };
+        self.edits.push(Edit::TransformedColumn { column, how });
+        self.replace_data(data);
+    }
+
+    fn convert_units(&mut self) {
+        let Some(mut state) = self.unit_conversion.take() else {
+            return;
+        };
+        let (col, conversion) = (state.col, state.conversion);
+        let mut converted = 0;
+        let mut skipped = 0;
+        let mut values = Vec::with_capacity(self.csv_data.len());
+        for row in self.csv_data.rows() {
+            let value = row.get(col).unwrap_or("");
+            let new = conversion.convert(value, state.factor, state.decimals);
+            match &new {
+                Some(_) => converted += 1,
+                None if !value.trim().is_empty() => skipped += 1,
+                None => {}
+            }
+            values.push(new);
+        }
+        let new_column = state.new_column.then(|| state.name.trim());
+        self.write_column(col, new_column, &values, conversion.description());
+        state.result = Some((converted, skipped));
+        self.unit_conversion = Some(state);
+        self.announcement = tr!("Converted {} cells", converted);
+    }
+
+    fn show_phone_formatting(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.phone_formatting else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        let mut filter = false;
+        egui::Window::new(tr("Format Phone Numbers")).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!("Rewrite the phone numbers in {} in one format. Numbers without a country code are read as numbers of the default region.", self.csv_header[state.col]));
+            let before = (state.region.code, state.format);
+            egui::ComboBox::from_label(tr("Default region"))
+                .selected_text(format!("{} ({})", state.region.name, state.region.code))
+                .show_ui(ui, |ui| {
+                    for region in phones::REGIONS {
+                        let selected = region.code == state.region.code;
+                        if ui.selectable_label(selected, format!("{} ({})", region.name, region.code)).clicked() {
+                            state.region = region;
+                        }
+                    }
+                });
+            for format in [phones::Format::E164, phones::Format::National] {
+                ui.radio_value(&mut state.format, format, format.label());
+            }
+            if state.format == phones::Format::National {
+                ui.weak(tr("Numbers from other countries are written in E.164."));
+            }
+            if (state.region.code, state.format) != before {
+                state.result = None;
+            }
+            ui.separator();
+            ui.radio_value(&mut state.new_column, true, tr("Write to a new column"));
+            if state.new_column {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Name"));
+                    ui.text_edit_singleline(&mut state.name);
+                });
+            }
+            ui.radio_value(&mut state.new_column, false, tr("Replace the values"));
+            ui.separator();
+            let named = !state.new_column || !state.name.trim().is_empty();
+            apply = ui
+                .add_enabled(named && self.pager.is_none(), egui::Button::new(tr("Format")))
+                .on_disabled_hover_text(tr("Files paged from disk can't be edited"))
+                .clicked();
+            let Some((formatted, unparseable)) = &state.result else {
+                return;
+            };
+            ui.label(tr!("Formatted {} cells", formatted));
+            if !unparseable.is_empty() {
+                ui.colored_label(ui.visuals().warn_fg_color, tr!("{} values aren't phone numbers and were left as they are", unparseable.len()));
+                filter = ui.button(tr("Show their rows")).clicked();
+            }
+        });
+        if !open {
+            self.phone_formatting = None;
+            return;
+        }
+        if apply {
+            self.format_phones();
+        }
+        if filter {
+            self.filter_unparseable_phones();
+        }
+    }
+
+    fn format_phones(&mut self) {
+        let Some(mut state) = self.phone_formatting.take() else {
+            return;
+        };
+        let col = state.col;
+        let mut formatted = 0;
+        let mut unparseable = HashSet::new();
+        let mut values = Vec::with_capacity(self.csv_data.len());
+        for row in self.csv_data.rows() {
+            let value = row.get(col).unwrap_or("");
+            let new = phones::reformat(value, state.region, state.format);
+            match &new {
+                Some(_) => formatted += 1,
+                None if !value.trim().is_empty() => {
+                    unparseable.insert(value.to_string());
+                }
+                None => {}
+            }
+            values.push(new);
+        }
+        let new_column = state.new_column.then(|| state.name.trim());
+        self.write_column(col, new_column, &values, state.format.description());
+        self.config.set("phones.region", state.region.code);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+        self.announcement = tr!("Formatted {} cells", formatted);
+        state.result = Some((formatted, unparseable));
+        self.phone_formatting = Some(state);
+    }
+
+    // A value filter on the values that weren't phone numbers, which are
+    // unchanged in the column whether the results replaced it or not
+    fn filter_unparseable_phones(&mut self) {
+        let Some(PhoneFormatting { col, result: Some((_, unparseable)), .. }) = &self.phone_formatting else {
+            return;
+        };
+        let values = unparseable.clone();
+        self.selected_row = None;
+        self.search_query.clear();
+        self.search_invert = false;
+        self.value_filter = Some((*col, Arc::new(values)));
+        self.invalid_filter = None;
+        self.invisible_filter = false;
+        self.run_search();
+    }
+
+    // Lists the keys of the JSON objects in `col`, all chosen
+    fn open_json_flattening(&mut self, col: usize) {
+        let mut keys: Vec<(Vec<String>, usize, bool)> = Vec::new();
+        let mut positions: HashMap<Vec<String>, usize> = HashMap::new();
+        for row in self.csv_data.rows() {
+            let Some(json) = Json::from_cell(row.get(col).unwrap_or("")) else {
+                continue;
+            };
+            for path in json.leaf_paths() {
+                match positions.get(&path) {
+                    Some(&i) => keys[i].1 += 1,
+                    None => {
+                        positions.insert(path.clone(), keys.len());
+                        keys.push((path, 1, true));
+                    }
+                }
+            }
+        }
+        self.json_flattening = Some(JsonFlattening { col, keys, result: None });
+    }
+
+    fn show_json_flattening(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.json_flattening else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new(tr("Flatten JSON")).open(&mut open).resizable(true).show(ctx, |ui| {
+            ui.label(tr!("Copy the chosen keys of the JSON objects in {} to new columns. Nested keys are joined with dots.", self.csv_header[state.col]));
+            if state.keys.is_empty() {
+                ui.weak(tr("The column's JSON has no object keys"));
+                return;
+            }
+            ui.horizontal(|ui| {
+                if ui.small_button(tr("All")).clicked() {
+                    state.keys.iter_mut().for_each(|key| key.2 = true);
+                }
+                if ui.small_button(tr("None")).clicked() {
+                    state.keys.iter_mut().for_each(|key| key.2 = false);
+                }
+            });
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("json_flattening_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    ui.strong(tr("Key"));
+                    ui.strong(tr("Rows"));
+                    ui.end_row();
+                    for (key, rows, chosen) in &mut state.keys {
+                        ui.checkbox(chosen, key.join("."));
+                        ui.label(rows.to_string());
+                        ui.end_row();
+                    }
+                });
+            });
+            ui.separator();
+            let chosen = state.keys.iter().any(|key| key.2);
+            apply = ui
+                .add_enabled(chosen && self.pager.is_none(), egui::Button::new(tr("Add columns")))
+                .on_disabled_hover_text(tr("Choose keys; files paged from disk can't be edited"))
+                .clicked();
+            if let Some(added) = state.result {
+                ui.label(tr!("Added {} columns", added));
+            }
+        });
+        if !open {
+            self.json_flattening = None;
+            return;
+        }
+        if apply {
+            self.flatten_json();
+        }
+    }
+
+    // Appends a column per chosen key, named after the column and the key.
+    // Rows without the key, or without JSON, get a blank cell.
+    fn flatten_json(&mut self) {
+        let Some(mut state) = self.json_flattening.take() else {
+            return;
+        };
+        let col = state.col;
+        let keys: Vec<&[String]> = state.keys.iter().filter(|key| key.2).map(|key| key.0.as_slice()).collect();
+        let width = self.csv_header.len();
+        let mut data = Table::new();
+        for row in self.csv_data.rows() {
+            let json = Json::from_cell(row.get(col).unwrap_or(""));
+            let values = keys.iter().map(|key| json.as_ref().and_then(|json| json.at(key)).map(Json::cell_text).unwrap_or_default());
+            let values: Vec<String> = values.collect();
+            let cells = row.iter().chain(std::iter::repeat("")).take(width);
+            data.push_row(cells.chain(values.iter().map(String::as_str)));
+        }
+        let prefix = self.csv_header[col].clone();
+        for key in &keys {
+            let column = self.add_column(&format!("{}.{}", prefix, key.join(".")));
+            self.edits.push(Edit::TransformedColumn { column, how: "flattened from JSON" });
+        }
+        self.replace_data(data);
+        self.announcement = tr!("Added {} columns", keys.len());
+        state.result = Some(keys.len());
+        self.json_flattening = Some(state);
+    }
+
+    fn show_currency_normalizing(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.currency_normalizing else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        let mut filter = false;
+        egui::Window::new(tr("Normalize Amounts")).open(&mut open).resizable(false).show(ctx, |ui| {
+            ui.label(tr!("Rewrite the money amounts in {} as plain numbers, without currency symbols or thousands separators.", self.csv_header[state.col]));
+            ui.label(tr("Amounts are written with"));
+            for convention in [Convention::Point, Convention::Comma] {
+                ui.radio_value(&mut state.convention, convention, convention.label());
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("$ stands for"));
+                ui.add(egui::TextEdit::singleline(&mut state.dollar).desired_width(48.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Decimal places"));
+                ui.add(egui::DragValue::new(&mut state.decimals).clamp_range(0..=6));
+            });
+            ui.checkbox(&mut state.split_code, tr("Put each amount's currency in a new column"));
+            if state.split_code {
+                ui.horizontal(|ui| {
+                    ui.label(tr("Name"));
+                    ui.text_edit_singleline(&mut state.code_name);
+                });
+            }
+            ui.separator();
+            let named = !state.split_code || !state.code_name.trim().is_empty();
+            apply = ui
+                .add_enabled(named && self.pager.is_none(), egui::Button::new(tr("Normalize")))
+                .on_disabled_hover_text(tr("Files paged from disk can't be edited"))
+                .clicked();
+            let Some((normalized, unparseable)) = &state.result else {
+                return;
+            };
+            ui.label(tr!("Normalized {} amounts", normalized));
+            if !unparseable.is_empty() {
+                ui.colored_label(ui.visuals().warn_fg_color, tr!("{} values aren't amounts and were left as they are", unparseable.len()));
+                filter = ui.button(tr("Show their rows")).clicked();
+            }
+        });
+        if !open {
+            self.currency_normalizing = None;
+            return;
+        }
+        if apply {
+            self.normalize_currency();
+        }
+        if filter {
+            self.filter_unparseable_amounts();
+        }
+    }
+
+    fn normalize_currency(&mut self) {
+        let Some(mut state) = self.currency_normalizing.take() else {
+            return;
+        };
+        let col = state.col;
+        let dollar = state.dollar.trim().to_uppercase();
+        let mut amounts = Vec::with_capacity(self.csv_data.len());
+        let mut codes = Vec::with_capacity(self.csv_data.len());
+        let mut unparseable = HashSet::new();
+        for row in self.csv_data.rows() {
+            let value = row.get(col).unwrap_or("");
+            match currency::parse(value, state.convention, &dollar) {
+                Some(amount) => {
+                    amounts.push(Some(decimals::format(amount.value, state.decimals)));
+                    codes.push(amount.code);
+                }
+                None => {
+                    if !value.trim().is_empty() {
+                        unparseable.insert(value.to_string());
+                    }
+                    amounts.push(None);
+                    codes.push(None);
+                }
+            }
+        }
+        let normalized = amounts.iter().flatten().count();
+        self.write_column(col, None, &amounts, "amounts without currency");
+        if state.split_code && codes.iter().any(Option::is_some) {
+            self.write_column(col, Some(state.code_name.trim()), &codes, "currency of amounts");
+        }
+        self.config.set("currency.dollar", &dollar);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+        self.announcement = tr!("Normalized {} amounts", normalized);
+        state.dollar = dollar;
+        state.result = Some((normalized, unparseable));
+        // The amounts are now plain numbers with a point
+        state.convention = Convention::Point;
+        self.currency_normalizing = Some(state);
+    }
+
+    // A value filter on the values that weren't amounts, which are unchanged
+    fn filter_unparseable_amounts(&mut self) {
+        let Some(CurrencyNormalizing { col, result: Some((_, unparseable)), .. }) = &self.currency_normalizing else {
+            return;
+        };
+        let values = unparseable.clone();
+        self.selected_row = None;
+        self.search_query.clear();
+        self.search_invert = false;
+        self.value_filter = Some((*col, Arc::new(values)));
+        self.invalid_filter = None;
+        self.invisible_filter = false;
+        self.run_search();
+    }
+
+    fn pseudonymize(&mut self, col: usize, method: pseudonym::Method) {
+        let pairs = pseudonym::pseudonyms(&self.csv_data, col, &method);
+        if let pseudonym::Method::Surrogate { .. } = method {
+            let dialog = FileDialog::new()
+                .set_title(tr("Save the ID mapping"))
+                .add_filter("CSV", &["csv"])
+                .set_file_name(format!("{}_ids.csv", file_name_part(&self.csv_header[col])));
+            let Some(path) = dialog.save_file() else {
+                return;
+            };
+            if let Err(err) = write_pairs(&path, [&self.csv_header[col], "id"], &pairs) {
+                eprintln!("Error saving ID mapping: {}", err);
+                return;
+            }
+        }
+        let replacements: HashMap<&str, &str> = pairs.iter().map(|(value, new)| (value.as_str(), new.as_str())).collect();
+        let mut data = Table::new();
+        for row in self.csv_data.rows() {
+            data.push_row(row.iter().enumerate().map(|(i, cell)| {
+                if i == col { replacements.get(cell).copied().unwrap_or(cell) } else { cell }
+            }));
+        }
+        self.edits.push(Edit::TransformedColumn {
+            column: self.csv_header[col].clone(),
+            how: method.description(),
+        });
+        self.replace_data(data);
+        self.announcement = tr!("Replaced {} distinct values in {}", pairs.len(), self.csv_header[col]);
+    }
+
+    fn show_invalid_utf8(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr!("{} cells aren't valid UTF-8", self.invalid_utf8.len()));
+        ui.weak(tr("Bytes that couldn't be read show as �. Turn on Keep bytes that aren't UTF-8 in the save options to write them back unchanged."));
+        let row_offset = self.row_offset();
+        let mut jump = None;
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical()
+            .id_source("invalid_utf8")
+            .max_height(160.0)
+            .show_rows(ui, row_height, self.invalid_utf8.len(), |ui, range| {
+                for cell in &self.invalid_utf8[range] {
+                    let name = self.csv_header.get(cell.col).map_or("", String::as_str);
+                    let bytes = cell.bytes.escape_ascii().to_string();
+                    let Some(idx) = cell.row else {
+                        ui.label(tr!("Header, column {}", cell.col + 1)).on_hover_text(bytes);
+                        continue;
+                    };
+                    let row_num = idx + row_offset + 2;
+                    let response = ui.selectable_label(false, tr!("Row {}, {}", row_num, name)).on_hover_text(bytes);
+                    if response.clicked() {
+                        jump = Some((row_num, cell.col));
+                    }
+                }
+            });
+        if let Some((row_num, col)) = jump {
+            self.focus_location(Some(row_num), Some(col));
+        }
+    }
+
+    fn show_invisible_scan(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            let remove_empty = ui
+                .add_enabled(self.pager.is_none(), egui::Button::new(tr("Remove empty rows and columns")))
+                .on_hover_text(tr("Delete rows where every cell is blank, and unnamed columns where every cell is blank"))
+                .on_disabled_hover_text(tr("Files paged from disk can't be edited"));
+            if remove_empty.clicked() {
+                self.remove_empty();
+            }
+            if ui.button(tr("Find ragged rows")).on_hover_text(tr("Rows with more or fewer fields than the header")).clicked() {
+                self.find_ragged_rows();
+            }
+            if ui.button(tr("Check spelling…")).on_hover_text(tr("List cells of text columns with words that aren't in a dictionary")).clicked() {
+                self.open_spelling();
+            }
+            if ui.button(tr("Find invisible characters")).clicked() {
+                self.scan_invisible();
+            }
+            let clean = ui
+                .add_enabled(self.pager.is_none(), egui::Button::new(tr("Clean all")))
+                .on_hover_text(tr("Turn unusual spaces into plain ones, remove zero-width and control characters, and replace look-alike letters in Latin words"))
+                .on_disabled_hover_text(tr("Files paged from disk can't be edited"));
+            if clean.clicked() {
+                self.clean_invisible();
+            }
+        });
+        let Some((generation, report)) = &self.invisible_scan else {
+            return;
+        };
+        if *generation != self.data_generation {
+            ui.weak(tr("The data has changed since it was scanned. Scan again to update the counts."));
+            return;
+        }
+        if report.cells == 0 {
+            ui.label(tr("No invisible characters found"));
+            return;
+        }
+        ui.label(tr!("{} cells in {} rows", report.cells, report.rows));
+        let mut filter = None;
+        egui::Grid::new("invisible_counts").num_columns(3).striped(true).show(ui, |ui| {
+            for (col, counts) in report.counts.iter().enumerate() {
+                for kind in Kind::ALL {
+                    let count = counts[kind as usize];
+                    if count == 0 {
+                        continue;
+                    }
+                    if ui.selectable_label(false, &self.csv_header[col]).on_hover_text(tr("Show these rows")).clicked() {
+                        filter = Some(col);
+                    }
+                    ui.label(kind.label());
+                    ui.label(count.to_string());
+                    ui.end_row();
+                }
+            }
+        });
+        if ui.button(tr("Show all rows with invisible characters")).clicked() {
+            self.filter_invisible(None);
+        } else if filter.is_some() {
+            self.filter_invisible(filter);
+        }
+    }
+
+    // Counts per date column and check; clicking one goes to its first cell
+    fn show_date_check(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            let valid = dates::parse_iso(&self.earliest_date).is_some();
+            let check = ui
+                .add_enabled(valid, egui::Button::new(tr("Check dates")))
+                .on_hover_text(tr("Look for dates in the future or before the earliest date, times with different UTC offsets, and dates that could be day or month first"));
+            if check.clicked() {
+                self.check_dates();
+            }
+            ui.label(tr("Earliest date:"));
+            let field = ui.add(egui::TextEdit::singleline(&mut self.earliest_date).desired_width(90.0));
+            a11y::set_name(&field, tr("Earliest date"));
+            if !valid {
+                ui.colored_label(ui.visuals().error_fg_color, tr("Use a date like 1900-01-01"));
+            } else if field.lost_focus() {
+                self.config.set("dates.earliest", &self.earliest_date);
+                if let Err(err) = self.config.save() {
+                    eprintln!("Error saving settings: {}", err);
+                }
+            }
+        });
+        let Some((generation, report)) = &self.date_check else {
+            return;
+        };
+        if *generation != self.data_generation {
+            ui.weak(tr("The data has changed since the dates were checked. Check again to update the counts."));
+            return;
+        }
+        if report.columns.is_empty() {
+            ui.label(tr("No date columns found"));
+            return;
+        }
+        let row_offset = self.row_offset();
+        let mut jump = None;
+        egui::Grid::new("date_checks").num_columns(3).striped(true).show(ui, |ui| {
+            for column in &report.columns {
+                let name = &self.csv_header[column.col];
+                if column.counts.iter().all(|&count| count == 0) {
+                    ui.label(name);
+                    ui.label(tr!("No problems in {} dates", column.dates));
+                    ui.label("");
+                    ui.end_row();
+                    continue;
+                }
+                for check in dates::Check::ALL {
+                    let count = column.counts[check as usize];
+                    if count == 0 {
+                        continue;
+                    }
+                    if ui.selectable_label(false, name).on_hover_text(tr("Go to the first one")).clicked() {
+                        jump = column.first[check as usize].map(|idx| (idx + row_offset + 2, column.col));
+                    }
+                    let hover = match check {
+                        dates::Check::MixedZone => tr!("Most times in this column have {}", column.zone.map_or_else(String::new, dates::Zone::label)),
+                        dates::Check::Ambiguous if column.order == dates::Order::Mixed => {
+                            tr("Some dates in this column are day first and others month first").to_string()
+                        }
+                        dates::Check::Ambiguous => tr("Nothing in this column shows whether dates like 01/02/2023 are day or month first").to_string(),
+                        _ => String::new(),
+                    };
+                    let label = ui.label(check.label());
+                    if !hover.is_empty() {
+                        label.on_hover_text(hover);
+                    }
+                    ui.label(count.to_string());
+                    ui.end_row();
+                }
+            }
+        });
+        if let Some((row_num, col)) = jump {
+            self.focus_location(Some(row_num), Some(col));
+        }
+    }
+
+    // A Table Schema or CSVW metadata file next to the open file replaces the
+    // schema, as its publisher meant it to be read, and the file is validated
+    // against it
+    fn load_schema_beside_file(&mut self) {
+        let Some(path) = &self.file_path else {
+            return;
+        };
+        let Some(schema_path) = Schema::paths_for(Path::new(path)).into_iter().find(|path| path.is_file()) else {
+            return;
+        };
+        if self.load_schema(&schema_path) && self.can_validate() {
+            self.run_validation();
+        }
+    }
+
+    fn load_schema(&mut self, path: &Path) -> bool {
+        let schema = std::fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| Schema::from_json(&text));
+        match schema {
+            Ok(mut schema) => {
+                // Schema files have no row rules, so the ones set here stay,
+                // as do named patterns unless the file has its own
+                schema.row_rules = std::mem::take(&mut self.schema.row_rules);
+                if schema.patterns.is_empty() {
+                    schema.patterns = std::mem::take(&mut self.schema.patterns);
+                }
+                self.schema = schema;
+                self.validation = None;
+                self.announcement = tr!("Schema loaded from {}", file_name(&path.to_string_lossy()));
+                self.save_schema();
+                true
+            }
+            Err(err) => {
+                eprintln!("Error reading schema {}: {}", path.display(), err);
+                false
+            }
+        }
+    }
+
+    // As name.schema.json next to the open file, where it's loaded from when
+    // the file is opened again
+    fn save_schema_beside_file(&mut self) {
+        let Some(path) = &self.file_path else {
+            return;
+        };
+        let [schema_path, _] = Schema::paths_for(Path::new(path));
+        match std::fs::write(&schema_path, self.schema.to_table_schema()) {
+            Ok(()) => self.announcement = tr!("Schema saved as {}", file_name(&schema_path.to_string_lossy())),
+            Err(err) => eprintln!("Error saving schema: {}", err),
+        }
+    }
+
+    fn save_schema_file(&self) {
+        let mut dialog = FileDialog::new().add_filter(tr("Table Schema"), &["json"]);
+        if let Some(path) = &self.file_path
+            && let Some(schema_path) = Schema::paths_for(Path::new(path)).first()
+        {
+            dialog = dialog.set_file_name(file_name(&schema_path.to_string_lossy()));
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        if let Err(err) = std::fs::write(&path, self.schema.to_table_schema()) {
+            eprintln!("Error saving schema: {}", err);
+        }
+    }
+
+    fn save_schema(&mut self) {
+        self.apply_schema();
+        self.schema.save_to(&mut self.config);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+    }
+
+    // Counts per rule, then every problem; clicking one goes to its cell
+    fn show_validation_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if ui.button(tr("Edit schema…")).clicked() {
+                self.show_schema = true;
+            }
+            let validate = ui
+                .add_enabled(self.can_validate(), egui::Button::new(tr("Validate")))
+                .on_disabled_hover_text(tr("Add rules to the schema first, and fix any that show an error"));
+            if validate.clicked() {
+                self.run_validation();
+            }
+        });
+        let check_toggle = ui
+            .checkbox(&mut self.check_headers, tr("Check columns when a file is opened"))
+            .on_hover_text(tr("Compare each file's header with the schema and open this panel if columns are missing, extra or out of order"));
+        if check_toggle.changed() {
+            self.config.set("schema.check_headers", self.check_headers.to_string());
+            if let Err(err) = self.config.save() {
+                eprintln!("Error saving settings: {}", err);
+            }
+        }
+        ui.separator();
+        if !self.schema.columns.is_empty() {
+            self.show_header_check(ui);
+            ui.separator();
+        }
+        if !self.invalid_utf8.is_empty() {
+            self.show_invalid_utf8(ui);
+            ui.separator();
+        }
+        self.show_invisible_scan(ui);
+        ui.separator();
+        self.show_date_check(ui);
+        ui.separator();
+        let Some((generation, report)) = &self.validation else {
+            ui.weak(tr("Describe the expected columns with Edit schema, then Validate to list every cell that doesn't fit."));
+            return;
+        };
+        if *generation != self.data_generation {
+            ui.weak(tr("The data has changed since it was validated. Validate again to update the list."));
+            return;
+        }
+        if report.total == 0 {
+            ui.label(tr!("No problems in {} rows", report.rows_checked));
+            return;
+        }
+        ui.label(tr!("{} problems in {} rows", report.total, report.rows_checked));
+        egui::Grid::new("validation_counts").num_columns(3).striped(true).show(ui, |ui| {
+            for &(rule, check, count) in &report.counts {
+                ui.label(&report.names[rule]);
+                ui.label(check.label());
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+        if report.problems.len() < report.total {
+            ui.weak(tr!("Listing the first {}", report.problems.len()));
+        }
+        ui.separator();
+        let row_offset = self.row_offset();
+        let mut jump = None;
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical()
+            .id_source("validation_problems")
+            .max_height(360.0)
+            .show_rows(ui, row_height, report.problems.len(), |ui, range| {
+                for problem in &report.problems[range] {
+                    let name = &report.names[problem.rule];
+                    let (Some(idx), Some(col)) = (problem.row, problem.col) else {
+                        ui.label(format!("{}: {}", name, problem.check.label()));
+                        continue;
+                    };
+                    let row_num = idx + row_offset + 2;
+                    let value = self.csv_data.row(idx).get(col).unwrap_or("");
+                    let response = ui
+                        .selectable_label(false, tr!("Row {}, {}: {}", row_num, name, problem.check.label()))
+                        .on_hover_text(tr!("Value: {}", format!("{:?}", value)));
+                    if response.clicked() {
+                        jump = Some((row_num, col));
+                    }
+                }
+            });
+        if let Some((row_num, col)) = jump {
+            self.focus_location(Some(row_num), Some(col));
+        }
+    }
+
+    // Scores each version of the data and schema once while the panel is open
+    fn show_quality_panel(&mut self, ui: &mut egui::Ui) {
+        if self.quality_started != Some(self.data_generation) {
+            self.score_quality();
+        }
+        let Some((generation, report)) = &self.quality else {
+            ui.weak(tr("Scoring…"));
+            return;
+        };
+        let color = |ui: &egui::Ui, score: f64| {
+            if score < 50.0 {
+                ui.visuals().error_fg_color
+            } else if score < 80.0 {
+                ui.visuals().warn_fg_color
+            } else {
+                ui.visuals().text_color()
+            }
+        };
+        if *generation != self.data_generation {
+            ui.weak(tr("Scoring the changed data…"));
+        }
+        let score = report.score();
+        ui.label(egui::RichText::new(format!("{:.0}", score)).heading().color(color(ui, score)))
+            .on_hover_text(tr("The mean of the parts below, out of 100"));
+        let percent = |share: f64| format!("{:.1}%", share * 100.0);
+        egui::Grid::new("quality_parts").num_columns(2).show(ui, |ui| {
+            ui.label(tr("Complete")).on_hover_text(tr("Cells that aren't blank"));
+            ui.label(percent(report.completeness()));
+            ui.end_row();
+            ui.label(tr("Valid")).on_hover_text(tr("Filled cells that fit the column's type, as set in the schema or guessed"));
+            ui.label(percent(report.validity()));
+            ui.end_row();
+            ui.label(tr("Unique")).on_hover_text(tr("Rows that don't repeat an earlier row"));
+            ui.label(tr!("{} ({} duplicate rows)", percent(report.uniqueness()), report.duplicate_rows));
+            ui.end_row();
+            ui.label(tr("Meets rules")).on_hover_text(tr("Rows meeting the schema's rules, other than type and required"));
+            ui.label(report.conformance().map_or_else(|| tr("No schema").to_string(), percent));
+            ui.end_row();
+        });
+        ui.separator();
+        let mut jump = None;
+        egui::Grid::new("quality_columns").num_columns(5).striped(true).show(ui, |ui| {
+            for heading in ["Column", "Score", "Complete", "Valid", "Meets rules"] {
+                ui.strong(tr(heading));
+            }
+            ui.end_row();
+            for column in &report.columns {
+                let name = self.csv_header.get(column.col).map_or("", String::as_str);
+                if ui.selectable_label(false, name).on_hover_text(tr("Go to this column")).clicked() {
+                    jump = Some(column.col);
+                }
+                let score = column.score();
+                ui.colored_label(color(ui, score), format!("{:.0}", score));
+                ui.label(percent(column.completeness));
+                ui.label(percent(column.validity));
+                ui.label(column.conformance.map_or_else(|| "–".to_string(), percent));
+                ui.end_row();
+            }
+        });
+        if let Some(col) = jump {
+            self.focus_location(None, Some(col));
+        }
+    }
+
+    fn show_header_check(&self, ui: &mut egui::Ui) {
+        let check = &self.header_check;
+        if check.is_ok() {
+            ui.label(tr!("All {} columns in the schema are present, in order", self.schema.columns.len()));
+            return;
+        }
+        let groups = [
+            (tr("Missing:"), &check.missing, tr("In the schema but not in this file")),
+            (tr("Extra:"), &check.extra, tr("In this file but not in the schema")),
+            (tr("Out of order:"), &check.misordered, tr("In both, but not where the schema puts them")),
+        ];
+        for (label, names, hover) in groups {
+            if names.is_empty() {
+                continue;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, label).on_hover_text(hover);
+                ui.label(names.join(", "));
+            });
+        }
+    }
+
+    fn show_schema_window(&mut self, ctx: &egui::Context) {
+        if !self.show_schema {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        let mut validate = false;
+        let mut load_file = None;
+        let mut save_file = false;
+        let mut save_beside = false;
+        egui::Window::new(tr("Schema")).open(&mut open).default_width(760.0).show(ctx, |ui| {
+            ui.label(tr("The columns a file should have. Limits are values for numbers, dates for dates and lengths for text. Patterns are regular expressions that must match the whole value. For numbers, \"Within σ\" flags values more than that many standard deviations from the column's mean."));
+            ui.horizontal(|ui| {
+                let from_file = ui
+                    .add_enabled(!self.csv_header.is_empty(), egui::Button::new(tr("Use this file's columns")))
+                    .on_hover_text(tr("Replace the schema with a rule for each column of this file, with types guessed from the first rows"));
+                if from_file.clicked() {
+                    self.schema = Schema::from_table(&self.csv_header, &self.csv_data);
+                    changed = true;
+                }
+                if ui.button(tr("Add rule")).clicked() {
+                    self.schema.columns.push(ColumnRule::default());
+                    changed = true;
+                }
+                if ui.add_enabled(self.schema != Schema::default(), egui::Button::new(tr("Remove all"))).clicked() {
+                    self.schema = Schema::default();
+                    changed = true;
+                }
+                ui.separator();
+                let load = ui
+                    .button(tr("Load…"))
+                    .on_hover_text(tr("Read a Frictionless Table Schema or CSVW metadata file"));
+                if load.clicked() {
+                    load_file = FileDialog::new().add_filter(tr("Table Schema or CSVW"), &["json"]).pick_file();
+                }
+                let save = ui
+                    .add_enabled(!self.schema.columns.is_empty(), egui::Button::new(tr("Save…")))
+                    .on_hover_text(tr("Write the schema as a Frictionless Table Schema. Saved next to a CSV as name.schema.json, it is loaded with the CSV."));
+                save_file = save.clicked();
+                let beside = ui
+                    .add_enabled(
+                        !self.schema.columns.is_empty() && self.file_path.is_some(),
+                        egui::Button::new(tr("Save beside file")),
+                    )
+                    .on_hover_text(tr("Save the schema next to this file as name.schema.json, so it's loaded and the file validated whenever it's opened"));
+                save_beside = beside.clicked();
+            });
+            ui.collapsing(tr("Paste expected column names"), |ui| {
+                ui.weak(tr("One per line, or separated by commas or tabs, in the order the file should have them"));
+                ui.add(egui::TextEdit::multiline(&mut self.header_list_input).desired_rows(4).desired_width(f32::INFINITY));
+                let replace = ui
+                    .add_enabled(!self.header_list_input.trim().is_empty(), egui::Button::new(tr("Replace the schema with these columns")))
+                    .on_hover_text(tr("Rules already in the schema for these names are kept"));
+                if replace.clicked() {
+                    let names = self.header_list_input.split(['\n', ',', '\t']).map(str::trim).filter(|name| !name.is_empty());
+                    let columns = names
+                        .map(|name| match self.schema.columns.iter().find(|rule| rule.name == name) {
+                            Some(rule) => rule.clone(),
+                            None => ColumnRule {
+                                name: name.to_string(),
+                                ..ColumnRule::default()
+                            },
+                        })
+                        .collect();
+                    self.schema.columns = columns;
+                    self.header_list_input.clear();
+                    changed = true;
+                }
+            });
+            ui.collapsing(tr("Pattern library"), |ui| {
+                ui.weak(tr("Named patterns to choose from for any column. The ones added here are saved with the schema."));
+                egui::Grid::new("pattern_library").num_columns(3).striped(true).show(ui, |ui| {
+                    for (name, pattern) in pattern::LIBRARY {
+                        ui.label(tr(name));
+                        ui.add(egui::Label::new(egui::RichText::new(*pattern).monospace()).wrap(true));
+                        ui.end_row();
+                    }
+                    let mut remove = None;
+                    for (idx, (name, pattern)) in self.schema.patterns.iter_mut().enumerate() {
+                        let field = ui.add(egui::TextEdit::singleline(name).desired_width(140.0));
+                        a11y::set_name(&field, tr("Name"));
+                        changed |= field.lost_focus();
+                        let field = ui.add(egui::TextEdit::singleline(pattern).desired_width(360.0).font(egui::TextStyle::Monospace));
+                        a11y::set_name(&field, tr("Pattern"));
+                        changed |= field.lost_focus();
+                        if ui.small_button("🗙").on_hover_text(tr("Remove pattern")).clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                    if let Some(idx) = remove {
+                        self.schema.patterns.remove(idx);
+                        changed = true;
+                    }
+                });
+                if ui.button(tr("Add pattern")).clicked() {
+                    self.schema.patterns.push(Default::default());
+                    changed = true;
+                }
+            });
+            ui.separator();
+            // Built in, then the schema's own
+            let library: Vec<(String, String)> = pattern::LIBRARY
+                .iter()
+                .map(|(name, pattern)| (tr(name).to_string(), pattern.to_string()))
+                .chain(self.schema.named_patterns().map(|(name, pattern)| (name.to_string(), pattern.to_string())))
+                .collect();
+            let mut remove = None;
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("schema_rules").num_columns(9).striped(true).show(ui, |ui| {
+                    for heading in ["Column", "Title", "Type", "Required", "Pattern", "Minimum", "Maximum", "Within σ"] {
+                        ui.strong(tr(heading));
+                    }
+                    ui.end_row();
+                    for (idx, rule) in self.schema.columns.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            let name = ui.add(egui::TextEdit::singleline(&mut rule.name).desired_width(140.0));
+                            a11y::set_name(&name, tr("Column"));
+                            changed |= name.lost_focus();
+                            if !self.csv_header.is_empty() && !self.csv_header.contains(&rule.name) {
+                                ui.colored_label(ui.visuals().warn_fg_color, "⚠").on_hover_text(tr("This file has no column with this name"));
+                            }
+                        });
+                        let title = ui.add(egui::TextEdit::singleline(&mut rule.title).desired_width(120.0));
+                        a11y::set_name(&title, tr("Title"));
+                        changed |= title.lost_focus();
+                        egui::ComboBox::from_id_source(("schema_type", idx))
+                            .selected_text(rule.column_type.label())
+                            .show_ui(ui, |ui| {
+                                for column_type in ColumnType::ALL {
+                                    changed |= ui.selectable_value(&mut rule.column_type, column_type, column_type.label()).changed();
+                                }
+                            });
+                        changed |= ui.checkbox(&mut rule.required, "").changed();
+                        ui.horizontal(|ui| {
+                            let field = ui.add(egui::TextEdit::singleline(&mut rule.pattern).desired_width(120.0));
+                            a11y::set_name(&field, tr("Pattern"));
+                            changed |= field.lost_focus();
+                            ui.menu_button("▾", |ui| {
+                                for (name, pattern) in &library {
+                                    if ui.button(name).on_hover_text(pattern).clicked() {
+                                        rule.pattern = pattern.clone();
+                                        changed = true;
+                                        ui.close_menu();
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text(tr("Choose from the pattern library"));
+                        });
+                        for (text, width, label) in [
+                            (&mut rule.min, 80.0, tr("Minimum")),
+                            (&mut rule.max, 80.0, tr("Maximum")),
+                            (&mut rule.deviations, 50.0, tr("Within σ")),
+                        ] {
+                            let field = ui.add(egui::TextEdit::singleline(text).desired_width(width));
+                            a11y::set_name(&field, label);
+                            changed |= field.lost_focus();
+                        }
+                        if ui.small_button("🗙").on_hover_text(tr("Remove rule")).clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if let Some(idx) = remove {
+                self.schema.columns.remove(idx);
+                changed = true;
+            }
+            ui.separator();
+            ui.strong(tr("Row rules"));
+            ui.weak(tr("Conditions every row must meet, like end_date >= start_date or total = price * qty. Put column names with spaces in backticks and text in double quotes. Rows where any of the rule's columns is blank are skipped."));
+            let mut remove = None;
+            for (idx, rule) in self.schema.row_rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let field = ui.add(egui::TextEdit::singleline(rule).desired_width(480.0).font(egui::TextStyle::Monospace));
+                    a11y::set_name(&field, tr("Row rule"));
+                    changed |= field.lost_focus();
+                    if ui.small_button("🗙").on_hover_text(tr("Remove rule")).clicked() {
+                        remove = Some(idx);
+                    }
+                });
+            }
+            if let Some(idx) = remove {
+                self.schema.row_rules.remove(idx);
+                changed = true;
+            }
+            if ui.button(tr("Add row rule")).clicked() {
+                self.schema.row_rules.push(String::new());
+                changed = true;
+            }
+            for err in self.schema.errors() {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+            ui.separator();
+            validate = ui.add_enabled(self.can_validate(), egui::Button::new(tr("Validate"))).clicked();
+        });
+        if changed {
+            self.save_schema();
+        }
+        if let Some(path) = load_file {
+            self.load_schema(&path);
+        }
+        if save_file {
+            self.save_schema_file();
+        }
+        if save_beside {
+            self.save_schema_beside_file();
+        }
+        if validate {
+            self.run_validation();
+        }
+        self.show_schema = open;
+    }
+
+    // Row detail and column statistics, stacked
+    fn show_details_panel(&mut self, ui: &mut egui::Ui) {
+        if self.row_detail.is_none() && self.column_stats.is_none() {
+            ui.weak(tr("Choose \"Go to row detail\" from a cell's menu or \"Statistics\" from a column header's menu."));
+        }
+        self.show_row_detail(ui);
+        if self.row_detail.is_some() && self.column_stats.is_some() {
+            ui.separator();
+        }
+        self.show_column_stats(ui);
+    }
+
+    // The selected row as field name and value pairs, one per line, with
+    // buttons to walk to the neighbouring rows
+    fn show_record_card(&mut self, ui: &mut egui::Ui, row_num: usize) {
+        let last_row = self.total_rows() + 1;
+        let mut go_to = None;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(row_num > 2, egui::Button::new(tr("Previous"))).clicked() {
+                go_to = Some(row_num - 1);
+            }
+            ui.label(tr!("Row {} of {}", row_num, last_row));
+            if ui.add_enabled(row_num < last_row, egui::Button::new(tr("Next"))).clicked() {
+                go_to = Some(row_num + 1);
+            }
+            ui.separator();
+            if ui.button(tr("Back to table")).clicked() {
+                self.selected_row = None;
+                self.view_changed();
+            }
+        });
+        ui.separator();
+        let cells: Vec<&str> = match self.data_index_for_row_number(row_num) {
+            Some(idx) => self.csv_data.row(idx).iter().collect(),
+            None if row_num == 1 => self.csv_header.iter().map(String::as_str).collect(),
+            None => Vec::new(),
+        };
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("record_card").num_columns(2).striped(true).show(ui, |ui| {
+                for (col, (header, cell)) in self.csv_header.iter().zip(cells).enumerate() {
+                    ui.strong(header);
+                    if row_num != 1 && self.masked_columns.contains(&col) {
+                        ui.add(egui::Label::new(pii::mask(cell)).wrap(true));
+                    } else {
+                        ui.add(egui::Label::new(cell).wrap(true));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(row_num) = go_to {
+            self.select_row(row_num);
+        }
+    }
+
+    // Full text of the opened cell, selectable for copying
+    fn show_cell_viewer(&mut self, ctx: &egui::Context) {
+        let Some((title, text, pretty)) = &self.cell_viewer else {
+            return;
+        };
+        let mut raw = self.cell_viewer_raw;
+        let shown = match pretty {
+            Some(pretty) if !raw => pretty,
+            _ => text,
+        };
+        let mut open = true;
+        egui::Window::new(title.as_str())
+            .id(egui::Id::new("cell_viewer"))
+            .open(&mut open)
+            .resizable(true)
+            .default_width(400.0)
+            .default_height(300.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.weak(tr!("{} characters, {} lines", text.chars().count(), text.lines().count().max(1)));
+                    if ui.button(tr("Copy")).clicked() {
+                        ui.output_mut(|o| o.copied_text = shown.clone());
+                    }
+                    if pretty.is_some() {
+                        ui.checkbox(&mut raw, tr("As written"))
+                            .on_hover_text(tr("Show the JSON as it is in the file rather than indented"));
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut shown.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        self.cell_viewer_raw = raw;
+        if !open || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.cell_viewer = None;
+        }
+    }
+
+    // Indices of the columns currently shown
+    fn visible_column_indices(&self) -> Vec<usize> {
+        self.visible_columns
+            .iter()
+            .enumerate()
+            .filter(|(_, visible)| **visible)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    // Rows for the table body in the current mode, borrowed rather than cloned
+    fn displayed_rows(&self) -> Vec<Row<'_>> {
+        self.displayed_indices().into_iter().map(|idx| self.csv_data.row(idx)).collect()
+    }
+
+    // Data indices of the displayed rows
+    fn displayed_indices(&self) -> Vec<usize> {
+        if let Some(row_num) = self.selected_row {
+            self.data_index_for_row_number(row_num).into_iter().collect()
+        } else if let Some(ref results) = self.search_results {
+            results.to_vec()
+        } else if self.pager.is_some() {
+            (0..self.csv_data.len()).map(|pos| self.sorted_index(pos)).collect()
+        } else if self.infinite_scroll {
+            let end = self.scroll_rows.min(self.csv_data.len());
+            (0..end).map(|pos| self.sorted_index(pos)).collect()
+        } else {
+            let start = self.current_page.saturating_mul(self.rows_per_page).min(self.csv_data.len());
+            let end = start.saturating_add(self.rows_per_page).min(self.csv_data.len());
+            (start..end).map(|pos| self.sorted_index(pos)).collect()
+        }
+    }
+
+    // Initialize visible columns when CSV is loaded
+    fn initialize_visible_columns(&mut self) {
+        self.visible_columns = vec![true; self.csv_header.len()];
+        self.first_scroll_column = 0;
+        self.column_aggregates = vec![Aggregate::default(); self.csv_header.len()];
+        self.column_widths = vec![DEFAULT_COLUMN_WIDTH; self.csv_header.len()];
+    }
+
+    // Columns laid out in the table. With frozen columns these are the first
+    // `frozen_columns` visible ones followed by the rest starting at
+    // `first_scroll_column`; when the focused cell is about to be scrolled to,
+    // the window moves to include its column.
+    fn table_columns(&mut self, available_width: f32) -> Vec<usize> {
+        let visible = self.visible_column_indices();
+        let frozen = self.frozen_columns.min(visible.len());
+        if frozen == 0 {
+            return visible;
+        }
+        let (pinned, rest) = visible.split_at(frozen);
+        self.first_scroll_column = self.first_scroll_column.min(rest.len().saturating_sub(1));
+        if let (true, Some((_, col))) = (self.scroll_to_focus, self.focused_cell)
+            && let Some(pos) = rest.iter().position(|&c| c == col)
+        {
+            let pinned_width: f32 = pinned.iter().map(|&c| self.column_widths[c]).sum();
+            let room = available_width - pinned_width;
+            if pos < self.first_scroll_column {
+                self.first_scroll_column = pos;
+            }
+            while self.first_scroll_column < pos
+                && rest[self.first_scroll_column..=pos].iter().map(|&c| self.column_widths[c]).sum::<f32>() > room
+            {
+                self.first_scroll_column += 1;
+            }
+        }
+        pinned.iter().chain(&rest[self.first_scroll_column..]).copied().collect()
+    }
+
+    // Shift+wheel or a sideways trackpad swipe over the table steps through
+    // the scrolled columns
+    fn scroll_columns_with_wheel(&mut self, ctx: &egui::Context, table_rect: egui::Rect) {
+        let (hovered, delta) = ctx.input(|i| {
+            let hovered = i.pointer.hover_pos().is_some_and(|pos| table_rect.contains(pos));
+            let delta = if i.modifiers.shift {
+                i.scroll_delta.x + i.scroll_delta.y
+            } else {
+                i.scroll_delta.x
+            };
+            (hovered, delta)
+        });
+        if !hovered {
+            return;
+        }
+        self.column_scroll_delta += delta;
+        let scrollable = self.visible_column_count().saturating_sub(self.frozen_columns);
+        while self.column_scroll_delta <= -COLUMN_SCROLL_STEP {
+            self.column_scroll_delta += COLUMN_SCROLL_STEP;
+            self.first_scroll_column = (self.first_scroll_column + 1).min(scrollable.saturating_sub(1));
+        }
+        while self.column_scroll_delta >= COLUMN_SCROLL_STEP {
+            self.column_scroll_delta -= COLUMN_SCROLL_STEP;
+            self.first_scroll_column = self.first_scroll_column.saturating_sub(1);
+        }
+    }
+
+    // Size the given columns to their header and a sample of the displayed rows
+    fn auto_fit_columns(&mut self, ctx: &egui::Context, columns: &[usize]) {
+        let rows = self.displayed_rows();
+        let sample = &rows[..rows.len().min(AUTO_FIT_SAMPLE_ROWS)];
+        let widths: Vec<f32> = columns
+            .iter()
+            .map(|&col| {
+                let cells = sample.iter().filter_map(|row| row.get(col));
+                fit_column_width(ctx, std::iter::once(self.csv_header[col].as_str()).chain(cells))
+            })
+            .collect();
+        for (&col, width) in columns.iter().zip(widths) {
+            self.column_widths[col] = width;
+        }
+        self.table_generation += 1;
+    }
+
+    // Toggle all columns on/off
+    fn toggle_all_columns(&mut self, visible: bool) {
+        self.visible_columns = vec![visible; self.csv_header.len()];
+    }
+
+    // Hide columns with no non-blank cell
+    fn hide_blank_columns(&mut self) {
+        let mut non_blank = vec![false; self.csv_header.len()];
+        for row in self.csv_data.rows() {
+            for (non_blank, cell) in non_blank.iter_mut().zip(row.iter()) {
+                if !cell.trim().is_empty() {
+                    *non_blank = true;
+                }
+            }
+        }
+        self.visible_columns = non_blank;
+    }
+
+    // Count visible columns
+    fn visible_column_count(&self) -> usize {
+        self.visible_columns.iter().filter(|&&v| v).count()
+    }
+
+    // Rows matched by the active search, or every row when there is none
+    fn filtered_rows(&self) -> Vec<Row<'_>> {
+        match &self.search_results {
+            Some(results) => results.iter().map(|&idx| self.csv_data.row(idx)).collect(),
+            None => self.csv_data.rows().collect(),
+        }
+    }
+
+    // Compute the footer aggregate for every column over the filtered rows.
+    // Columns are computed in parallel, and each column in parallel chunks.
+    fn compute_summary(&self) -> Vec<String> {
+        let rows = self.filtered_rows();
+        self.column_aggregates
+            .par_iter()
+            .enumerate()
+            .map(|(idx, aggregate)| aggregate.apply(&rows, idx))
+            .collect()
+    }
+
+    fn show_perf_overlay(&self, ctx: &egui::Context) {
+        let millis = |duration: Duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0);
+        let last = |duration: Option<Duration>| duration.map_or("-".to_string(), millis);
+        egui::Window::new(tr("Performance")).resizable(false).show(ctx, |ui| {
+            egui::Grid::new("perf_grid").num_columns(2).show(ui, |ui| {
+                ui.label(tr("Frame"));
+                ui.label(millis(self.perf.frame_time));
+                ui.end_row();
+                ui.label(tr("Table layout"));
+                ui.label(millis(self.perf.table_time));
+                ui.end_row();
+                ui.label(tr("Row measuring"));
+                ui.label(millis(self.row_height_cache.measure_time));
+                ui.end_row();
+                ui.label(tr("Rows rendered"));
+                ui.label(self.perf.rows_rendered.to_string());
+                ui.end_row();
+                ui.label(tr("Last load"));
+                ui.label(last(self.load_duration));
+                ui.end_row();
+                ui.label(tr("Last search"));
+                ui.label(last(self.perf.search_time));
+                ui.end_row();
+                ui.label(tr("Last sort"));
+                ui.label(last(self.perf.sort_time));
+                ui.end_row();
+            });
+        });
+    }
+
+    fn show_appearance_window(&mut self, ctx: &egui::Context) {
+        if !self.show_appearance {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        let mut zoom = None;
+        let mut language = i18n::language();
+        egui::Window::new(tr("Appearance")).open(&mut open).resizable(false).show(ctx, |ui| {
+            egui::Grid::new("appearance_grid").num_columns(2).show(ui, |ui| {
+                ui.label(tr("Language"));
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(language.label())
+                    .show_ui(ui, |ui| {
+                        for option in Language::ALL {
+                            ui.selectable_value(&mut language, option, option.label());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr("Theme"));
+                ui.horizontal(|ui| {
+                    for mode in ThemeMode::ALL {
+                        changed |= ui.radio_value(&mut self.theme.mode, mode, mode.label()).changed();
+                    }
+                });
+                ui.end_row();
+
+                // Unset colors are showing the theme's own
+                let current = ctx.style().visuals.clone();
+                let colors = [
+                    (tr("Accent"), &mut self.theme.accent, current.selection.bg_fill),
+                    (tr("Warning"), &mut self.theme.warning, current.warn_fg_color),
+                ];
+                for (label, color, default) in colors {
+                    ui.label(label);
+                    ui.horizontal(|ui| {
+                        let mut value = color.unwrap_or(default);
+                        if ui.color_edit_button_srgba(&mut value).changed() {
+                            *color = Some(value);
+                            changed = true;
+                        }
+                        if ui.add_enabled(color.is_some(), egui::Button::new(tr("Default"))).clicked() {
+                            *color = None;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+                }
+
+                ui.label(tr("Row density"));
+                ui.horizontal(|ui| {
+                    for density in Density::ALL {
+                        changed |= ui.radio_value(&mut self.theme.density, density, density.label()).changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(tr("Zoom"));
+                ui.horizontal(|ui| {
+                    let zoom_in = shortcuts::format_shortcut(&self.shortcuts.get(Action::ZoomIn));
+                    let zoom_out = shortcuts::format_shortcut(&self.shortcuts.get(Action::ZoomOut));
+                    let zoom_out_button = ui.button("-").on_hover_text(zoom_out);
+                    a11y::set_name(&zoom_out_button, tr("Zoom out"));
+                    if zoom_out_button.clicked() {
+                        zoom = Some(self.zoom / ZOOM_STEP);
+                    }
+                    ui.label(format!("{:.0}%", self.zoom * 100.0));
+                    let zoom_in_button = ui.button("+").on_hover_text(zoom_in);
+                    a11y::set_name(&zoom_in_button, tr("Zoom in"));
+                    if zoom_in_button.clicked() {
+                        zoom = Some(self.zoom * ZOOM_STEP);
+                    }
+                    if ui.add_enabled(self.zoom != 1.0, egui::Button::new(tr("Reset"))).clicked() {
+                        zoom = Some(1.0);
+                    }
+                });
+                ui.end_row();
+            });
+        });
+        if let Some(zoom) = zoom {
+            self.set_zoom(zoom);
+        }
+        if language != i18n::language() {
+            i18n::set_language(language);
+            language.save_to(&mut self.config);
+            changed = true;
+        }
+        if !open {
+            self.show_appearance = false;
+        }
+
+        if changed {
+            self.theme.save_to(&mut self.config);
+            if let Err(err) = self.config.save() {
+                eprintln!("Error saving settings: {}", err);
+            }
+        }
+    }
+
+    fn show_save_options_window(&mut self, ctx: &egui::Context) {
+        if !self.show_save_options {
+            return;
+        }
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(tr("Save options")).open(&mut open).resizable(false).show(ctx, |ui| {
+            csv_options_ui(ui, &mut self.export_settings.csv);
+            ui.separator();
+            ui.horizontal(|ui| {
+                save = ui.add_enabled(self.can_save(), egui::Button::new(tr("Save…"))).clicked();
+                if ui.button(tr("Cancel")).clicked() {
+                    self.show_save_options = false;
+                }
+            });
+        });
+        if !open {
+            self.show_save_options = false;
+        }
+        if save {
+            self.show_save_options = false;
+            self.save_export_settings();
+            if let Some(path) = FileDialog::new().add_filter("CSV", &["csv"]).save_file()
+                && let Some(path_str) = path.to_str()
+            {
+                let format = ExportFormat::Csv(self.export_settings.csv);
+                self.start_save(path_str.to_string(), format, Selection::default());
+            }
+        }
+    }
+
+    fn save_export_settings(&mut self) {
+        self.export_settings.save_to(&mut self.config);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
+    }
+
+    // Lists each action with its binding; "Change" records the next key
+    // pressed, Escape cancels
+    fn show_shortcuts_window(&mut self, ctx: &egui::Context) {
+        if !self.show_shortcuts {
+            return;
+        }
+        let mut changed = false;
+        if let Some(action) = self.capturing_shortcut {
+            if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.capturing_shortcut = None;
+            } else if let Some(shortcut) = shortcuts::pressed_shortcut(ctx) {
+                self.shortcuts.set(action, shortcut);
+                self.capturing_shortcut = None;
+                changed = true;
+            }
+        }
+
+        let mut open = true;
+        egui::Window::new(tr("Keyboard Shortcuts")).open(&mut open).resizable(false).show(ctx, |ui| {
+            egui::Grid::new("shortcuts_grid").num_columns(3).show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(action.label());
+                    if self.capturing_shortcut == Some(action) {
+                        ui.label(tr("Press a key..."));
+                    } else {
+                        ui.monospace(shortcuts::format_shortcut(&self.shortcuts.get(action)));
+                    }
+                    if ui.button(tr("Change")).clicked() {
+                        self.capturing_shortcut = Some(action);
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            if ui.button(tr("Reset to Defaults")).clicked() {
+                self.shortcuts = Shortcuts::default();
+                self.capturing_shortcut = None;
+                changed = true;
+            }
+        });
+        if !open {
+            self.show_shortcuts = false;
+            self.capturing_shortcut = None;
+        }
+
+        if changed {
+            self.shortcuts.save_to(&mut self.config);
+            if let Err(err) = self.config.save() {
+                eprintln!("Error saving settings: {}", err);
+            }
         }
     }
 
-    fn perform_search(&self) -> Vec<Vec<String>> {
-        let query = self.search_query.to_lowercase();
-        self.csv_data
-            .iter()
-            .filter(|row| {
-                row.iter()
-                    .enumerate()
-                    .any(|(idx, cell)| idx as u8 == self.search_header && cell.to_lowercase().contains(&query))
-            })
-            .cloned()
-            .collect()
+    fn is_dirty(&self) -> bool {
+        self.edit_generation != self.saved_generation
     }
 
-    fn get_row_by_number(&self, row_num: usize) -> Option<Vec<String>> {
-        if row_num == 1 {
-            Some(self.csv_header.clone())
-        } else if row_num > 1 && row_num - 2 < self.csv_data.len() {
-            Some(self.csv_data[row_num - 2].clone())
+    // Data index of the row at a position in the displayed rows
+    fn displayed_index(&self, pos: usize) -> Option<usize> {
+        if let Some(row_num) = self.selected_row {
+            return self.data_index_for_row_number(row_num).filter(|_| pos == 0);
+        }
+        if let Some(results) = &self.search_results {
+            return results.get(pos).copied();
+        }
+        let start = if self.pager.is_some() || self.infinite_scroll {
+            0
+        } else {
+            self.current_page.saturating_mul(self.rows_per_page)
+        };
+        let pos = start.checked_add(pos)?;
+        if pos < self.csv_data.len() {
+            Some(self.sorted_index(pos))
         } else {
             None
         }
     }
 
-    // Filter columns based on visibility
-    fn filter_visible_columns(&self, row: &Vec<String>) -> Vec<String> {
-        row.iter()
-            .enumerate()
-            .filter(|(idx, _)| *idx < self.visible_columns.len() && self.visible_columns[*idx])
-            .map(|(_, cell)| cell.clone())
-            .collect()
+    // What is narrowing the displayed rows, if anything
+    fn filter_summary(&self) -> Option<String> {
+        if let Some(row_num) = self.selected_row {
+            return Some(tr!("Row {}", row_num));
+        }
+        let matches = self.search_results.as_ref()?.len();
+        let filter = if let Some(col) = self.invalid_filter {
+            tr!("{} values that aren't {}", self.csv_header[col], self.column_types[col].label())
+        } else if self.invisible_filter {
+            let column = self.search_column.map_or(tr("all columns"), |col| self.csv_header[col].as_str());
+            tr!("invisible characters in {}", column)
+        } else if let Some((col, values)) = &self.value_filter {
+            tr!("{} in {} values", self.csv_header[*col], values.len())
+        } else {
+            let column = self.search_column.map_or(tr("all columns"), |col| self.csv_header[col].as_str());
+            if self.search_invert {
+                tr!("excluding \"{}\" in {}", self.search_query, column)
+            } else {
+                tr!("matching \"{}\" in {}", self.search_query, column)
+            }
+        };
+        Some(tr!("Filter: {} ({} rows)", filter, matches))
     }
 
-    // Get visible headers
-    fn get_visible_headers(&self) -> Vec<String> {
-        self.filter_visible_columns(&self.csv_header)
+    fn set_panel_open(&mut self, panel: Panel, open: bool) {
+        self.layout.set_open(panel, open);
+        self.save_layout();
     }
 
-    // Initialize visible columns when CSV is loaded
-    fn initialize_visible_columns(&mut self) {
-        self.visible_columns = vec![true; self.csv_header.len()];
+    fn save_layout(&mut self) {
+        self.layout.save_to(&mut self.config);
+        if let Err(err) = self.config.save() {
+            eprintln!("Error saving settings: {}", err);
+        }
     }
 
-    // Toggle all columns on/off
-    fn toggle_all_columns(&mut self, visible: bool) {
-        self.visible_columns = vec![visible; self.csv_header.len()];
+    // Open panels, each docked on its side of the table with buttons to move
+    // it across or close it
+    fn show_side_panels(&mut self, ctx: &egui::Context) {
+        if self.csv_header.is_empty() {
+            return;
+        }
+        for panel in Panel::ALL {
+            if !self.layout.is_open(panel) {
+                continue;
+            }
+            let side = self.layout.side(panel);
+            let mut close = false;
+            let mut move_across = false;
+            egui::SidePanel::new(side, egui::Id::new("side_panel").with(panel as usize))
+                .resizable(true)
+                .default_width(260.0)
+                .min_width(160.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(panel.label());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let close_button = ui.small_button("🗙").on_hover_text(tr("Close panel"));
+                            a11y::set_name(&close_button, tr("Close panel"));
+                            close = close_button.clicked();
+                            let arrow = match side {
+                                Side::Left => "➡",
+                                Side::Right => "⬅",
+                            };
+                            let move_button = ui.small_button(arrow).on_hover_text(tr("Dock on the other side"));
+                            a11y::set_name(&move_button, tr("Dock on the other side"));
+                            move_across = move_button.clicked();
+                        });
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| match panel {
+                        Panel::Search => self.show_search_panel(ui),
+                        Panel::Columns => self.show_columns_panel(ui),
+                        Panel::Details => self.show_details_panel(ui),
+                        Panel::Validation => self.show_validation_panel(ui),
+                        Panel::Quality => self.show_quality_panel(ui),
+                    });
+                });
+            if close {
+                self.set_panel_open(panel, false);
+            }
+            if move_across {
+                self.layout.move_to_other_side(panel);
+                self.save_layout();
+            }
+        }
     }
 
-    // Count visible columns
-    fn visible_column_count(&self) -> usize {
-        self.visible_columns.iter().filter(|&&v| v).count()
+    // Text search, the value filter and row lookup
+    fn show_search_panel(&mut self, ui: &mut egui::Ui) {
+        let search_label = ui.label(tr("Search:"));
+        let search_field = ui
+            .add(egui::TextEdit::singleline(&mut self.search_query).desired_width(f32::INFINITY))
+            .labelled_by(search_label.id);
+        if self.focus_search {
+            search_field.request_focus();
+            self.focus_search = false;
+        }
+        let query_changed = search_field.changed();
+
+        let previous_column = self.search_column;
+        ui.horizontal(|ui| {
+            ui.label(tr("Column:"));
+            let selected = match self.search_column {
+                Some(col) => self.csv_header[col].as_str(),
+                None => tr("All columns"),
+            };
+            egui::ComboBox::from_id_source("search_column")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.search_column, None, tr("All columns"));
+                    for (idx, name) in self.csv_header.iter().enumerate() {
+                        ui.selectable_value(&mut self.search_column, Some(idx), name);
+                    }
+                });
+        });
+        ui.checkbox(&mut self.search_exact, tr("Exact match"))
+            .on_hover_text(tr("Match whole cell values, case-sensitively"));
+        let invert_toggled = ui
+            .checkbox(&mut self.search_invert, tr("Exclude matches"))
+            .on_hover_text(tr("Show the rows that don't match instead"))
+            .changed();
+        let query_changed = query_changed || self.search_column != previous_column || invert_toggled;
+
+        ui.horizontal(|ui| {
+            // Once results are showing, keep them in step with the query
+            if ui.button(tr("Search")).clicked() || (query_changed && self.search_results.is_some()) {
+                self.value_filter = None;
+                self.invalid_filter = None;
+                self.invisible_filter = false;
+                self.run_search();
+            }
+            if ui.button(tr("Clear Search")).clicked() {
+                self.clear_search();
+            }
+        });
+
+        if let Some((col, values)) = &self.value_filter {
+            ui.label(tr!("{} filtered to {} values", self.csv_header[*col], values.len()));
+        }
+        if let Some(col) = self.invalid_filter {
+            ui.label(tr!("{} filtered to values that aren't {}", self.csv_header[col], self.column_types[col].label()));
+        }
+        if self.invisible_filter {
+            ui.label(tr("Filtered to cells with invisible characters"));
+        }
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let row_label = ui.label(tr("Go to row:"));
+            ui.add(egui::TextEdit::singleline(&mut self.row_number_input).desired_width(80.0))
+                .labelled_by(row_label.id);
+            if ui.button(tr("Go")).clicked()
+                && let Ok(row_num) = self.row_number_input.trim().parse::<usize>()
+            {
+                self.select_row(row_num);
+            }
+        });
     }
-}
 
-impl eframe::App for MyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
+    // Column visibility, widths and freezing
+    fn show_columns_panel(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr!("Visible: {}/{}", self.visible_column_count(), self.csv_header.len()));
+        ui.horizontal_wrapped(|ui| {
+            if ui.button(tr("Show All")).clicked() {
+                self.toggle_all_columns(true);
+            }
+            if ui.button(tr("Hide All")).clicked() {
+                self.toggle_all_columns(false);
+            }
+            if ui.button(tr("Hide All Blank Columns")).clicked() {
+                self.hide_blank_columns();
+            }
+            if ui.button(tr("Auto-fit Widths")).on_hover_text(tr!("Fit visible columns to the first {} displayed rows", AUTO_FIT_SAMPLE_ROWS)).clicked() {
+                let columns = self.visible_column_indices();
+                self.auto_fit_columns(ui.ctx(), &columns);
+            }
+        });
+        ui.horizontal(|ui| {
+            let freeze_label = ui.label(tr("Freeze:"));
+            let visible_count = self.visible_column_count();
+            ui.add(
+                egui::DragValue::new(&mut self.frozen_columns)
+                    .clamp_range(0..=visible_count)
+                    .suffix(tr(" columns")),
+            )
+            .labelled_by(freeze_label.id)
+            .on_hover_text(tr("Keep the first visible columns in place while scrolling sideways"));
+        });
+        ui.separator();
+
+        for (idx, header) in self.csv_header.iter().enumerate() {
+            if idx < self.visible_columns.len() {
+                ui.push_id(idx, |ui| {
+                    ui.checkbox(&mut self.visible_columns[idx], header)
+                        .on_hover_text(tr!("Toggle visibility for column: {}", header));
+                });
+            }
+        }
+    }
+
+    fn show_status_bar(&self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                // Load CSV file
-                if ui.button("Load CSV").clicked() {
-                    if let Some(path) = FileDialog::new().add_filter("CSV", &["csv"]).pick_file() {
-                        if let Some(path_str) = path.to_str() {
-                            if let Ok((header, data)) = read_csv_with_header(path_str) {
-                                self.csv_header = header;
-                                self.csv_data = data;
-                                self.current_page = 0;
-                                self.search_query.clear();
-                                self.search_results = None;
-                                self.row_number_input.clear();
-                                self.selected_row = None;
-                                self.initialize_visible_columns();
-                            }
-                        } else {
-                            eprintln!("Selected file path is not valid UTF-8");
-                        }
+                if let Some(path) = &self.file_path {
+                    ui.label(file_name(path)).on_hover_text(path.as_str());
+                    if self.is_dirty() {
+                        ui.colored_label(ui.visuals().warn_fg_color, tr("Modified"))
+                            .on_hover_text(tr("There are changes that haven't been saved"));
                     }
+                    ui.separator();
                 }
-                // Save CSV file
-                if ui.button("Save CSV").clicked() {
-                    if let Some(path) = FileDialog::new().save_file() {
-                        if let Some(path_str) = path.to_str() {
-                            if let Err(err) = save_csv(path_str, &self.csv_header, &self.csv_data) {
-                                eprintln!("Error saving CSV: {}", err);
-                            }
-                        }
-                    }
+                let rows = self.pager.as_ref().map_or(self.csv_data.len(), DiskPager::total_rows);
+                ui.label(tr!("Rows: {}", rows));
+                ui.separator();
+                ui.label(tr!("Columns: {}", self.csv_header.len()));
+                ui.separator();
+                let memory = self.csv_data.memory_usage()
+                    + self.search_results.as_ref().map_or(0, |results| results.capacity() * size_of::<usize>());
+                ui.label(tr!("Memory: {}", format_bytes(memory)))
+                    .on_hover_text(tr("Approximate memory held by the loaded rows and search results"));
+                if let Some(filter) = self.filter_summary() {
+                    ui.separator();
+                    ui.label(filter);
                 }
-
-                // Column visibility controls
-                if !self.csv_header.is_empty() {
+                if !self.selected_rows.is_empty() {
                     ui.separator();
-                    if ui.button(if self.show_column_controls { "Hide Column Controls" } else { "Show Column Controls" }).clicked() {
-                        self.show_column_controls = !self.show_column_controls;
-                    }
-
-                    ui.label(format!("Visible: {}/{}", self.visible_column_count(), self.csv_header.len()));
+                    let hint = if self.pager.is_some() {
+                        tr("Ctrl+C copies the selected rows")
+                    } else {
+                        tr("Ctrl+C copies the selected rows and Delete removes them")
+                    };
+                    ui.label(tr!("{} rows selected", self.selected_rows.len())).on_hover_text(hint);
+                }
+                if let Some((pos, col)) = self.focused_cell
+                    && let Some(idx) = self.displayed_index(pos)
+                {
+                    ui.separator();
+                    ui.label(tr!("Cell: row {}, {}", idx + self.row_offset() + 2, self.csv_header[col]));
+                }
+                if !self.announcement.is_empty() {
+                    ui.separator();
+                    a11y::make_live(&ui.label(&self.announcement));
+                }
+                if let Some(duration) = self.load_duration {
+                    ui.separator();
+                    ui.label(tr!("Loaded in {} s", format!("{:.2}", duration.as_secs_f64())))
+                        .on_hover_text(tr!(
+                            "Press {} for performance details",
+                            shortcuts::format_shortcut(&self.shortcuts.get(Action::TogglePerfOverlay))
+                        ));
                 }
             });
+        });
+    }
 
-            // Column visibility controls
-            if self.show_column_controls && !self.csv_header.is_empty() {
-                ui.separator();
+    fn show_summary_footer(&mut self, ctx: &egui::Context) {
+        if self.summary_cache.is_none() {
+            self.summary_cache = Some(self.compute_summary());
+        }
+        let summary = self.summary_cache.clone().unwrap_or_default();
+        let mut changed = false;
+
+        egui::TopBottomPanel::bottom("summary_footer").show(ctx, |ui| {
+            egui::ScrollArea::horizontal().show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    if ui.button("Show All").clicked() {
-                        self.toggle_all_columns(true);
-                    }
-                    if ui.button("Hide All").clicked() {
-                        self.toggle_all_columns(false);
-                    }
-                    if ui.button("Hide All Blank Columns").clicked() {
-                        let mut non_blank = vec![false; self.csv_header.len()];
-                        for row in &self.csv_data {
-                            for (i, cell) in row.iter().enumerate() {
-                                if !cell.trim().is_empty() {
-                                    non_blank[i] = true;
-                                }
-                            }
+                    for (idx, header) in self.csv_header.iter().enumerate() {
+                        if !self.visible_columns.get(idx).copied().unwrap_or(false) {
+                            continue;
                         }
-                        self.visible_columns = non_blank;
+                        ui.push_id(idx, |ui| {
+                            ui.vertical(|ui| {
+                                ui.strong(header);
+                                let aggregate = &mut self.column_aggregates[idx];
+                                egui::ComboBox::from_id_source("aggregate")
+                                    .selected_text(aggregate.label())
+                                    .show_ui(ui, |ui| {
+                                        for option in Aggregate::ALL {
+                                            changed |= ui.selectable_value(aggregate, option, option.label()).changed();
+                                        }
+                                    });
+                                ui.label(summary.get(idx).map(String::as_str).unwrap_or("-"));
+                            });
+                        });
+                        ui.separator();
                     }
                 });
+            });
+        });
 
-                ui.label("Column Visibility:");
-                ui.push_id("column_visibility_scroll", |ui| {
-                    egui::ScrollArea::horizontal().show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            for (idx, header) in self.csv_header.iter().enumerate() {
-                                if idx < self.visible_columns.len() {
-                                    ui.push_id(idx, |ui| {
-                                        ui.checkbox(&mut self.visible_columns[idx], header)
-                                            .on_hover_text(format!("Toggle visibility for column: {}", header));
-                                    });
+        if changed {
+            self.summary_cache = None;
+        }
+    }
+}
+
+impl eframe::App for MyApp {
+    fn on_close_event(&mut self) -> bool {
+        self.remember_view();
+        true
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let frame_started = Instant::now();
+        let system_dark = frame.info().system_theme.map(|theme| theme == eframe::Theme::Dark);
+        let visuals = self.theme.visuals(system_dark);
+        let spacing = self.theme.spacing();
+        let style = ctx.style();
+        if style.visuals != visuals || style.spacing != spacing {
+            ctx.set_style(egui::Style {
+                visuals,
+                spacing,
+                ..(*style).clone()
+            });
+        }
+        let pixels_per_point = frame.info().native_pixels_per_point.unwrap_or(1.0) * self.zoom;
+        if (ctx.pixels_per_point() - pixels_per_point).abs() > f32::EPSILON {
+            ctx.set_pixels_per_point(pixels_per_point);
+        }
+        self.poll_jobs();
+        if !self.jobs.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        if !self.has_job(JobKind::Load)
+            && !self.has_job(JobKind::Sort)
+            && !self.has_job(JobKind::Search)
+            && let Some((row, col)) = self.pending_focus.take()
+        {
+            self.focus_location(row, col);
+        }
+
+        if !self.csv_header.is_empty() {
+            self.show_status_bar(ctx);
+        }
+        if !self.jobs.is_empty() {
+            self.show_jobs_panel(ctx);
+        }
+        if self.show_summary_footer && !self.csv_header.is_empty() {
+            self.show_summary_footer(ctx);
+        }
+        if self.capturing_shortcut.is_none() {
+            for action in self.shortcuts.triggered(ctx) {
+                self.run_action(action);
+            }
+        }
+        if self.show_perf_overlay {
+            self.show_perf_overlay(ctx);
+        }
+        self.show_shortcuts_window(ctx);
+        self.show_appearance_window(ctx);
+        self.show_save_options_window(ctx);
+        if let Some(mut palette) = self.palette.take() {
+            match palette.show(ctx, self.palette_entries()) {
+                Outcome::Open => self.palette = Some(palette),
+                Outcome::Closed => {}
+                Outcome::Chosen(command) => self.run_command(ctx, command),
+            }
+        }
+        if !self.csv_header.is_empty() && self.capturing_shortcut.is_none() {
+            self.handle_grid_keys(ctx);
+        }
+        self.show_cell_viewer(ctx);
+        self.show_rename_column(ctx);
+        self.show_pseudonymize(ctx);
+        self.show_normalize(ctx);
+        self.show_reference_check(ctx);
+        self.show_decimal_separators(ctx);
+        self.show_unit_conversion(ctx);
+        self.show_phone_formatting(ctx);
+        self.show_currency_normalizing(ctx);
+        self.show_json_flattening(ctx);
+        self.show_spelling(ctx);
+        self.show_duplicate_headers(ctx);
+        self.show_save_problem(ctx);
+        self.show_ragged_repair(ctx);
+        self.show_value_picker(ctx);
+        self.show_split_export(ctx);
+        self.show_schema_window(ctx);
+        self.show_personal_data_window(ctx);
+
+        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                // Load CSV file
+                if ui.button(tr("Load CSV")).clicked() {
+                    self.run_action(Action::OpenFile);
+                }
+                // Page a large file from disk instead of loading all of it
+                if ui
+                    .button(tr("Open Large CSV"))
+                    .on_hover_text(tr("Read pages from disk on demand instead of loading the whole file"))
+                    .clicked()
+                    && let Some(path) = pick_csv_file()
+                {
+                    self.open_paged(&path);
+                }
+                // Load only the first rows for a quick look
+                if ui
+                    .button(tr("Preview CSV"))
+                    .on_hover_text(tr("Load only the first rows of the file"))
+                    .clicked()
+                    && let Some(path) = pick_csv_file()
+                {
+                    self.open_preview(&path);
+                }
+                let preview_limit =
+                    ui.add(egui::DragValue::new(&mut self.preview_limit).clamp_range(1..=10_000_000).suffix(tr(" rows")));
+                a11y::set_name(&preview_limit, tr("Rows to preview"));
+                // Save CSV file
+                let save_button = ui
+                    .add_enabled(self.can_save(), egui::Button::new(tr("Save CSV")))
+                    .on_disabled_hover_text(if self.pager.is_some() {
+                        tr("Files paged from disk can't be saved")
+                    } else if self.is_preview {
+                        tr("Load the rest of the file before saving")
+                    } else {
+                        tr("A save is already in progress")
+                    });
+                if save_button.clicked() {
+                    self.run_action(Action::SaveFile);
+                }
+                ui.add_enabled_ui(self.can_export(), |ui| {
+                    ui.menu_button(tr("Export"), |ui| {
+                        let view = self.view_selection();
+                        let export_button = ui.button(tr("Export current view…")).on_hover_text(tr!(
+                            "Save exactly what is shown: {} rows matching the search, in sorted order, with the {} visible columns",
+                            view.row_count(&self.csv_data),
+                            view.columns.as_ref().map_or(self.csv_header.len(), Vec::len)
+                        ));
+                        if export_button.clicked() {
+                            ui.close_menu();
+                            self.run_action(Action::ExportView);
+                        }
+                        ui.add_enabled_ui(!self.selected_rows.is_empty(), |ui| {
+                            let export_selection = ui
+                                .button(tr("Export selection…"))
+                                .on_hover_text(tr!("Save the {} selected rows with only the visible columns", self.selected_rows.len()))
+                                .on_disabled_hover_text(tr("Click rows to select them, with Shift or Ctrl to select several"));
+                            if export_selection.clicked() {
+                                ui.close_menu();
+                                self.run_action(Action::ExportSelection);
+                            }
+                            let json = ExportFormat::json(&self.export_settings, false);
+                            for (label, format) in [(tr("Copy selection as CSV"), ExportFormat::Csv(self.export_settings.csv)), (tr("Copy selection as JSON"), json)] {
+                                if ui.button(label).clicked() {
+                                    ui.close_menu();
+                                    let (data, selection) = self.masked_copy(self.row_selection());
+                                    let text = export::render(format, &self.csv_header, &data, &selection);
+                                    ui.output_mut(|o| o.copied_text = text);
+                                }
+                            }
+                        });
+                        ui.menu_button(tr("Export in chunks"), |ui| {
+                            ui.horizontal(|ui| {
+                                let label = ui.label(tr("Rows per file:"));
+                                let rows = ui
+                                    .add(egui::DragValue::new(&mut self.export_settings.chunk_rows).clamp_range(1..=10_000_000))
+                                    .labelled_by(label.id);
+                                if rows.lost_focus() || rows.drag_released() {
+                                    self.save_export_settings();
                                 }
+                            });
+                            let export_chunks = ui
+                                .button(tr("Choose file name and export…"))
+                                .on_hover_text(tr("Save the rows matching the search as numbered files, data_001.csv, data_002.csv…, each with the header, for systems that limit the rows per upload"));
+                            if export_chunks.clicked() {
+                                ui.close_menu();
+                                self.save_export_settings();
+                                self.export_chunks();
+                            }
+                        });
+                        let export_template = ui
+                            .button(tr("Export with template…"))
+                            .on_hover_text(tr("Write a text file through each row matching the search, with {{Column name}} replaced by the row's value"));
+                        if export_template.clicked() {
+                            ui.close_menu();
+                            self.export_with_template();
+                        }
+                        let export_transposed = ui
+                            .button(tr("Export transposed…"))
+                            .on_hover_text(tr("Save the rows matching the search with rows and columns swapped, one line per visible column. The table here is not changed."));
+                        if export_transposed.clicked() {
+                            ui.close_menu();
+                            self.export_transposed();
+                        }
+                        let find_personal_data = ui
+                            .button(tr("Find personal data…"))
+                            .on_hover_text(tr("Look for emails, phone numbers, card and ID numbers, and mask the columns that hold them in everything exported"));
+                        if find_personal_data.clicked() {
+                            ui.close_menu();
+                            self.show_personal_data = true;
+                            if self.personal_data.as_ref().is_none_or(|(generation, _)| *generation != self.data_generation) {
+                                self.find_personal_data();
                             }
+                        }
+                        let export_profile = ui
+                            .button(tr("Export column profile…"))
+                            .on_hover_text(tr("Save count, blanks, distinct values, sum, mean, min and max for each visible column, over the rows matching the search"));
+                        if export_profile.clicked() {
+                            ui.close_menu();
+                            self.export_profile();
+                        }
+                        let export_changes = ui
+                            .add_enabled(!self.edits.is_empty(), egui::Button::new(tr("Export changes…")))
+                            .on_hover_text(tr("Save a log of deleted rows and renamed columns, with row, column, old and new values, for review before sharing"))
+                            .on_disabled_hover_text(tr("No rows deleted or columns renamed since the file was opened or saved"));
+                        if export_changes.clicked() {
+                            ui.close_menu();
+                            self.export_changes();
+                        }
+                        let copy_markdown = ui
+                            .button(tr("Copy as Markdown"))
+                            .on_hover_text(tr("Copy the rows matching the search, with only the visible columns, as a Markdown table"));
+                        if copy_markdown.clicked() {
+                            ui.close_menu();
+                            let (data, selection) = self.masked_copy(self.view_selection());
+                            let text = export::render(ExportFormat::Markdown, &self.csv_header, &data, &selection);
+                            ui.output_mut(|o| o.copied_text = text);
+                        }
+                        ui.separator();
+                        let settings = &mut self.export_settings;
+                        let mut changed = false;
+                        ui.menu_button(tr("CSV options"), |ui| changed |= csv_options_ui(ui, &mut settings.csv));
+                        changed |= ui
+                            .checkbox(&mut settings.coerce_numbers, tr("Numbers as JSON numbers"))
+                            .on_hover_text(tr("In JSON and NDJSON exports, write cells like 42 or -1.5e3 as numbers instead of strings"))
+                            .changed();
+                        changed |= ui
+                            .checkbox(&mut settings.pdf_landscape, tr("PDF in landscape"))
+                            .on_hover_text(tr("Turn PDF pages sideways to fit more columns"))
+                            .changed();
+                        ui.horizontal(|ui| {
+                            ui.label(tr("SQL:"));
+                            egui::ComboBox::from_id_source("sql_dialect")
+                                .selected_text(settings.sql_dialect.label())
+                                .show_ui(ui, |ui| {
+                                    for dialect in SqlDialect::ALL {
+                                        changed |= ui.selectable_value(&mut settings.sql_dialect, dialect, dialect.label()).changed();
+                                    }
+                                });
+                            let table = ui.add(
+                                egui::TextEdit::singleline(&mut settings.sql_table)
+                                    .hint_text(tr("Table name"))
+                                    .desired_width(120.0),
+                            );
+                            a11y::set_name(&table, tr("SQL table name"));
+                            changed |= table.lost_focus();
+                        })
+                        .response
+                        .on_hover_text(tr("Used when exporting to a .sql file. Leave the table name empty to name it after the file."));
+                        ui.menu_button(tr("XML options"), |ui| {
+                            egui::Grid::new("xml_options").num_columns(2).show(ui, |ui| {
+                                ui.label(tr("Root element:"));
+                                changed |= ui.text_edit_singleline(&mut settings.xml_root).lost_focus();
+                                ui.end_row();
+                                ui.label(tr("Row element:"));
+                                changed |= ui.text_edit_singleline(&mut settings.xml_record).lost_focus();
+                                ui.end_row();
+                            });
+                            ui.separator();
+                            ui.label(tr("Write as attributes:"))
+                                .on_hover_text(tr("Checked columns become attributes of the row element; the rest become child elements"));
+                            egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                                for name in &self.csv_header {
+                                    let mut attribute = settings.xml_attributes.contains(name);
+                                    if ui.checkbox(&mut attribute, name.as_str()).changed() {
+                                        if attribute {
+                                            settings.xml_attributes.push(name.clone());
+                                        } else {
+                                            settings.xml_attributes.retain(|other| other != name);
+                                        }
+                                        changed = true;
+                                    }
+                                }
+                            });
                         });
+                        if changed {
+                            self.save_export_settings();
+                        }
                     });
                 });
-            }
-
-            ui.separator();
-
-            // Search by text:
-            ui.horizontal(|ui| {
-                ui.label("Search:");
-                ui.text_edit_singleline(&mut self.search_query);
-
-                ui.label("Column Index:");
-                let mut search_header = self.search_header.to_string();
-                ui.text_edit_singleline(&mut search_header);
-                self.search_header = match search_header.parse() { Ok(n) => n, Err(_) => 0};
-
-                if ui.button("Search").clicked() {
-                    self.search_results = Some(self.perform_search());
-                    self.selected_row = None;
+                if ui.button(tr("Shortcuts")).clicked() {
+                    self.show_shortcuts = true;
                 }
-
-                if ui.button("Clear Search").clicked() {
-                    self.search_query.clear();
-                    self.search_results = None;
+                if ui.button(tr("Appearance")).clicked() {
+                    self.show_appearance = true;
                 }
-            });
-            ui.separator();
 
-            // Row lookup:
-            ui.horizontal(|ui| {
-                ui.label("Go to row:");
-                ui.text_edit_singleline(&mut self.row_number_input);
-                if ui.button("Go").clicked() {
-                    if let Ok(row_num) = self.row_number_input.trim().parse::<usize>() {
-                        if row_num == 1 {
-                            self.selected_row = Some(self.csv_header.clone());
-                        } else if let Some(row) = self.get_row_by_number(row_num) {
-                            self.selected_row = Some(row);
-                        } else {
-                            self.selected_row = None;
+                if !self.csv_header.is_empty() {
+                    ui.separator();
+                    ui.menu_button(tr("Panels"), |ui| {
+                        for panel in Panel::ALL {
+                            let mut open = self.layout.is_open(panel);
+                            if ui.checkbox(&mut open, panel.label()).changed() {
+                                self.set_panel_open(panel, open);
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    ui.checkbox(&mut self.show_summary_footer, tr("Summary Footer"));
+                    ui.checkbox(&mut self.show_row_numbers, tr("Row Numbers"));
+                    let wrap_toggle = ui
+                        .checkbox(&mut self.wrap_cells, tr("Wrap Text"))
+                        .on_hover_text(tr("Wrap long cells onto several lines, or cut them off at the column edge. Double-click a cell to see all of it."));
+                    if wrap_toggle.changed() {
+                        self.config.set("wrap_cells", self.wrap_cells.to_string());
+                        if let Err(err) = self.config.save() {
+                            eprintln!("Error saving settings: {}", err);
+                        }
+                    }
+                    let highlight_toggle = ui
+                        .checkbox(&mut self.highlight_invalid, tr("Highlight Invalid Values"))
+                        .on_hover_text(tr("Mark cells that don't parse as their column's type, such as N/A in a number column. Types come from the schema, or are guessed from the first rows."));
+                    if highlight_toggle.changed() {
+                        self.config.set("highlight_invalid", self.highlight_invalid.to_string());
+                        if let Err(err) = self.config.save() {
+                            eprintln!("Error saving settings: {}", err);
+                        }
+                    }
+                    let invisible_toggle = ui
+                        .checkbox(&mut self.show_invisible, tr("Show Invisible Characters"))
+                        .on_hover_text(tr("Mark cells with no-break spaces, zero-width or control characters, or look-alike letters, and show the hidden characters"));
+                    if invisible_toggle.changed() {
+                        self.config.set("show_invisible", self.show_invisible.to_string());
+                        if let Err(err) = self.config.save() {
+                            eprintln!("Error saving settings: {}", err);
                         }
-                        self.search_results = None;
                     }
                 }
             });
+        });
+        self.show_side_panels(ctx);
 
-            if self.search_results.is_none() && self.selected_row.is_none() {
-                ui.separator();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.is_preview {
                 ui.horizontal(|ui| {
-                    if ui.button("Previous").clicked() && self.current_page > 0 {
-                        self.current_page -= 1;
-                    }
-                    ui.label(format!("Page {} of {}", self.current_page + 1, self.total_pages()));
-                    if ui.button("Next").clicked() && self.current_page + 1 < self.total_pages() {
-                        self.current_page += 1;
+                    ui.label(tr!("Previewing the first {} rows of the file.", self.csv_data.len()));
+                    if ui.button(tr("Load the rest")).clicked()
+                        && let Some(path) = self.file_path.clone()
+                    {
+                        self.open_csv(&path);
                     }
                 });
+                ui.separator();
             }
 
-            ui.separator();
+            if let Some(pager) = &self.pager {
+                ui.label(tr!(
+                    "Paging {} rows from disk. Search and summaries cover the current page only.",
+                    pager.total_rows()
+                ));
+                ui.separator();
+            }
 
-            let rows_to_display: Vec<Vec<String>> = if !self.csv_header.is_empty() {
-                if let Some(ref selected) = self.selected_row {
-                    if selected != &self.csv_header {
-                        vec![self.csv_header.clone(), selected.clone()]
+            if self.search_results.is_none() && self.selected_row.is_none() && !self.csv_header.is_empty() {
+                ui.horizontal(|ui| {
+                    if self.infinite_scroll {
+                        let shown = self.scroll_rows.min(self.csv_data.len());
+                        ui.label(tr!("Showing {} of {} rows", shown, self.csv_data.len()));
                     } else {
-                        vec![self.csv_header.clone()]
+                        let last_page = self.total_pages() - 1;
+                        if ui.add_enabled(self.current_page > 0, egui::Button::new(tr("First"))).clicked() {
+                            self.go_to_page(0);
+                        }
+                        if ui.button(tr("Previous")).clicked() && self.current_page > 0 {
+                            self.go_to_page(self.current_page - 1);
+                        }
+                        ui.label(tr!("Page {} of {}", self.current_page + 1, self.total_pages()));
+                        if ui.button(tr("Next")).clicked() && self.current_page < last_page {
+                            self.go_to_page(self.current_page + 1);
+                        }
+                        if ui.add_enabled(self.current_page < last_page, egui::Button::new(tr("Last"))).clicked() {
+                            self.go_to_page(last_page);
+                        }
+
+                        ui.separator();
+                        let page_label = ui.label(tr("Go to page:"));
+                        let page_field = ui
+                            .add(
+                                egui::TextEdit::singleline(&mut self.page_number_input)
+                                    .hint_text((self.current_page + 1).to_string())
+                                    .desired_width(60.0),
+                            )
+                            .labelled_by(page_label.id);
+                        let submitted = page_field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if ui.button(tr("Go")).clicked() || submitted {
+                            // Out-of-range pages go to the nearest end
+                            if let Ok(page) = self.page_number_input.trim().parse::<usize>() {
+                                self.go_to_page(page.saturating_sub(1).min(last_page));
+                            }
+                            self.page_number_input.clear();
+                        }
                     }
-                } else if let Some(ref results) = self.search_results {
-                    let mut rows = vec![self.csv_header.clone()];
-                    rows.extend(results.clone());
-                    rows
-                } else {
-                    let start = self.current_page * self.rows_per_page;
-                    let end = ((self.current_page + 1) * self.rows_per_page).min(self.csv_data.len());
-                    let mut rows = vec![self.csv_header.clone()];
-                    rows.extend(self.csv_data[start..end].iter().cloned());
-                    rows
-                }
-            } else {
-                vec![]
-            };
 
-            if !rows_to_display.is_empty() && self.visible_column_count() > 0 {
-                egui::ScrollArea::both().show(ui, |ui| {
-                    let ctx = ui.ctx().clone();
-                    let visible_headers = self.get_visible_headers();
-                    let num_visible_columns = visible_headers.len();
+                    ui.separator();
+                    ui.label(tr("Rows per page:"));
+                    let mut page_size = self.rows_per_page;
+                    egui::ComboBox::from_id_source("page_size")
+                        .selected_text(page_size_label(page_size))
+                        .show_ui(ui, |ui| {
+                            for size in PAGE_SIZES {
+                                // A single page would read the whole file into memory
+                                if size == ALL_ROWS && self.pager.is_some() {
+                                    continue;
+                                }
+                                ui.selectable_value(&mut page_size, size, page_size_label(size));
+                            }
+                        });
+                    if page_size != self.rows_per_page {
+                        self.set_page_size(page_size);
+                    }
+
+                    let mut infinite_scroll = self.infinite_scroll;
+                    let toggle = ui
+                        .add_enabled(self.pager.is_none(), egui::Checkbox::new(&mut infinite_scroll, tr("Infinite scroll")))
+                        .on_hover_text(tr("Load more rows as you scroll instead of paging"))
+                        .on_disabled_hover_text(tr("Files paged from disk are browsed a page at a time"));
+                    if toggle.changed() {
+                        self.infinite_scroll = infinite_scroll;
+                        self.scroll_rows = self.rows_per_page;
+                        self.current_page = 0;
+                        self.view_changed();
+                    }
+                });
+                ui.separator();
+            }
 
-                    if num_visible_columns > 0 {
-                        TableBuilder::new(ui)
+            if let Some(row_num) = self.selected_row {
+                self.show_record_card(ui, row_num);
+            } else if !self.csv_header.is_empty() && self.visible_column_count() > 0 {
+                let scrollable = self.visible_column_count().saturating_sub(self.frozen_columns);
+                if self.frozen_columns > 0 && scrollable > 0 {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Scroll columns:"));
+                        ui.add(egui::Slider::new(&mut self.first_scroll_column, 0..=scrollable - 1))
+                            .on_hover_text(tr("Shift+scroll over the table also moves through the columns"));
+                    });
+                }
+                let visible_columns = self.table_columns(ui.available_width());
+                // File row numbers for the gutter, row 1 being the header
+                let row_numbers: Option<Vec<usize>> = self.show_row_numbers.then(|| {
+                    let offset = self.row_offset() + 2;
+                    self.displayed_indices().into_iter().map(|idx| idx + offset).collect()
+                });
+                let gutter_width = row_numbers.as_ref().map(|numbers| {
+                    let widest = numbers.iter().max().copied().unwrap_or(0).to_string();
+                    fit_column_width(ui.ctx(), std::iter::once(widest.as_str()))
+                });
+                let mut height_cache = std::mem::take(&mut self.row_height_cache);
+                let view_generation = self.view_generation;
+                let rows = self.displayed_rows();
+                let displayed = rows.len();
+                let table_started = Instant::now();
+                let mut current_widths = Vec::new();
+                let mut sort_clicked = None;
+                let mut rows_rendered = 0;
+                let mut last_rendered = 0;
+                let mut clicked_cell = None;
+                let mut clicked_row = None;
+                let mut drag_started_row = None;
+                let mut dragged_over_row = None;
+                let dragging_rows = self.dragging_rows && ui.input(|i| i.pointer.primary_down());
+                let problem_cells = match &self.validation {
+                    Some((generation, report)) if *generation == self.data_generation => Some(&report.cells),
+                    _ => None,
+                };
+                let indices = self.displayed_indices();
+                let selected: Vec<bool> = indices
+                    .iter()
+                    .map(|idx| self.selected_rows.contains(idx))
+                    .collect();
+                let mut opened_cell = None;
+                // Cells are reached with the arrow keys (see handle_grid_keys),
+                // so Tab skips over them instead of visiting every one
+                let grid_sense = egui::Sense {
+                    focusable: false,
+                    ..egui::Sense::click_and_drag()
+                };
+                let mut cell_action = None;
+                let mut header_action = None;
+                let focused_cell = self.focused_cell;
+                let scroll_to_focus = self.scroll_to_focus;
+                // Frozen columns stay put, so the table only scrolls sideways a
+                // column at a time (see table_columns) instead of by pixels.
+                // Vertical scrolling is left to the table body so the header
+                // row stays at the top.
+                let frozen = self.frozen_columns > 0;
+                let density = self.theme.density;
+                let min_row_height = density.min_row_height();
+                let wrap_cells = self.wrap_cells;
+                let column_types = if self.highlight_invalid { self.column_types.clone() } else { Vec::new() };
+                let show_invisible = self.show_invisible;
+                let masked_columns = self.masked_columns.clone();
+                let body_font = egui::TextStyle::Body.resolve(ui.style());
+                let line_height = ui.fonts(|f| f.row_height(&body_font)).max(min_row_height);
+                let table_area = egui::ScrollArea::new([!frozen, false]).show(ui, |ui| {
+                    let ctx = ui.ctx().clone();
+                    // egui keeps resized widths per table id, so a new id is
+                    // what makes it pick up column_widths again
+                    ui.push_id(self.table_generation, |ui| {
+                        let spacing = ui.spacing().item_spacing.y;
+                        ui.spacing_mut().item_spacing.y = density.row_spacing(spacing);
+                        let mut table = TableBuilder::new(ui)
                             .striped(true)
                             .resizable(true)
-                            .cell_layout(egui::Layout::left_to_right(egui::Align::TOP))
-                            .columns(Column::initial(150.0), num_visible_columns)
+                            .auto_shrink([false, false])
+                            .max_scroll_height(f32::INFINITY)
+                            .cell_layout(egui::Layout::left_to_right(egui::Align::TOP));
+                        if let Some(width) = gutter_width {
+                            table = table.column(Column::exact(width).resizable(false));
+                        }
+                        for &col in &visible_columns {
+                            table = table.column(Column::initial(self.column_widths[col]));
+                        }
+                        if let (true, Some((row, _))) = (scroll_to_focus, focused_cell) {
+                            table = table.scroll_to_row(row, None);
+                        }
+                        table
                             .header(25.0, |mut header| {
-                                for header_cell in &visible_headers {
+                                if row_numbers.is_some() {
                                     header.col(|ui| {
-                                        ui.label(egui::RichText::new(header_cell).text());
+                                        ui.weak("#").on_hover_text(tr("Row number in the file"));
                                     });
                                 }
-                            })
-                            .body(|mut body| {
-                                let rows = if rows_to_display.len() > 1 && rows_to_display[0] == self.csv_header {
-                                    &rows_to_display[1..]
-                                } else {
-                                    &rows_to_display[..]
-                                };
-                                for row in rows {
-                                    let visible_row = self.filter_visible_columns(row);
-                                    let row_height = visible_row.iter().fold(20.0f32, |mut max_height, cell| {
-                                        let available_width = 150.0;
-                                        let galley = ctx.fonts(|f| {
-                                            f.layout(
-                                                cell.clone(),
-                                                FontId::new(14.0, FontFamily::Proportional),
-                                                Color32::WHITE,
-                                                available_width,
-                                            )
+                                for &col in &visible_columns {
+                                    header.col(|ui| {
+                                        let arrow = match self.sort {
+                                            Some((sorted, false)) if sorted == col => " ⬆",
+                                            Some((sorted, true)) if sorted == col => " ⬇",
+                                            _ => "",
+                                        };
+                                        let name = &self.csv_header[col];
+                                        let title = self.schema.title(name);
+                                        let label = egui::Label::new(format!("{}{}", title.unwrap_or(name), arrow))
+                                            .sense(egui::Sense::click());
+                                        let hover = match title {
+                                            Some(_) => tr!("{}. Click to sort, right-click for more", name),
+                                            None => tr("Click to sort, right-click for more").to_string(),
+                                        };
+                                        let response = ui.add(label).on_hover_text(hover);
+                                        if response.clicked() {
+                                            sort_clicked = Some(col);
+                                        }
+                                        response.context_menu(|ui| {
+                                            let items = [
+                                                (tr("Sort ascending"), HeaderAction::SortAscending),
+                                                (tr("Sort descending"), HeaderAction::SortDescending),
+                                                (tr("Hide column"), HeaderAction::Hide),
+                                                (tr("Auto-fit width"), HeaderAction::AutoFit),
+                                                (tr("Rename..."), HeaderAction::Rename),
+                                                (tr("Column statistics"), HeaderAction::Statistics),
+                                                (tr("Filter by values..."), HeaderAction::FilterByValues),
+                                                (tr("Export split by column…"), HeaderAction::SplitExport),
+                                                (tr("Normalize values…"), HeaderAction::Normalize),
+                                                (tr("Check against lookup file…"), HeaderAction::CheckReferences),
+                                                (tr("Decimal separators…"), HeaderAction::DecimalSeparators),
+                                                (tr("Convert units…"), HeaderAction::ConvertUnits),
+                                                (tr("Format phone numbers…"), HeaderAction::FormatPhones),
+                                                (tr("Normalize amounts…"), HeaderAction::NormalizeCurrency),
+                                                (tr("Pseudonymize…"), HeaderAction::Pseudonymize),
+                                            ];
+                                            for (label, action) in items {
+                                                if ui.button(label).clicked() {
+                                                    header_action = Some((col, action));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            if self.json_columns.get(col) == Some(&true) {
+                                                let flatten = ui
+                                                    .button(tr("Flatten JSON…"))
+                                                    .on_hover_text(tr("Copy chosen keys of the JSON in this column to columns of their own"));
+                                                if flatten.clicked() {
+                                                    header_action = Some((col, HeaderAction::FlattenJson));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            let column_type = self.column_types.get(col).copied().unwrap_or_default();
+                                            if column_type != ColumnType::Text {
+                                                let filter_invalid = ui
+                                                    .button(tr("Filter to invalid values"))
+                                                    .on_hover_text(tr!("Show the rows where this column isn't blank and isn't a valid {}", column_type.label()));
+                                                if filter_invalid.clicked() {
+                                                    header_action = Some((col, HeaderAction::FilterInvalid));
+                                                    ui.close_menu();
+                                                }
+                                            }
+                                            let mask_label = if self.masked_columns.contains(&col) {
+                                                tr("Unmask")
+                                            } else {
+                                                tr("Mask for export")
+                                            };
+                                            let mask_button = ui
+                                                .button(mask_label)
+                                                .on_hover_text(tr("Hide all but the last few characters of this column here and in everything exported"));
+                                            if mask_button.clicked() {
+                                                header_action = Some((col, HeaderAction::ToggleMask));
+                                                ui.close_menu();
+                                            }
+                                            ui.menu_button(tr("Copy column"), |ui| {
+                                                for format in CopyFormat::ALL {
+                                                    if ui.button(format.label()).clicked() {
+                                                        header_action = Some((col, HeaderAction::CopyColumn(format)));
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
                                         });
-                                        max_height = max_height.max(galley.size().y as f32);
-                                        max_height
                                     });
-                                    body.row(row_height, |mut row_ui| {
-                                        for cell in &visible_row {
-                                            row_ui.col(|ui| {
-                                                ui.add(egui::Label::new(cell).wrap(true));
-                                            });
+                                }
+                            })
+                            .body(|body| {
+                                // Only rows inside the viewport are laid out; the cached
+                                // heights are all the table needs for the rest.
+                                let gutter = usize::from(row_numbers.is_some());
+                                let widths = body.widths()[gutter..].to_vec();
+                                // Unwrapped rows are all one line high, so there is nothing to measure
+                                let heights = wrap_cells.then(|| {
+                                    height_cache.heights(&ctx, view_generation, &rows, &visible_columns, &widths, min_row_height)
+                                });
+                                current_widths = widths;
+                                let add_row = |row_index: usize, mut row_ui: TableRow<'_, '_>| {
+                                    rows_rendered += 1;
+                                    last_rendered = last_rendered.max(row_index);
+                                    let row = rows[row_index];
+                                    let selection_fill = |ui: &mut egui::Ui| {
+                                        if selected[row_index] {
+                                            let fill = ui.visuals().selection.bg_fill.gamma_multiply(0.4);
+                                            ui.painter().rect_filled(ui.max_rect(), 0.0, fill);
                                         }
-                                    });
+                                    };
+                                    if let Some(numbers) = &row_numbers {
+                                        row_ui.col(|ui| {
+                                            selection_fill(ui);
+                                            let number = egui::RichText::new(numbers[row_index].to_string()).weak();
+                                            if ui.add(egui::Label::new(number).sense(grid_sense)).clicked() {
+                                                clicked_row = Some(row_index);
+                                            }
+                                        });
+                                    }
+                                    for &col in &visible_columns {
+                                        row_ui.col(|ui| {
+                                            selection_fill(ui);
+                                            if dragging_rows && ui.rect_contains_pointer(ui.max_rect()) {
+                                                dragged_over_row = Some(row_index);
+                                            }
+                                            if focused_cell == Some((row_index, col)) {
+                                                let fill = ui.visuals().selection.bg_fill;
+                                                ui.painter().rect_filled(ui.max_rect(), 0.0, fill);
+                                                if scroll_to_focus {
+                                                    ui.scroll_to_rect(ui.max_rect(), None);
+                                                }
+                                            }
+                                            let cell = row.get(col).unwrap_or("");
+                                            let invalid_type = column_types
+                                                .get(col)
+                                                .filter(|column_type| !cell.trim().is_empty() && !column_type.accepts(cell));
+                                            let problem = problem_cells.and_then(|cells| cells.get(&(indices[row_index], col)));
+                                            if invalid_type.is_some() || problem.is_some() {
+                                                let fill = ui.visuals().error_fg_color.gamma_multiply(0.2);
+                                                ui.painter().rect_filled(ui.max_rect(), 0.0, fill);
+                                            }
+                                            let hidden = if show_invisible { invisible::kinds(cell) } else { Vec::new() };
+                                            let shown = if masked_columns.contains(&col) {
+                                                Cow::Owned(pii::mask(cell))
+                                            } else if hidden.is_empty() {
+                                                Cow::Borrowed(cell)
+                                            } else {
+                                                let fill = ui.visuals().warn_fg_color.gamma_multiply(0.2);
+                                                ui.painter().rect_filled(ui.max_rect(), 0.0, fill);
+                                                Cow::Owned(invisible::reveal(cell))
+                                            };
+                                            let width = ui.available_width();
+                                            let label = if wrap_cells {
+                                                egui::Label::new(shown.as_ref()).wrap(true)
+                                            } else {
+                                                egui::Label::new(elide(ui, &shown, width)).wrap(false)
+                                            };
+                                            let mut response = ui.add(label.sense(grid_sense));
+                                            if !wrap_cells && response.hovered() && is_truncated(ui, &shown, width) {
+                                                response = response.on_hover_text(tooltip_text(&shown));
+                                            }
+                                            if let Some(column_type) = invalid_type {
+                                                response = response.on_hover_text(tr!("Not a valid {}", column_type.label()));
+                                            }
+                                            if let Some(check) = problem {
+                                                response = response.on_hover_text(check.label());
+                                            }
+                                            if !hidden.is_empty() {
+                                                let kinds: Vec<&str> = hidden.iter().map(|kind| kind.label()).collect();
+                                                response = response.on_hover_text(kinds.join(", "));
+                                            }
+                                            if response.clicked() {
+                                                clicked_cell = Some((row_index, col));
+                                                clicked_row = Some(row_index);
+                                            }
+                                            if response.drag_started() {
+                                                drag_started_row = Some(row_index);
+                                            }
+                                            if response.double_clicked() {
+                                                opened_cell = Some((row_index, col));
+                                            }
+                                            response.context_menu(|ui| {
+                                                let copies = [
+                                                    (tr("Copy cell"), CellAction::CopyCell as fn(_) -> _),
+                                                    (tr("Copy row"), CellAction::CopyRow),
+                                                ];
+                                                for (label, copy) in copies {
+                                                    ui.menu_button(label, |ui| {
+                                                        for format in CopyFormat::ALL {
+                                                            if ui.button(format.label()).clicked() {
+                                                                cell_action = Some((row_index, col, copy(format)));
+                                                                ui.close_menu();
+                                                            }
+                                                        }
+                                                    });
+                                                }
+                                                let items = [
+                                                    (tr("Filter to this value"), CellAction::FilterTo),
+                                                    (tr("Exclude this value"), CellAction::Exclude),
+                                                    (tr("Go to row detail"), CellAction::RowDetail),
+                                                ];
+                                                for (label, action) in items {
+                                                    if ui.button(label).clicked() {
+                                                        cell_action = Some((row_index, col, action));
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        });
+                                    }
+                                };
+                                match heights {
+                                    Some(heights) => body.heterogeneous_rows(heights.iter().copied(), add_row),
+                                    None => body.rows(line_height, rows.len(), add_row),
                                 }
                             });
-                    }
+                        });
                 });
-            } else if !rows_to_display.is_empty() && self.visible_column_count() == 0 {
-                ui.label("No columns are visible. Use the column controls to show columns.");
+                self.row_height_cache = height_cache;
+                self.perf.table_time = table_started.elapsed();
+                self.perf.rows_rendered = rows_rendered;
+                for (&col, width) in visible_columns.iter().zip(current_widths) {
+                    self.column_widths[col] = width;
+                }
+                self.scroll_to_focus = false;
+                if frozen {
+                    self.scroll_columns_with_wheel(ui.ctx(), table_area.inner_rect);
+                }
+                if clicked_cell.is_some() {
+                    self.focused_cell = clicked_cell;
+                }
+                if let Some(row) = clicked_row {
+                    let modifiers = ui.input(|i| i.modifiers);
+                    self.click_row(row, modifiers);
+                }
+                // Dragging down or up the table selects the rows passed over
+                if let Some(row) = drag_started_row {
+                    self.click_row(row, egui::Modifiers::NONE);
+                    self.dragging_rows = true;
+                } else if !dragging_rows {
+                    self.dragging_rows = false;
+                }
+                if let Some(row) = dragged_over_row {
+                    self.select_range_to(row);
+                }
+                // Long values can't be read in the grid, so a click opens them
+                if let (None, Some((row, col))) = (opened_cell, clicked_cell) {
+                    let long = self
+                        .displayed_index(row)
+                        .and_then(|idx| self.csv_data.row(idx).get(col))
+                        .is_some_and(is_long_cell);
+                    if long {
+                        opened_cell = clicked_cell;
+                    }
+                }
+                if let Some((row, col)) = opened_cell {
+                    self.open_cell_viewer(row, col);
+                }
+                if let Some((row, col, action)) = cell_action {
+                    self.run_cell_action(ui.ctx(), row, col, action);
+                }
+                if let Some((col, action)) = header_action {
+                    self.run_header_action(ui.ctx(), col, action);
+                }
+                if let Some(col) = sort_clicked {
+                    self.toggle_sort(col);
+                }
+
+                let scrolling = self.infinite_scroll && self.search_results.is_none() && self.selected_row.is_none();
+                if scrolling
+                    && displayed < self.csv_data.len()
+                    && last_rendered + INFINITE_SCROLL_MARGIN >= displayed
+                {
+                    self.scroll_rows = self.scroll_rows.saturating_add(self.rows_per_page);
+                    ui.ctx().request_repaint();
+                }
+            } else if !self.csv_header.is_empty() {
+                ui.label(tr("No columns are visible. Use the column controls to show columns."));
             }
         });
+        self.perf.frame_time = frame_started.elapsed();
     }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let launch = match LaunchArgs::parse(std::env::args().skip(1)) {
+        Ok(launch) => launch,
+        Err(err) => {
+            eprintln!("{}\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if launch.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
     let mut options = eframe::NativeOptions::default();
     options.maximized = true;
+    let config = Config::load();
+    i18n::set_language(Language::from_config(&config));
     eframe::run_native(
         "CSV Reader",
         options,
-        Box::new(|_cc| Box::new(MyApp {
-            rows_per_page: 100,
-            show_column_controls: false,
-            ..Default::default()
-        })),
+        Box::new(|_cc| {
+            let mut app = MyApp {
+                shortcuts: Shortcuts::from_config(&config),
+                theme: Theme::from_config(&config),
+                zoom: config.get("zoom").and_then(|zoom| zoom.parse().ok()).unwrap_or(1.0),
+                wrap_cells: config.get("wrap_cells") != Some("false"),
+                highlight_invalid: config.get("highlight_invalid") != Some("false"),
+                show_invisible: config.get("show_invisible") != Some("false"),
+                earliest_date: config.get("dates.earliest").unwrap_or("1900-01-01").to_string(),
+                check_headers: config.get("schema.check_headers") != Some("false"),
+                export_settings: ExportSettings::from_config(&config),
+                schema: Schema::from_config(&config),
+                layout: PanelLayout::from_config(&config),
+                selected_rows: HashSet::new(),
+                selection_anchor: None,
+                dragging_rows: false,
+                config,
+                rows_per_page: 100,
+                preview_limit: 1000,
+                show_row_numbers: true,
+                ..Default::default()
+            };
+            app.launch(launch);
+            Box::new(app)
+        }),
     )?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+// Disk-backed paging for files too large to load into memory.
+//
+// Opening a file builds a row index in one streaming pass. Pages are then read
+// on demand by seeking to their first row, and only the most recently viewed
+// few are kept in memory.
+
+use crate::jobs::Progress;
+use crate::row_index::RowIndex;
+use crate::table::Table;
+use std::error::Error;
+
+// Number of pages kept in memory besides the one on screen
+const CACHED_PAGES: usize = 4;
+
+pub struct DiskPager {
+    header: Vec<String>,
+    index: RowIndex,
+    page_size: usize,
+    cache: Vec<(usize, Table)>, // Least recently used first
+}
+
+impl DiskPager {
+    pub fn open(path: &str, page_size: usize, progress: &Progress) -> Result<Self, Box<dyn Error>> {
+        let (header, index) = RowIndex::build(path, progress)?;
+        Ok(DiskPager {
+            header,
+            index,
+            page_size,
+            cache: Vec::new(),
+        })
+    }
+
+    pub fn header(&self) -> &[String] {
+        &self.header
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    // Cached pages were cut at the old size, so they are dropped
+    pub fn set_page_size(&mut self, page_size: usize) {
+        self.page_size = page_size;
+        self.cache.clear();
+    }
+
+    pub fn total_rows(&self) -> usize {
+        self.index.row_count()
+    }
+
+    pub fn total_pages(&self) -> usize {
+        self.total_rows().div_ceil(self.page_size).max(1)
+    }
+
+    // Rows of one page, from the cache or read from disk
+    pub fn page(&mut self, page: usize) -> Result<Table, Box<dyn Error>> {
+        if let Some(pos) = self.cache.iter().position(|(cached, _)| *cached == page) {
+            let entry = self.cache.remove(pos);
+            let table = entry.1.clone();
+            self.cache.push(entry);
+            return Ok(table);
+        }
+
+        let table = self.index.read_rows(page * self.page_size, self.page_size)?;
+        if self.cache.len() > CACHED_PAGES {
+            self.cache.remove(0);
+        }
+        self.cache.push((page, table.clone()));
+        Ok(table)
+    }
+}
@@ -0,0 +1,150 @@
+// Command palette.
+//
+// Lists everything the app can do, including per-column commands such as
+// sorting or hiding a column, and narrows the list with a fuzzy match on what
+// has been typed: the query's characters must appear in order, and matches at
+// word starts or in runs rank higher.
+
+use crate::a11y;
+use crate::i18n::tr;
+use crate::shortcuts::Action;
+use eframe::egui::{self, Key, Modifiers};
+use std::cmp::Reverse;
+
+pub enum Command {
+    Run(Action),
+    Sort(usize, bool), // Column and whether descending
+    ClearSort,
+    ToggleColumn(usize),
+    ShowAllColumns,
+    HideAllColumns,
+    HideBlankColumns,
+    RemoveEmpty,
+    DecimalSeparators,
+    CheckSpelling,
+    AutoFitWidths,
+    EditShortcuts,
+}
+
+pub struct Entry {
+    pub label: String,
+    pub hint: Option<String>, // Shown dimmed after the label, e.g. the shortcut
+    pub command: Command,
+}
+
+pub enum Outcome {
+    Open,
+    Closed,
+    Chosen(Command),
+}
+
+#[derive(Default)]
+pub struct Palette {
+    query: String,
+    selected: usize, // Position in the filtered list
+}
+
+impl Palette {
+    // Up/Down move the selection, Enter or a click runs it, Escape closes
+    pub fn show(&mut self, ctx: &egui::Context, entries: Vec<Entry>) -> Outcome {
+        let mut matches: Vec<(i64, Entry)> = entries
+            .into_iter()
+            .filter_map(|entry| fuzzy_score(&self.query, &entry.label).map(|score| (score, entry)))
+            .collect();
+        // Stable, so equal scores keep the order they were listed in
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+
+        let (escape, up, down, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::Escape),
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+            )
+        });
+        if escape {
+            return Outcome::Closed;
+        }
+        if down && self.selected + 1 < matches.len() {
+            self.selected += 1;
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+        let mut chosen = if enter && !matches.is_empty() {
+            Some(self.selected)
+        } else {
+            None
+        };
+
+        egui::Window::new(tr("Command Palette"))
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                let query_field = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text(tr("Type a command"))
+                        .desired_width(f32::INFINITY),
+                );
+                a11y::set_name(&query_field, tr("Command palette"));
+                query_field.request_focus();
+                if query_field.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    if matches.is_empty() {
+                        ui.weak(tr("No matching commands"));
+                    }
+                    for (idx, (_, entry)) in matches.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let row = ui.selectable_label(idx == self.selected, &entry.label);
+                            if let Some(hint) = &entry.hint {
+                                ui.weak(hint);
+                            }
+                            if idx == self.selected && (up || down) {
+                                row.scroll_to_me(None);
+                            }
+                            if row.clicked() {
+                                chosen = Some(idx);
+                            }
+                        });
+                    }
+                });
+            });
+
+        match chosen {
+            Some(idx) => Outcome::Chosen(matches.swap_remove(idx).1.command),
+            None => Outcome::Open,
+        }
+    }
+}
+
+// None when the query's characters don't all appear in order in `text`.
+// Whitespace in the query is ignored, so "sort age" finds "Sort by age".
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + text[next..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|prev| prev + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if previous.is_none() {
+            // Prefer matches that start early
+            score -= found as i64 / 4;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
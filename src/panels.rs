@@ -0,0 +1,134 @@
+// Side panels around the table.
+//
+// Each panel can be docked on the left or right, resized by dragging its
+// edge and closed. Panels on the same side sit next to each other in the
+// order listed in `Panel::ALL`. Saved in the config as
+// `panel.<name>=left|right|closed`.
+
+use crate::config::Config;
+use crate::i18n::tr;
+use eframe::egui::panel::Side;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    Search,
+    Columns,
+    Details,
+    Validation,
+    Quality,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 5] = [Panel::Search, Panel::Columns, Panel::Details, Panel::Validation, Panel::Quality];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Panel::Search => "Search",
+            Panel::Columns => "Columns",
+            Panel::Details => "Details",
+            Panel::Validation => "Validation",
+            Panel::Quality => "Quality",
+        })
+    }
+
+    fn config_key(self) -> &'static str {
+        match self {
+            Panel::Search => "panel.search",
+            Panel::Columns => "panel.columns",
+            Panel::Details => "panel.details",
+            Panel::Validation => "panel.validation",
+            Panel::Quality => "panel.quality",
+        }
+    }
+
+    fn default_dock(self) -> Dock {
+        match self {
+            Panel::Search => Dock {
+                side: Side::Left,
+                open: true,
+            },
+            Panel::Columns => Dock {
+                side: Side::Left,
+                open: false,
+            },
+            Panel::Details | Panel::Validation | Panel::Quality => Dock {
+                side: Side::Right,
+                open: false,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Dock {
+    side: Side,
+    open: bool,
+}
+
+pub struct PanelLayout {
+    docks: [Dock; 5], // In the order of Panel::ALL
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        PanelLayout {
+            docks: Panel::ALL.map(Panel::default_dock),
+        }
+    }
+}
+
+impl PanelLayout {
+    pub fn from_config(config: &Config) -> Self {
+        let mut layout = PanelLayout::default();
+        for panel in Panel::ALL {
+            let dock = layout.dock_mut(panel);
+            match config.get(panel.config_key()) {
+                Some("left") => *dock = Dock { side: Side::Left, open: true },
+                Some("right") => *dock = Dock { side: Side::Right, open: true },
+                Some("closed") => dock.open = false,
+                _ => {}
+            }
+        }
+        layout
+    }
+
+    pub fn save_to(&self, config: &mut Config) {
+        for panel in Panel::ALL {
+            let dock = self.dock(panel);
+            let value = match (dock.open, dock.side) {
+                (false, _) => "closed",
+                (true, Side::Left) => "left",
+                (true, Side::Right) => "right",
+            };
+            config.set(panel.config_key(), value);
+        }
+    }
+
+    pub fn is_open(&self, panel: Panel) -> bool {
+        self.dock(panel).open
+    }
+
+    pub fn set_open(&mut self, panel: Panel, open: bool) {
+        self.dock_mut(panel).open = open;
+    }
+
+    pub fn side(&self, panel: Panel) -> Side {
+        self.dock(panel).side
+    }
+
+    pub fn move_to_other_side(&mut self, panel: Panel) {
+        let dock = self.dock_mut(panel);
+        dock.side = match dock.side {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        };
+    }
+
+    fn dock(&self, panel: Panel) -> &Dock {
+        &self.docks[panel as usize]
+    }
+
+    fn dock_mut(&mut self, panel: Panel) -> &mut Dock {
+        &mut self.docks[panel as usize]
+    }
+}
@@ -0,0 +1,468 @@
+// Regular expressions for schema patterns.
+//
+// A small matcher covering what column patterns use in practice:
+// literals, `.`, classes like `[A-Z0-9_-]` and `[^,]`, `\d \w \s` and their
+// negations, groups with `|` (`(?:...)` is accepted too), the quantifiers
+// `* + ? {n} {n,} {n,m}` and the anchors `^ $`. A pattern must match the whole
+// cell, as in JSON Table Schema, so anchors are optional. Backreferences and
+// lookaround aren't supported and are rejected when the pattern is parsed.
+//
+// Common patterns are offered by name in the schema editor, alongside the
+// ones a schema names itself.
+
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    program: Vec<Inst>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Start,
+    End,
+    Group(Vec<Vec<Node>>), // Alternatives
+    Repeat { node: Box<Node>, min: usize, max: Option<usize> },
+}
+
+// Compiled form, run by simulating every path at once so that patterns like
+// `(a*)*b` can't take exponential time
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(Node), // Char, Any or Class
+    Start,
+    End,
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+// Named patterns built in, offered for any column
+pub const LIBRARY: &[(&str, &str)] = &[
+    ("UUID", r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}"),
+    ("IPv4 address", r"((25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)"),
+    ("ISO date", r"\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])"),
+    (
+        "ISO date and time",
+        r"\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])[T ]([01]\d|2[0-3]):[0-5]\d(:[0-5]\d(\.\d+)?)?(Z|[+-]([01]\d|2[0-3]):?[0-5]\d)?",
+    ),
+    ("Time (24-hour)", r"([01]\d|2[0-3]):[0-5]\d(:[0-5]\d)?"),
+    ("Email address", r"[^@\s]+@[^@\s]+\.[^@\s]+"),
+    ("Web address", r"https?://[^\s]+"),
+    ("US ZIP code", r"\d{5}(-\d{4})?"),
+    ("Hex color", r"#([0-9a-fA-F]{3}|[0-9a-fA-F]{6})"),
+];
+
+// Counted repeats are compiled by copying, so `{n}` is limited
+const MAX_PROGRAM: usize = 10_000;
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+const SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let alternatives = parser.alternatives()?;
+        if parser.pos < parser.chars.len() {
+            return Err(format!("unmatched ) at position {}", parser.pos + 1));
+        }
+        let mut program = Vec::new();
+        compile(&Node::Group(alternatives), &mut program)?;
+        program.push(Inst::Match);
+        Ok(Pattern { program })
+    }
+
+    // True if the pattern matches all of `text`
+    pub fn is_match(&self, text: &str) -> bool {
+        let len = text.chars().count();
+        let mut current = Vec::new();
+        let mut next = Vec::new();
+        let mut seen = vec![usize::MAX; self.program.len()]; // Step each instruction was last added in
+        self.add_thread(&mut current, &mut seen, 0, 0, len);
+        for (pos, ch) in text.chars().enumerate() {
+            for &pc in &current {
+                if let Inst::Char(node) = &self.program[pc]
+                    && matches_char(node, ch)
+                {
+                    self.add_thread(&mut next, &mut seen, pc + 1, pos + 1, len);
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+            if current.is_empty() {
+                return false;
+            }
+        }
+        current.iter().any(|&pc| matches!(self.program[pc], Inst::Match))
+    }
+
+    // Follows jumps, splits and anchors from `pc`, keeping the instructions
+    // that wait for a character or match
+    fn add_thread(&self, threads: &mut Vec<usize>, seen: &mut [usize], pc: usize, pos: usize, len: usize) {
+        if seen[pc] == pos {
+            return;
+        }
+        seen[pc] = pos;
+        match self.program[pc] {
+            Inst::Jump(to) => self.add_thread(threads, seen, to, pos, len),
+            Inst::Split(first, second) => {
+                self.add_thread(threads, seen, first, pos, len);
+                self.add_thread(threads, seen, second, pos, len);
+            }
+            Inst::Start => {
+                if pos == 0 {
+                    self.add_thread(threads, seen, pc + 1, pos, len);
+                }
+            }
+            Inst::End => {
+                if pos == len {
+                    self.add_thread(threads, seen, pc + 1, pos, len);
+                }
+            }
+            Inst::Char(_) | Inst::Match => threads.push(pc),
+        }
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Result<(), String> {
+    if program.len() > MAX_PROGRAM {
+        return Err("pattern is too long once its repeats are expanded".to_string());
+    }
+    match node {
+        Node::Char(_) | Node::Any | Node::Class { .. } => program.push(Inst::Char(node.clone())),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Group(alternatives) => {
+            let mut jumps = Vec::new();
+            for (i, alternative) in alternatives.iter().enumerate() {
+                let split = program.len();
+                let last = i + 1 == alternatives.len();
+                if !last {
+                    program.push(Inst::Split(split + 1, 0));
+                }
+                for node in alternative {
+                    compile(node, program)?;
+                }
+                if !last {
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(node, program)?;
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn matches_char(node: &Node, ch: char) -> bool {
+    match node {
+        Node::Char(expected) => ch == *expected,
+        Node::Any => ch != '\n',
+        Node::Class { ranges, negated } => ranges.iter().any(|&(low, high)| (low..=high).contains(&ch)) != *negated,
+        _ => false,
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek();
+        self.pos += 1;
+        ch
+    }
+
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        while let Some(ch) = self.peek() {
+            if ch == '|' || ch == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(nodes)
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let start = self.pos;
+        match self.next() {
+            Some('.') => Ok(Node::Any),
+            Some('^') => Ok(Node::Start),
+            Some('$') => Ok(Node::End),
+            Some('(') => {
+                if self.peek() == Some('?') {
+                    if self.chars.get(self.pos + 1) != Some(&':') {
+                        return Err(format!("unsupported group (? at position {}", start + 1));
+                    }
+                    self.pos += 2;
+                }
+                let alternatives = self.alternatives()?;
+                if self.next() != Some(')') {
+                    return Err(format!("unclosed ( at position {}", start + 1));
+                }
+                Ok(Node::Group(alternatives))
+            }
+            Some('[') => self.class(start),
+            Some('\\') => self.escape(),
+            Some(ch @ ('*' | '+' | '?')) => Err(format!("nothing to repeat before {} at position {}", ch, start + 1)),
+            Some(ch) => Ok(Node::Char(ch)),
+            None => Err("pattern ends unexpectedly".to_string()),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, String> {
+        let class = |ranges: &[(char, char)], negated| Node::Class {
+            ranges: ranges.to_vec(),
+            negated,
+        };
+        Ok(match self.next() {
+            Some('d') => class(DIGIT, false),
+            Some('D') => class(DIGIT, true),
+            Some('w') => class(WORD, false),
+            Some('W') => class(WORD, true),
+            Some('s') => class(SPACE, false),
+            Some('S') => class(SPACE, true),
+            Some(ch) => Node::Char(escaped_char(ch)?),
+            None => return Err("pattern ends with \\".to_string()),
+        })
+    }
+
+    fn class(&mut self, start: usize) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        loop {
+            let low = match self.next() {
+                None => return Err(format!("unclosed [ at position {}", start + 1)),
+                // A ']' first in the class is a literal
+                Some(']') if !ranges.is_empty() => break,
+                Some('\\') => match self.next() {
+                    Some('d') => {
+                        ranges.extend_from_slice(DIGIT);
+                        continue;
+                    }
+                    Some('w') => {
+                        ranges.extend_from_slice(WORD);
+                        continue;
+                    }
+                    Some('s') => {
+                        ranges.extend_from_slice(SPACE);
+                        continue;
+                    }
+                    Some(ch) => escaped_char(ch)?,
+                    None => return Err("pattern ends with \\".to_string()),
+                },
+                Some(ch) => ch,
+            };
+            // A '-' at either end of the class is a literal
+            let high = if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&ch| ch != ']') {
+                self.pos += 1;
+                match self.next() {
+                    Some('\\') => escaped_char(self.next().unwrap_or('\\'))?,
+                    Some(ch) => ch,
+                    None => return Err(format!("unclosed [ at position {}", start + 1)),
+                }
+            } else {
+                low
+            };
+            if high < low {
+                return Err(format!("range {}-{} is backwards", low, high));
+            }
+            ranges.push((low, high));
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('{') => match self.counts() {
+                Some(counts) => counts,
+                None => return Ok(atom), // Not a quantifier, so '{' is read as a literal next
+            },
+            Some(ch @ ('*' | '+' | '?')) => {
+                self.pos += 1;
+                match ch {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End) {
+            return Err("anchors can't be repeated".to_string());
+        }
+        if min.max(max.unwrap_or(0)) > MAX_PROGRAM {
+            return Err("pattern is too long once its repeats are expanded".to_string());
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("{{{},{}}} has its counts backwards", min, max.unwrap_or(0)));
+        }
+        // Lazy and greedy repeats accept the same cells
+        if self.peek() == Some('?') {
+            self.pos += 1;
+        }
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+        })
+    }
+
+    // `{n}`, `{n,}` or `{n,m}` at the current position, consumed if valid
+    fn counts(&mut self) -> Option<(usize, Option<usize>)> {
+        let close = self.chars[self.pos..].iter().position(|&ch| ch == '}')? + self.pos;
+        let inside: String = self.chars[self.pos + 1..close].iter().collect();
+        let counts = match inside.split_once(',') {
+            None => {
+                let count = inside.parse().ok()?;
+                (count, Some(count))
+            }
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+        };
+        self.pos = close + 1;
+        Some(counts)
+    }
+}
+
+fn escaped_char(ch: char) -> Result<char, String> {
+    match ch {
+        'n' => Ok('\n'),
+        'r' => Ok('\r'),
+        't' => Ok('\t'),
+        ch if ch.is_ascii_alphanumeric() => Err(format!("unsupported escape \\{}", ch)),
+        ch => Ok(ch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn alternation() {
+        assert!(matches("cat|dog", "dog"));
+        assert!(!matches("cat|dog", "catdog"));
+        assert!(matches("(?:ab|cd)+", "abcdab"));
+        assert!(matches("a(|b)c", "ac"));
+        assert!(!matches("a(b|c)d", "ad"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("[A-Z0-9_-]+", "AB_9-"));
+        assert!(!matches("[A-Z]", "a"));
+        assert!(matches("[^,]+", "a b"));
+        assert!(!matches("[^,]+", "a,b"));
+        assert!(matches("[]a]+", "]a]"));
+        assert!(matches(r"[\d.]+", "1.5"));
+        assert!(matches(r"\w\s\W\D\S", "a !x?"));
+        assert!(matches("[A-CEGHJ-PR-TW-Z]", "W"));
+        assert!(!matches("[A-CEGHJ-PR-TW-Z]", "Q"));
+    }
+
+    #[test]
+    fn anchors() {
+        assert!(matches("^abc$", "abc"));
+        assert!(matches("abc", "abc"));
+        assert!(!matches("abc", "abcd"));
+        assert!(!matches("a^b", "ab"));
+    }
+
+    #[test]
+    fn repetition_bounds() {
+        assert!(matches(r"\d{3}", "123"));
+        assert!(!matches(r"\d{3}", "12"));
+        assert!(!matches(r"\d{3}", "1234"));
+        assert!(matches(r"\d{2,}", "12345"));
+        assert!(matches(r"\d{2,3}", "123"));
+        assert!(!matches(r"\d{2,3}", "1234"));
+        assert!(matches("a{0}b", "b"));
+        assert!(matches("a{,2}", "a{,2}")); // Not a count, so literal
+        assert!(matches("x?y*z+", "zz"));
+        assert!(matches("(a*)*b", &format!("{}b", "a".repeat(5000))));
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert!(matches("caf.", "café"));
+        assert!(matches("[à-ÿ]+", "éèü"));
+        assert!(matches("日本.{2}", "日本語だ"));
+        assert!(!matches(r"\w+", "é"));
+    }
+
+    #[test]
+    fn errors() {
+        for pattern in ["(a", "a)", "[a", "*a", "a{3,1}", "^*", r"\1", "(?=a)", "[z-a]", "a\\", "a{20000}", "(){999999999999}", "(){0,999999999999}"] {
+            assert!(Pattern::new(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn library_parses() {
+        for (name, pattern) in LIBRARY {
+            assert!(Pattern::new(pattern).is_ok(), "{}", name);
+        }
+        let uuid = Pattern::new(LIBRARY[0].1).unwrap();
+        assert!(uuid.is_match("123e4567-e89b-12d3-a456-426614174000"));
+    }
+}
@@ -0,0 +1,215 @@
+// PDF export of the current view, for printing.
+//
+// Written by hand rather than through a PDF library: every page is plain
+// text in Courier, one of the fonts every PDF reader has built in, so nothing
+// is embedded and monospacing lines the columns up without measuring glyphs.
+// Each page repeats the header row and is numbered "Page n of m". Characters
+// outside Windows-1252 print as '?'.
+
+use crate::encoding::windows_1252_byte;
+use crate::jobs::Progress;
+use crate::table::Table;
+use crate::{SAVE_BUFFER_BYTES, Selection};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+const PAGE_SHORT: f32 = 595.0; // A4, in points
+const PAGE_LONG: f32 = 842.0;
+const MARGIN: f32 = 36.0;
+const FONT_SIZE: f32 = 8.0;
+const CHAR_WIDTH: f32 = FONT_SIZE * 0.6; // Courier glyphs are all 600/1000 em wide
+const LINE_HEIGHT: f32 = 11.0;
+const COLUMN_GAP: usize = 2;
+const MIN_COLUMN_CHARS: usize = 4;
+const MAX_COLUMN_CHARS: usize = 40;
+const SAMPLE_ROWS: usize = 1000; // Rows looked at to size the columns
+
+// Objects 1-4 are fixed; each page then adds its content stream and itself
+const CATALOG: usize = 1;
+const PAGES: usize = 2;
+const FONT: usize = 3;
+const BOLD_FONT: usize = 4;
+
+pub fn write_pdf(
+    path: &str,
+    landscape: bool,
+    header: &[String],
+    data: &Table,
+    selection: &Selection,
+    progress: &Progress,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let row_count = selection.row_count(data);
+    progress.set_total(row_count);
+    let (page_width, page_height) = if landscape { (PAGE_LONG, PAGE_SHORT) } else { (PAGE_SHORT, PAGE_LONG) };
+    let columns: Vec<usize> = match &selection.columns {
+        Some(columns) => columns.clone(),
+        None => (0..header.len()).collect(),
+    };
+    let line_chars = ((page_width - 2.0 * MARGIN) / CHAR_WIDTH) as usize;
+    let widths = column_widths(header, data, selection, &columns, line_chars);
+    // The header, the rule under it and the page number take three lines
+    let rows_per_page = (((page_height - 2.0 * MARGIN) / LINE_HEIGHT) as usize).saturating_sub(3).max(1);
+    let page_count = row_count.div_ceil(rows_per_page).max(1);
+
+    let mut pdf = PdfWriter::new(BufWriter::with_capacity(SAVE_BUFFER_BYTES, File::create(path)?))?;
+    pdf.object(FONT, b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>")?;
+    pdf.object(BOLD_FONT, b"<< /Type /Font /Subtype /Type1 /BaseFont /Courier-Bold /Encoding /WinAnsiEncoding >>")?;
+    let header_line = format_line(columns.iter().map(|&col| header[col].as_str()), &widths, line_chars);
+    let mut rows = selection.rows(data);
+    let mut rows_done = 0;
+    let mut page_ids = Vec::with_capacity(page_count);
+    for page in 0..page_count {
+        let mut content = Vec::new();
+        let mut y = page_height - MARGIN - FONT_SIZE;
+        push_text(&mut content, "F2", MARGIN, y, &header_line);
+        let rule_y = y - 3.0;
+        let rule_end = MARGIN + header_line.chars().count() as f32 * CHAR_WIDTH;
+        content.extend_from_slice(format!("0.5 w {} {} m {} {} l S\n", MARGIN, rule_y, rule_end, rule_y).as_bytes());
+        y -= LINE_HEIGHT * 1.5;
+        for row in rows.by_ref().take(rows_per_page) {
+            let line = format_line(columns.iter().map(|&col| row.get(col).unwrap_or("")), &widths, line_chars);
+            push_text(&mut content, "F1", MARGIN, y, &line);
+            y -= LINE_HEIGHT;
+            rows_done += 1;
+        }
+        let footer = tr!("Page {} of {}", page + 1, page_count);
+        let footer_x = (page_width - footer.chars().count() as f32 * CHAR_WIDTH) / 2.0;
+        push_text(&mut content, "F1", footer_x, MARGIN / 2.0, &footer);
+
+        let content_id = pdf.next_id();
+        let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(&content);
+        stream.extend_from_slice(b"\nendstream");
+        pdf.object(content_id, &stream)?;
+        let page_id = pdf.next_id();
+        let page_object = format!(
+            "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R \
+             /Resources << /Font << /F1 {} 0 R /F2 {} 0 R >> >> >>",
+            PAGES, page_width, page_height, content_id, FONT, BOLD_FONT
+        );
+        pdf.object(page_id, page_object.as_bytes())?;
+        page_ids.push(page_id);
+
+        progress.set_done(rows_done);
+        if progress.is_cancelled() {
+            return Ok(false);
+        }
+    }
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    let pages = format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), page_ids.len());
+    pdf.object(PAGES, pages.as_bytes())?;
+    pdf.object(CATALOG, format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES).as_bytes())?;
+    let file = pdf.finish()?.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
+    progress.set_done(row_count);
+    Ok(true)
+}
+
+// Each column gets the width of its longest value in the first SAMPLE_ROWS
+// rows, within MIN and MAX_COLUMN_CHARS. If that is wider than the page, the
+// columns are narrowed in proportion; if even the narrowest don't fit, the
+// rightmost columns run off the page.
+fn column_widths(header: &[String], data: &Table, selection: &Selection, columns: &[usize], line_chars: usize) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns.iter().map(|&col| header[col].chars().count()).collect();
+    for row in selection.rows(data).take(SAMPLE_ROWS) {
+        for (width, &col) in widths.iter_mut().zip(columns) {
+            *width = (*width).max(row.get(col).unwrap_or("").chars().count());
+        }
+    }
+    for width in &mut widths {
+        *width = (*width).clamp(MIN_COLUMN_CHARS, MAX_COLUMN_CHARS);
+    }
+    let total: usize = widths.iter().map(|width| width + COLUMN_GAP).sum();
+    if total > line_chars {
+        for width in &mut widths {
+            *width = (*width * line_chars / total).max(MIN_COLUMN_CHARS);
+        }
+    }
+    widths
+}
+
+// Cells padded or cut to their column's width, one line per row
+fn format_line<'c>(cells: impl Iterator<Item = &'c str>, widths: &[usize], line_chars: usize) -> String {
+    let mut line = String::new();
+    for (cell, &width) in cells.zip(widths) {
+        let mut chars = cell.chars().map(|ch| if ch.is_control() { ' ' } else { ch });
+        let count = cell.chars().count();
+        if count > width {
+            line.extend(chars.by_ref().take(width - 1));
+            line.push('…');
+        } else {
+            line.extend(chars);
+            line.extend(std::iter::repeat_n(' ', width - count));
+        }
+        line.extend(std::iter::repeat_n(' ', COLUMN_GAP));
+    }
+    line.chars().take(line_chars).collect::<String>().trim_end().to_string()
+}
+
+fn push_text(content: &mut Vec<u8>, font: &str, x: f32, y: f32, text: &str) {
+    content.extend_from_slice(format!("BT /{} {} Tf {} {} Td (", font, FONT_SIZE, x, y).as_bytes());
+    for ch in text.chars() {
+        let byte = windows_1252_byte(ch).unwrap_or(b'?');
+        if matches!(byte, b'(' | b')' | b'\\') {
+            content.push(b'\\');
+        }
+        content.push(byte);
+    }
+    content.extend_from_slice(b") Tj ET\n");
+}
+
+// Tracks where each object starts for the cross-reference table at the end
+struct PdfWriter<W: Write> {
+    out: W,
+    position: usize,
+    offsets: Vec<usize>, // By object number; 0 is unused
+}
+
+impl<W: Write> PdfWriter<W> {
+    fn new(out: W) -> io::Result<Self> {
+        let mut pdf = PdfWriter {
+            out,
+            position: 0,
+            offsets: vec![0; BOLD_FONT + 1],
+        };
+        // The binary comment tells transfer tools the file isn't plain text
+        pdf.write(b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n")?;
+        Ok(pdf)
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len() - 1
+    }
+
+    fn object(&mut self, id: usize, body: &[u8]) -> io::Result<()> {
+        self.offsets[id] = self.position;
+        self.write(format!("{} 0 obj\n", id).as_bytes())?;
+        self.write(body)?;
+        self.write(b"\nendobj\n")
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.out.write_all(bytes)?;
+        self.position += bytes.len();
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        let xref = self.position;
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len());
+        for offset in &self.offsets[1..] {
+            table.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        table.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len(),
+            CATALOG,
+            xref
+        ));
+        self.write(table.as_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
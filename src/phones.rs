@@ -0,0 +1,288 @@
+// Phone number parsing and formatting as E.164 (+14155552671) or in a
+// region's national format ((415) 555-2671).
+//
+// Numbers starting with + or 00 (011 from North America) are international
+// and take their region from the country calling code; any other number is
+// read as a number of the default region, with its trunk prefix (the leading
+// 0 of many countries) dropped. A number is accepted when its length fits
+// its region. Numbers of calling codes not in the table below are accepted at
+// 8 to 15 digits and always written as E.164. Separators ( ) - . / and spaces
+// are ignored; letters and extensions make a number unparseable.
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    E164,
+    National,
+}
+
+impl Format {
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Format::E164 => "International (E.164)",
+            Format::National => "National",
+        })
+    }
+
+    // For the change log
+    pub fn description(self) -> &'static str {
+        match self {
+            Format::E164 => "phone numbers to E.164",
+            Format::National => "phone numbers to national format",
+        }
+    }
+}
+
+pub struct Region {
+    pub code: &'static str, // ISO 3166 code, as saved in the config
+    pub name: &'static str,
+    calling: &'static str,
+    trunk: &'static str,                // Dialled before national numbers within the country
+    lengths: (usize, usize),            // Of the national number without the trunk prefix
+    templates: &'static [(&'static str, &'static str)], // Leading digits and national format, with # for each digit
+}
+
+pub const REGIONS: &[Region] = &[
+    Region {
+        code: "US",
+        name: "United States",
+        calling: "1",
+        trunk: "1",
+        lengths: (10, 10),
+        templates: &[("", "(###) ###-####")],
+    },
+    Region {
+        code: "CA",
+        name: "Canada",
+        calling: "1",
+        trunk: "1",
+        lengths: (10, 10),
+        templates: &[("", "(###) ###-####")],
+    },
+    Region {
+        code: "GB",
+        name: "United Kingdom",
+        calling: "44",
+        trunk: "0",
+        lengths: (9, 10),
+        templates: &[("2", "0## #### ####"), ("", "0#### ######"), ("", "0#### #####")],
+    },
+    Region {
+        code: "IE",
+        name: "Ireland",
+        calling: "353",
+        trunk: "0",
+        lengths: (7, 9),
+        templates: &[("", "0## ### ####"), ("", "0# ### ####")],
+    },
+    Region {
+        code: "ES",
+        name: "Spain",
+        calling: "34",
+        trunk: "",
+        lengths: (9, 9),
+        templates: &[("", "### ## ## ##")],
+    },
+    Region {
+        code: "FR",
+        name: "France",
+        calling: "33",
+        trunk: "0",
+        lengths: (9, 9),
+        templates: &[("", "0# ## ## ## ##")],
+    },
+    Region {
+        code: "DE",
+        name: "Germany",
+        calling: "49",
+        trunk: "0",
+        lengths: (6, 13),
+        templates: &[],
+    },
+    Region {
+        code: "IT",
+        name: "Italy",
+        calling: "39",
+        trunk: "",
+        lengths: (6, 11),
+        templates: &[],
+    },
+    Region {
+        code: "MX",
+        name: "Mexico",
+        calling: "52",
+        trunk: "",
+        lengths: (10, 10),
+        templates: &[("", "## #### ####")],
+    },
+    Region {
+        code: "BR",
+        name: "Brazil",
+        calling: "55",
+        trunk: "0",
+        lengths: (10, 11),
+        templates: &[("", "(##) #####-####"), ("", "(##) ####-####")],
+    },
+    Region {
+        code: "PH",
+        name: "Philippines",
+        calling: "63",
+        trunk: "0",
+        lengths: (8, 10),
+        templates: &[("", "0### ### ####"), ("", "0# #### ####")],
+    },
+    Region {
+        code: "IN",
+        name: "India",
+        calling: "91",
+        trunk: "0",
+        lengths: (10, 10),
+        templates: &[("", "0##### #####")],
+    },
+    Region {
+        code: "AU",
+        name: "Australia",
+        calling: "61",
+        trunk: "0",
+        lengths: (9, 9),
+        templates: &[("", "0# #### ####")],
+    },
+    Region {
+        code: "JP",
+        name: "Japan",
+        calling: "81",
+        trunk: "0",
+        lengths: (9, 10),
+        templates: &[("", "0##-####-####"), ("", "0#-####-####")],
+    },
+];
+
+pub fn region(code: &str) -> Option<&'static Region> {
+    REGIONS.iter().find(|region| region.code == code)
+}
+
+impl Region {
+    fn is_nanp(&self) -> bool {
+        self.calling == "1"
+    }
+
+    fn fits(&self, national: &str) -> bool {
+        let (min, max) = self.lengths;
+        if !(min..=max).contains(&national.len()) {
+            return false;
+        }
+        // North American area codes and exchanges don't start with 0 or 1
+        !self.is_nanp() || (!national.starts_with(['0', '1']) && !national[3..].starts_with(['0', '1']))
+    }
+
+    fn national_format(&self, national: &str) -> String {
+        let template = self
+            .templates
+            .iter()
+            .find(|(leading, template)| national.starts_with(leading) && template.matches('#').count() == national.len());
+        let Some((_, template)) = template else {
+            return format!("{}{}", self.trunk, national);
+        };
+        let mut digits = national.chars();
+        template.chars().map(|ch| if ch == '#' { digits.next().unwrap_or(ch) } else { ch }).collect()
+    }
+}
+
+// `cell` in `format`, or None if it isn't a phone number. Blank cells are
+// left to the caller.
+pub fn reformat(cell: &str, default: &Region, format: Format) -> Option<String> {
+    let cell = cell.trim();
+    let (international, rest) = match cell.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, cell),
+    };
+    if rest.is_empty() || !rest.chars().all(|ch| ch.is_ascii_digit() || matches!(ch, ' ' | '-' | '.' | '(' | ')' | '/')) {
+        return None;
+    }
+    // The (0) in +44 (0)20 7946 0958 is the trunk prefix, not dialled from abroad
+    let rest = if international { rest.replace("(0)", "") } else { rest.to_string() };
+    let mut digits: String = rest.chars().filter(char::is_ascii_digit).collect();
+    let mut international = international;
+    for prefix in ["00", "011"] {
+        if !international && (prefix == "00" || default.is_nanp()) && digits.starts_with(prefix) {
+            digits.drain(..prefix.len());
+            international = true;
+        }
+    }
+    let (region, national) = if international {
+        // The calling code's region, preferring the default where codes are shared
+        let region = REGIONS
+            .iter()
+            .filter(|region| digits.starts_with(region.calling))
+            .max_by_key(|region| (region.calling.len(), region.code == default.code));
+        match region {
+            Some(region) => (region, &digits[region.calling.len()..]),
+            None => return (8..=15).contains(&digits.len()).then(|| format!("+{}", digits)),
+        }
+    } else {
+        // National numbers don't start with the trunk prefix, so it's always dialled
+        (default, digits.strip_prefix(default.trunk).unwrap_or(&digits))
+    };
+    if !region.fits(national) {
+        return None;
+    }
+    Some(match format {
+        Format::National if region.calling == default.calling => region.national_format(national),
+        _ => format!("+{}{}", region.calling, national),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn e164(cell: &str, default: &str) -> Option<String> {
+        reformat(cell, region(default).unwrap(), Format::E164)
+    }
+
+    fn national(cell: &str, default: &str) -> Option<String> {
+        reformat(cell, region(default).unwrap(), Format::National)
+    }
+
+    #[test]
+    fn e164_output() {
+        assert_eq!(e164("(415) 555-2671", "US").as_deref(), Some("+14155552671"));
+        assert_eq!(e164("1 415 555 2671", "US").as_deref(), Some("+14155552671"));
+        assert_eq!(e164("011 44 20 7946 0958", "US").as_deref(), Some("+442079460958"));
+        assert_eq!(e164("+44 (0)20 7946 0958", "US").as_deref(), Some("+442079460958"));
+        assert_eq!(e164("020 7946 0958", "GB").as_deref(), Some("+442079460958"));
+        assert_eq!(e164("0044 20 7946 0958", "FR").as_deref(), Some("+442079460958"));
+        // A calling code not in the table
+        assert_eq!(e164("+7 495 123 45 67", "US").as_deref(), Some("+74951234567"));
+        assert_eq!(e164("555-2671", "US"), None);
+        assert_eq!(e164("(015) 555-2671", "US"), None);
+        assert_eq!(e164("415 555 2671 ext 2", "US"), None);
+        assert_eq!(e164("+", "US"), None);
+    }
+
+    #[test]
+    fn italian_numbers_keep_their_leading_zero() {
+        assert_eq!(e164("06 1234 5678", "IT").as_deref(), Some("+390612345678"));
+        assert_eq!(e164("+39 06 1234 5678", "US").as_deref(), Some("+390612345678"));
+        assert_eq!(national("+39 06 1234 5678", "IT").as_deref(), Some("0612345678"));
+    }
+
+    #[test]
+    fn national_output() {
+        assert_eq!(national("+14155552671", "US").as_deref(), Some("(415) 555-2671"));
+        assert_eq!(national("+14155552671", "CA").as_deref(), Some("(415) 555-2671"));
+        assert_eq!(national("+44 20 7946 0958", "GB").as_deref(), Some("020 7946 0958"));
+        assert_eq!(national("+33 1 23 45 67 89", "FR").as_deref(), Some("01 23 45 67 89"));
+        // Other countries' numbers stay international
+        assert_eq!(national("+33 1 23 45 67 89", "GB").as_deref(), Some("+33123456789"));
+    }
+
+    #[test]
+    fn national_output_without_a_template() {
+        // No templates for Germany, and none of Ireland's fit seven digits
+        assert_eq!(national("+49 30 1234567", "DE").as_deref(), Some("0301234567"));
+        assert_eq!(national("030 1234567", "DE").as_deref(), Some("0301234567"));
+        assert_eq!(national("0123 4567", "IE").as_deref(), Some("01234567"));
+    }
+}
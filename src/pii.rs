@@ -0,0 +1,254 @@
+// Likely personal data in cells, and partial redaction of the columns that
+// hold it before the data leaves the app.
+//
+// Emails, phone numbers, national ID numbers and IBANs are found with the
+// pattern packs below, matched anywhere in a cell between word boundaries.
+// Card numbers are digit runs that pass the Luhn check, which a pattern can't
+// express. The packs aim at likely matches, not proof.
+
+use crate::i18n::tr;
+use crate::jobs::{Cancelled, Progress};
+use crate::pattern::Pattern;
+use crate::table::Table;
+use rayon::prelude::*;
+
+// Rows scanned between progress updates and cancellation checks
+const SCAN_CHUNK_ROWS: usize = 16 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Email,
+    Phone,
+    Card,
+    NationalId,
+    Iban,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [Kind::Email, Kind::Phone, Kind::Card, Kind::NationalId, Kind::Iban];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Kind::Email => "Email addresses",
+            Kind::Phone => "Phone numbers",
+            Kind::Card => "Card numbers",
+            Kind::NationalId => "National ID numbers",
+            Kind::Iban => "Bank account numbers",
+        })
+    }
+}
+
+const PACKS: &[(Kind, &str)] = &[
+    (Kind::Email, r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}"),
+    // International, North American and dotted or dashed local numbers
+    (Kind::Phone, r"\+\d{1,3}[ .-]?(?:\(\d{1,4}\)|\d{1,4})(?:[ .-]?\d{2,4}){2,4}"),
+    (Kind::Phone, r"\(\d{3}\) ?\d{3}[ .-]\d{4}"),
+    (Kind::Phone, r"\d{3}[.-]\d{3}[.-]\d{4}"),
+    // US Social Security, UK National Insurance and Spanish DNI and NIE numbers
+    (Kind::NationalId, r"\d{3}-\d{2}-\d{4}"),
+    (Kind::NationalId, r"[A-CEGHJ-PR-TW-Z]{2} ?\d{2} ?\d{2} ?\d{2} ?[A-D]"),
+    (Kind::NationalId, r"[XYZ]?\d{7,8}-?[A-HJ-NP-TV-Z]"),
+    (Kind::Iban, r"[A-Z]{2}\d{2} ?[A-Z0-9]{4}(?: ?[A-Z0-9]{4}){1,6}(?: ?[A-Z0-9]{1,3})?"),
+];
+
+pub struct Detector {
+    patterns: Vec<(Kind, Pattern)>,
+}
+
+impl Detector {
+    pub fn new() -> Self {
+        // Patterns match whole cells, so allow anything up to a word boundary
+        // on either side
+        let patterns = PACKS
+            .iter()
+            .map(|&(kind, pack)| {
+                let pattern = format!("(?:(?:.|\\n)*[^0-9A-Za-z])?(?:{})(?:[^0-9A-Za-z](?:.|\\n)*)?", pack);
+                (kind, Pattern::new(&pattern).unwrap_or_else(|err| panic!("built-in pattern {} doesn't parse: {}", pack, err)))
+            })
+            .collect();
+        Detector { patterns }
+    }
+
+    // The kinds found in `text`, in the order of Kind::ALL
+    pub fn kinds(&self, text: &str) -> Vec<Kind> {
+        // Every pack needs an @ or at least six digits
+        if !text.contains('@') && text.bytes().filter(u8::is_ascii_digit).count() < 6 {
+            return Vec::new();
+        }
+        let mut found = [false; 5];
+        for (kind, pattern) in &self.patterns {
+            if !found[*kind as usize] && pattern.is_match(text) {
+                found[*kind as usize] = true;
+            }
+        }
+        found[Kind::Card as usize] = has_card_number(text);
+        Kind::ALL.into_iter().filter(|&kind| found[kind as usize]).collect()
+    }
+}
+
+// A run of 13 to 19 digits, maybe grouped by single spaces or dashes, that
+// passes the Luhn check
+fn has_card_number(text: &str) -> bool {
+    let mut digits = Vec::new();
+    let mut after_separator = false;
+    for ch in text.chars().chain(['\0']) {
+        match ch {
+            '0'..='9' => {
+                digits.push(ch as u32 - '0' as u32);
+                after_separator = false;
+            }
+            ' ' | '-' if !digits.is_empty() && !after_separator => after_separator = true,
+            _ => {
+                if luhn(&digits) {
+                    return true;
+                }
+                digits.clear();
+                after_separator = false;
+            }
+        }
+    }
+    false
+}
+
+fn luhn(digits: &[u32]) -> bool {
+    if !(13..=19).contains(&digits.len()) || digits.iter().all(|&digit| digit == 0) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2 == 1, digit * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+// Partial redaction that keeps enough to recognize a value: the first letter
+// and the domain of an email, otherwise the last four letters or digits.
+// Punctuation and spacing stay so the format still shows.
+pub fn mask(text: &str) -> String {
+    if let Some((local, domain)) = text.split_once('@')
+        && !text.contains(char::is_whitespace)
+        && domain.contains('.')
+    {
+        let mut chars = local.chars();
+        if let Some(first) = chars.next() {
+            return format!("{}{}@{}", first, "*".repeat(chars.count()), domain);
+        }
+    }
+    let total = text.chars().filter(|ch| ch.is_alphanumeric()).count();
+    let keep_from = if total > 4 { total - 4 } else { total };
+    let mut seen = 0;
+    text.chars()
+        .map(|ch| {
+            if !ch.is_alphanumeric() {
+                return ch;
+            }
+            seen += 1;
+            if seen > keep_from { ch } else { '*' }
+        })
+        .collect()
+}
+
+// A copy of `data` with the cells of `columns` masked
+pub fn mask_table(data: &Table, columns: &[usize]) -> Table {
+    let mut masked = Table::new();
+    for row in data.rows() {
+        masked.push_row(row.iter().enumerate().map(|(col, cell)| {
+            if columns.contains(&col) { mask(cell) } else { cell.to_string() }
+        }));
+    }
+    masked.intern_low_cardinality_columns();
+    masked
+}
+
+pub struct ScanReport {
+    pub counts: Vec<[usize; 5]>, // Cells by column, then Kind as usize
+    pub filled: Vec<usize>,      // Non-blank cells by column
+}
+
+impl ScanReport {
+    fn new(columns: usize) -> Self {
+        ScanReport {
+            counts: vec![[0; 5]; columns],
+            filled: vec![0; columns],
+        }
+    }
+
+    fn add(mut self, other: ScanReport) -> Self {
+        for (counts, other) in self.counts.iter_mut().zip(other.counts) {
+            for (count, other) in counts.iter_mut().zip(other) {
+                *count += other;
+            }
+        }
+        for (filled, other) in self.filled.iter_mut().zip(other.filled) {
+            *filled += other;
+        }
+        self
+    }
+}
+
+// Chunks of rows are scanned in parallel, as the patterns are slow next to
+// the other scans
+pub fn scan(data: &Table, columns: usize, progress: &Progress) -> Result<ScanReport, Cancelled> {
+    let detector = Detector::new();
+    progress.set_total(data.len());
+    let starts: Vec<usize> = (0..data.len()).step_by(SCAN_CHUNK_ROWS).collect();
+    starts
+        .par_iter()
+        .map(|&start| {
+            progress.check()?;
+            let end = (start + SCAN_CHUNK_ROWS).min(data.len());
+            let mut report = ScanReport::new(columns);
+            for idx in start..end {
+                for (col, cell) in data.row(idx).iter().enumerate().take(columns) {
+                    if cell.trim().is_empty() {
+                        continue;
+                    }
+                    report.filled[col] += 1;
+                    for kind in detector.kinds(cell) {
+                        report.counts[col][kind as usize] += 1;
+                    }
+                }
+            }
+            progress.add_done(end - start);
+            Ok(report)
+        })
+        .try_reduce(|| ScanReport::new(columns), |total, report| Ok(total.add(report)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_pack_parses_and_matches() {
+        let detector = Detector::new();
+        assert_eq!(detector.patterns.len(), PACKS.len());
+        let samples = [
+            ("Mail jane.doe@example.com today", Kind::Email),
+            ("Call +44 20 7946 0958", Kind::Phone),
+            ("(415) 555-2671", Kind::Phone),
+            ("415-555-2671", Kind::Phone),
+            ("SSN 078-05-1120", Kind::NationalId),
+            ("NI AB 12 34 56 C", Kind::NationalId),
+            ("DNI 12345678Z", Kind::NationalId),
+            ("IBAN GB82 WEST 1234 5698 7654 32", Kind::Iban),
+            ("4111 1111 1111 1111", Kind::Card),
+        ];
+        for (text, kind) in samples {
+            assert!(detector.kinds(text).contains(&kind), "{:?} in {}", kind, text);
+        }
+        assert!(detector.kinds("Order 42 shipped").is_empty());
+    }
+
+    #[test]
+    fn mask_keeps_the_end() {
+        assert_eq!(mask("jane@example.com"), "j***@example.com");
+        assert_eq!(mask("4111-1111-1111-1234"), "****-****-****-1234");
+    }
+}
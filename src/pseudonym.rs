@@ -0,0 +1,53 @@
+// Replacing a column's identifiers so a dataset can be shared without them.
+//
+// Salted hashes give the same output for the same value and salt, so files
+// hashed with one salt still join, and without the salt a hash can't be
+// checked against guessed values. Surrogate IDs number the distinct values in
+// order of first appearance; the mapping back is saved separately.
+
+use crate::sha256::sha256_hex;
+use crate::table::Table;
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+#[derive(Clone, PartialEq)]
+pub enum Method {
+    Hash { salt: String },
+    Surrogate { prefix: String },
+}
+
+impl Method {
+    // For the change log
+    pub fn description(&self) -> &'static str {
+        match self {
+            Method::Hash { .. } => "salted SHA-256",
+            Method::Surrogate { .. } => "surrogate IDs",
+        }
+    }
+}
+
+// The replacement of each distinct value of `col`, in order of first
+// appearance. Blank cells stay blank.
+pub fn pseudonyms(data: &Table, col: usize, method: &Method) -> Vec<(String, String)> {
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::new();
+    for row in data.rows() {
+        let value = row.get(col).unwrap_or("");
+        if value.is_empty() || seen.contains(value) {
+            continue;
+        }
+        let replacement = match method {
+            Method::Hash { salt } => sha256_hex(format!("{}{}", salt, value).as_bytes()),
+            Method::Surrogate { prefix } => format!("{}{}", prefix, pairs.len() + 1),
+        };
+        seen.insert(value);
+        pairs.push((value.to_string(), replacement));
+    }
+    pairs
+}
+
+// 32 hex digits from the standard library's per-process random keys
+pub fn new_salt() -> String {
+    (0..2).map(|_| format!("{:016x}", RandomState::new().build_hasher().finish())).collect()
+}
@@ -0,0 +1,171 @@
+// A data-quality score out of 100 for each column and the whole file, so
+// files from different sources can be compared at a glance.
+//
+// Each score is the mean of the parts that apply:
+// - completeness: the share of cells that aren't blank
+// - validity: the share of filled cells that fit the column's type, as set in
+//   the schema or guessed from the first rows
+// - conformance: the share of rows meeting the column's schema rules, not
+//   counting blanks and type problems, which the parts above cover
+// The file's score also has uniqueness, the share of rows that don't repeat
+// an earlier row, and the share of rows meeting each row rule.
+
+use crate::jobs::{Cancelled, Progress};
+use crate::schema::{Check, ColumnType, ValidationReport};
+use crate::table::Table;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// Rows scanned between progress updates and cancellation checks
+const SCAN_CHUNK_ROWS: usize = 64 * 1024;
+
+pub struct ColumnQuality {
+    pub col: usize,
+    pub completeness: f64,
+    pub validity: f64,
+    pub conformance: Option<f64>, // None without schema rules for the column
+}
+
+impl ColumnQuality {
+    pub fn score(&self) -> f64 {
+        mean([Some(self.completeness), Some(self.validity), self.conformance]).unwrap_or(1.0) * 100.0
+    }
+}
+
+pub struct QualityReport {
+    pub rows: usize,
+    pub columns: Vec<ColumnQuality>,
+    pub duplicate_rows: usize,
+    pub row_rules: Option<f64>, // Mean share of rows meeting each row rule
+}
+
+impl QualityReport {
+    pub fn completeness(&self) -> f64 {
+        mean(self.columns.iter().map(|column| Some(column.completeness))).unwrap_or(1.0)
+    }
+
+    pub fn validity(&self) -> f64 {
+        mean(self.columns.iter().map(|column| Some(column.validity))).unwrap_or(1.0)
+    }
+
+    pub fn uniqueness(&self) -> f64 {
+        if self.rows == 0 { 1.0 } else { 1.0 - self.duplicate_rows as f64 / self.rows as f64 }
+    }
+
+    // Of the columns with rules and the row rules, or None without a schema
+    pub fn conformance(&self) -> Option<f64> {
+        mean(self.columns.iter().map(|column| column.conformance).chain([self.row_rules]))
+    }
+
+    pub fn score(&self) -> f64 {
+        let parts = [Some(self.completeness()), Some(self.validity()), Some(self.uniqueness()), self.conformance()];
+        mean(parts).unwrap_or(1.0) * 100.0
+    }
+}
+
+// Mean of the parts that are Some, or None if none are
+fn mean(parts: impl IntoIterator<Item = Option<f64>>) -> Option<f64> {
+    let (mut sum, mut count) = (0.0, 0);
+    for part in parts.into_iter().flatten() {
+        sum += part;
+        count += 1;
+    }
+    (count > 0).then(|| sum / count as f64)
+}
+
+// `types` has each column's type; `validation` is the schema's report on the
+// same data, if there is a schema
+pub fn score(
+    data: &Table,
+    types: &[ColumnType],
+    validation: Option<&ValidationReport>,
+    progress: &Progress,
+) -> Result<QualityReport, Cancelled> {
+    let rows = data.len();
+    progress.set_total(rows);
+    let mut filled = vec![0usize; types.len()];
+    let mut valid = vec![0usize; types.len()];
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new(); // Distinct rows by hash
+    let mut duplicate_rows = 0;
+    for start in (0..rows).step_by(SCAN_CHUNK_ROWS) {
+        progress.check()?;
+        let end = (start + SCAN_CHUNK_ROWS).min(rows);
+        for idx in start..end {
+            let row = data.row(idx);
+            let mut hasher = DefaultHasher::new();
+            for (col, cell) in row.iter().enumerate() {
+                cell.hash(&mut hasher);
+                if col >= types.len() || cell.trim().is_empty() {
+                    continue;
+                }
+                filled[col] += 1;
+                if types[col].accepts(cell) {
+                    valid[col] += 1;
+                }
+            }
+            // Rows with the same hash are compared, as hashes can collide
+            let earlier = seen.entry(hasher.finish()).or_default();
+            if earlier.iter().any(|&other| data.row(other).iter().eq(row.iter())) {
+                duplicate_rows += 1;
+            } else {
+                earlier.push(idx);
+            }
+        }
+        progress.set_done(end);
+    }
+
+    // Rule problems by column, and by row rule
+    let mut ruled = vec![false; types.len()];
+    let mut violations = vec![0usize; types.len()];
+    let mut row_rules = Vec::new();
+    if let Some(report) = validation {
+        for &col in report.columns.iter().flatten() {
+            ruled[col] = true;
+        }
+        row_rules = vec![0; report.names.len() - report.columns.len()];
+        for &(rule, check, count) in &report.counts {
+            if matches!(check, Check::MissingColumn | Check::Required | Check::Type) {
+                continue;
+            }
+            match report.columns.get(rule) {
+                Some(&Some(col)) => violations[col] += count,
+                Some(None) => {}
+                None => row_rules[rule - report.columns.len()] += count,
+            }
+        }
+    }
+    let share = |count: usize| if rows == 0 { 1.0 } else { 1.0 - (count as f64 / rows as f64).min(1.0) };
+    let columns = (0..types.len())
+        .map(|col| ColumnQuality {
+            col,
+            completeness: if rows == 0 { 1.0 } else { filled[col] as f64 / rows as f64 },
+            validity: if filled[col] == 0 { 1.0 } else { valid[col] as f64 / filled[col] as f64 },
+            conformance: ruled[col].then(|| share(violations[col])),
+        })
+        .collect();
+    let row_rules = mean(row_rules.iter().map(|&count| Some(share(count))));
+    Ok(QualityReport {
+        rows,
+        columns,
+        duplicate_rows,
+        row_rules,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_are_whole_rows() {
+        let mut data = Table::new();
+        for row in [["a", "1"], ["b", "2"], ["a", "1"], ["a", "2"], ["a", "1"], ["", ""]] {
+            data.push_row(row);
+        }
+        let types = [ColumnType::Text, ColumnType::Integer];
+        let report = score(&data, &types, None, &Progress::default()).unwrap();
+        assert_eq!(report.duplicate_rows, 2);
+        assert_eq!(report.columns[0].completeness, 5.0 / 6.0);
+        assert_eq!(report.columns[1].validity, 1.0);
+    }
+}
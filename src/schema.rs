@@ -0,0 +1,813 @@
+// Column schemas and validation against them.
+//
+// A schema lists columns by name, each with a type, whether a value is
+// required, a pattern the whole value must match (see pattern.rs) and a minimum
+// and maximum. The limits are values for integer and number columns, dates for
+// date columns and lengths in characters for text. Number columns can also be
+// limited to so many standard deviations from the column's mean, which catches
+// values that are possible but unlikely. Empty cells are only checked for being
+// required. Row rules relate the columns of each row with an expression (see
+// expr.rs), like `end_date >= start_date`, and are skipped for rows where any
+// of their columns is blank. Validation runs as a background job and reports
+// each failing check on each cell, plus one problem for every schema column the
+// file doesn't have. The header alone can also be checked for missing, extra
+// and misordered columns, which is done whenever a file opens.
+//
+// The schema is kept in the config as `schema.column.<n>` lines, so it
+// carries over to the next file of the same kind. It can also be read from a
+// Frictionless JSON Table Schema or CSVW metadata file, and saved as a Table
+// Schema. A schema file next to the CSV, `data.schema.json` or
+// `data.csv-metadata.json` for `data.csv`, is loaded with it.
+//
+// A schema can also name patterns of its own, kept with it as a library for
+// its columns; Table Schemas store them in a `patterns` object beside
+// `fields`, which other tools ignore.
+
+use crate::config::Config;
+use crate::export::json_string;
+use crate::expr::Expr;
+use crate::i18n::tr;
+use crate::json::Json;
+use crate::jobs::{Cancelled, Progress};
+use crate::pattern::Pattern;
+use crate::table::Table;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+// Rows validated between progress updates and cancellation checks
+const VALIDATE_CHUNK_ROWS: usize = 64 * 1024;
+
+// Problems kept for listing; all of them are still counted
+const MAX_LISTED_PROBLEMS: usize = 100_000;
+
+// Rows looked at when guessing column types
+const INFER_ROWS: usize = 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnType {
+    #[default]
+    Text,
+    Integer,
+    Number,
+    Date,
+    Boolean,
+}
+
+impl ColumnType {
+    pub const ALL: [ColumnType; 5] = [
+        ColumnType::Text,
+        ColumnType::Integer,
+        ColumnType::Number,
+        ColumnType::Date,
+        ColumnType::Boolean,
+    ];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            ColumnType::Text => "Text",
+            ColumnType::Integer => "Integer",
+            ColumnType::Number => "Number",
+            ColumnType::Date => "Date (YYYY-MM-DD)",
+            ColumnType::Boolean => "Boolean",
+        })
+    }
+
+    fn config_value(self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Integer => "integer",
+            ColumnType::Number => "number",
+            ColumnType::Date => "date",
+            ColumnType::Boolean => "boolean",
+        }
+    }
+
+    // Table Schema and CSVW (XML Schema) type names. Types with no equivalent
+    // here, like datetime, are checked as text.
+    fn from_schema_type(name: &str) -> Self {
+        match name {
+            "integer" | "int" | "long" | "short" | "byte" | "nonNegativeInteger" | "positiveInteger" => ColumnType::Integer,
+            "number" | "decimal" | "double" | "float" => ColumnType::Number,
+            "date" => ColumnType::Date,
+            "boolean" => ColumnType::Boolean,
+            _ => ColumnType::Text,
+        }
+    }
+
+    fn schema_type(self) -> &'static str {
+        match self {
+            ColumnType::Text => "string",
+            ColumnType::Integer => "integer",
+            ColumnType::Number => "number",
+            ColumnType::Date => "date",
+            ColumnType::Boolean => "boolean",
+        }
+    }
+
+    fn from_config_value(value: &str) -> Self {
+        ColumnType::ALL
+            .into_iter()
+            .find(|column_type| column_type.config_value() == value)
+            .unwrap_or_default()
+    }
+
+    // Surrounding spaces are allowed, since most tools ignore them
+    pub fn accepts(self, cell: &str) -> bool {
+        let cell = cell.trim();
+        match self {
+            ColumnType::Text => true,
+            ColumnType::Integer => cell.parse::<i64>().is_ok(),
+            ColumnType::Number => parse_number(cell).is_some(),
+            ColumnType::Date => is_date(cell),
+            ColumnType::Boolean => {
+                ["true", "false", "yes", "no", "1", "0"].iter().any(|value| cell.eq_ignore_ascii_case(value))
+            }
+        }
+    }
+
+    // The narrowest type that accepts every non-empty value, or text
+    pub fn infer<'c>(cells: impl Iterator<Item = &'c str>) -> Self {
+        let mut candidates = vec![ColumnType::Integer, ColumnType::Number, ColumnType::Date, ColumnType::Boolean];
+        let mut any = false;
+        for cell in cells.filter(|cell| !cell.trim().is_empty()) {
+            any = true;
+            candidates.retain(|column_type| column_type.accepts(cell));
+            if candidates.is_empty() {
+                break;
+            }
+        }
+        match candidates.first() {
+            Some(&column_type) if any => column_type,
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+// Finite numbers only, so "inf" and "NaN" aren't numbers
+fn parse_number(cell: &str) -> Option<f64> {
+    cell.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+fn is_date(cell: &str) -> bool {
+    let bytes = cell.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return false;
+    }
+    let number = |range: std::ops::Range<usize>| {
+        let digits = &cell[range];
+        digits.bytes().all(|byte| byte.is_ascii_digit()).then(|| digits.parse::<u32>().ok()).flatten()
+    };
+    let (Some(year), Some(month), Some(day)) = (number(0..4), number(5..7), number(8..10)) else {
+        return false;
+    };
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct ColumnRule {
+    pub name: String,
+    pub column_type: ColumnType,
+    pub required: bool,
+    pub pattern: String, // Empty for none
+    pub min: String,     // As typed; empty for no limit
+    pub max: String,
+    pub title: String, // Shown in the column header instead of the name; empty for none
+    pub deviations: String, // Standard deviations from the mean allowed, as typed; empty for no limit
+}
+
+impl ColumnRule {
+    // With the pattern and limits parsed and the column found in `header`
+    fn compile(&self, header: &[String]) -> Result<CompiledRule, String> {
+        let pattern = match self.pattern.as_str() {
+            "" => None,
+            pattern => Some(Pattern::new(pattern).map_err(|err| tr!("{}: pattern: {}", self.name, err))?),
+        };
+        Ok(CompiledRule {
+            name: self.name.clone(),
+            col: header.iter().position(|name| *name == self.name),
+            column_type: self.column_type,
+            required: self.required,
+            pattern,
+            min: self.limit(&self.min).map_err(|err| tr!("{}: minimum: {}", self.name, err))?,
+            max: self.limit(&self.max).map_err(|err| tr!("{}: maximum: {}", self.name, err))?,
+            deviations: self.deviations().map_err(|err| tr!("{}: standard deviations: {}", self.name, err))?,
+        })
+    }
+
+    fn deviations(&self) -> Result<Option<f64>, String> {
+        let text = self.deviations.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        if !matches!(self.column_type, ColumnType::Integer | ColumnType::Number) {
+            return Err(tr("only numbers have a mean").to_string());
+        }
+        match parse_number(text) {
+            Some(deviations) if deviations > 0.0 => Ok(Some(deviations)),
+            _ => Err(tr("must be a number above zero").to_string()),
+        }
+    }
+
+    fn limit(&self, text: &str) -> Result<Option<Limit>, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let limit = match self.column_type {
+            ColumnType::Integer | ColumnType::Number => parse_number(text).map(Limit::Value),
+            ColumnType::Text => text.parse().ok().map(Limit::Length),
+            ColumnType::Date => is_date(text).then(|| Limit::Date(text.to_string())),
+            ColumnType::Boolean => return Err(tr("booleans have no limits").to_string()),
+        };
+        limit.map(Some).ok_or_else(|| {
+            tr(match self.column_type {
+                ColumnType::Text => "must be a whole number of characters",
+                ColumnType::Date => "must be a date like 2024-01-31",
+                _ => "must be a number",
+            })
+            .to_string()
+        })
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Limit {
+    Value(f64),
+    Length(usize),
+    Date(String),
+}
+
+impl Limit {
+    // How `cell`, already known to be of the column's type, compares to the limit
+    fn compare(&self, cell: &str) -> Option<std::cmp::Ordering> {
+        let cell = cell.trim();
+        match self {
+            Limit::Value(limit) => parse_number(cell)?.partial_cmp(limit),
+            Limit::Length(limit) => Some(cell.chars().count().cmp(limit)),
+            Limit::Date(limit) => Some(cell.cmp(limit.as_str())),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Default)]
+pub struct Schema {
+    pub columns: Vec<ColumnRule>,
+    pub row_rules: Vec<String>,          // Expressions each row must meet
+    pub patterns: Vec<(String, String)>, // Named patterns offered for the columns, besides pattern::LIBRARY
+}
+
+impl Schema {
+    pub fn from_config(config: &Config) -> Self {
+        let count = config.get("schema.columns").and_then(|count| count.parse().ok()).unwrap_or(0);
+        let columns = (0..count)
+            .filter_map(|idx| {
+                let line = config.get(&format!("schema.column.{}", idx))?;
+                let mut fields = line.split('\t');
+                let mut field = || fields.next().unwrap_or("").to_string();
+                Some(ColumnRule {
+                    name: field(),
+                    column_type: ColumnType::from_config_value(&field()),
+                    required: field() == "required",
+                    min: field(),
+                    max: field(),
+                    pattern: field(),
+                    title: field(),
+                    deviations: field(),
+                })
+            })
+            .collect();
+        let count = config.get("schema.row_rules").and_then(|count| count.parse().ok()).unwrap_or(0);
+        let row_rules = (0..count).filter_map(|idx| config.get(&format!("schema.row_rule.{}", idx)).map(str::to_string)).collect();
+        let count = config.get("schema.patterns").and_then(|count| count.parse().ok()).unwrap_or(0);
+        let patterns = (0..count)
+            .filter_map(|idx| {
+                let (name, pattern) = config.get(&format!("schema.pattern.{}", idx))?.split_once('\t')?;
+                Some((name.to_string(), pattern.to_string()))
+            })
+            .collect();
+        Schema { columns, row_rules, patterns }
+    }
+
+    // Tab-separated, since names and patterns often contain commas
+    pub fn save_to(&self, config: &mut Config) {
+        config.set("schema.columns", self.columns.len().to_string());
+        for (idx, rule) in self.columns.iter().enumerate() {
+            let required = if rule.required { "required" } else { "optional" };
+            let line = [rule.name.as_str(), rule.column_type.config_value(), required, &rule.min, &rule.max, &rule.pattern, &rule.title, &rule.deviations];
+            config.set(&format!("schema.column.{}", idx), line.join("\t"));
+        }
+        config.set("schema.row_rules", self.row_rules.len().to_string());
+        for (idx, rule) in self.row_rules.iter().enumerate() {
+            config.set(&format!("schema.row_rule.{}", idx), rule);
+        }
+        config.set("schema.patterns", self.patterns.len().to_string());
+        for (idx, (name, pattern)) in self.patterns.iter().enumerate() {
+            config.set(&format!("schema.pattern.{}", idx), format!("{}\t{}", name, pattern));
+        }
+    }
+
+    // One rule per column, with types guessed from the first rows
+    pub fn from_table(header: &[String], data: &Table) -> Self {
+        let types = Schema::default().column_types(header, data);
+        let columns = header
+            .iter()
+            .zip(types)
+            .map(|(name, column_type)| ColumnRule {
+                name: name.clone(),
+                column_type,
+                ..ColumnRule::default()
+            })
+            .collect();
+        Schema {
+            columns,
+            ..Schema::default()
+        }
+    }
+
+    // Each column's type as declared here, or else as guessed from the first rows
+    pub fn column_types(&self, header: &[String], data: &Table) -> Vec<ColumnType> {
+        let rows = data.len().min(INFER_ROWS);
+        header
+            .iter()
+            .enumerate()
+            .map(|(col, name)| match self.columns.iter().find(|rule| rule.name == *name) {
+                Some(rule) => rule.column_type,
+                None => ColumnType::infer((0..rows).map(|idx| data.row(idx).get(col).unwrap_or(""))),
+            })
+            .collect()
+    }
+
+    pub fn title(&self, name: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|rule| rule.name == name && !rule.title.is_empty())
+            .map(|rule| rule.title.as_str())
+    }
+
+    // Schema files that are loaded along with the CSV at `path`, if present
+    pub fn paths_for(path: &Path) -> [PathBuf; 2] {
+        let stem = path.file_stem().map_or_else(Default::default, |stem| stem.to_string_lossy());
+        let name = path.file_name().map_or_else(Default::default, |name| name.to_string_lossy());
+        [path.with_file_name(format!("{}.schema.json", stem)), path.with_file_name(format!("{}-metadata.json", name))]
+    }
+
+    // A Table Schema has a `fields` list; CSVW metadata has `tableSchema`,
+    // possibly inside the first of its `tables`. CSVW columns are matched to
+    // the header by their title, Table Schema fields by their name.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let root = Json::parse(text)?;
+        if let Some(fields) = root.get("fields") {
+            let columns = fields.as_array().iter().map(Schema::table_schema_field).collect::<Result<_, _>>()?;
+            let patterns = match root.get("patterns") {
+                Some(Json::Object(entries)) => {
+                    entries.iter().filter_map(|(name, pattern)| Some((name.clone(), pattern.as_str()?.to_string()))).collect()
+                }
+                _ => Vec::new(),
+            };
+            return Ok(Schema {
+                columns,
+                row_rules: Vec::new(),
+                patterns,
+            });
+        }
+        let table_schema = root
+            .get("tableSchema")
+            .or_else(|| root.get("tables")?.as_array().first()?.get("tableSchema"))
+            .ok_or_else(|| tr("not a Table Schema or CSVW metadata file").to_string())?;
+        let columns = table_schema
+            .get("columns")
+            .map_or(&[][..], Json::as_array)
+            .iter()
+            .filter(|column| column.get("virtual").and_then(Json::as_bool) != Some(true))
+            .map(Schema::csvw_column)
+            .collect::<Result<_, _>>()?;
+        Ok(Schema {
+            columns,
+            ..Schema::default()
+        })
+    }
+
+    fn table_schema_field(field: &Json) -> Result<ColumnRule, String> {
+        let name = field.get("name").and_then(Json::as_str).ok_or_else(|| tr("a field has no name").to_string())?;
+        let column_type = ColumnType::from_schema_type(field.get("type").and_then(Json::as_str).unwrap_or("string"));
+        let constraint = |key: &str| field.get("constraints")?.get(key);
+        let limit = |value_key: &str, length_key: &str| {
+            let key = if column_type == ColumnType::Text { length_key } else { value_key };
+            constraint(key).and_then(Json::to_text).unwrap_or_default()
+        };
+        Ok(ColumnRule {
+            name: name.to_string(),
+            column_type,
+            required: constraint("required").and_then(Json::as_bool).unwrap_or(false),
+            pattern: constraint("pattern").and_then(Json::as_str).unwrap_or("").to_string(),
+            min: limit("minimum", "minLength"),
+            max: limit("maximum", "maxLength"),
+            title: field.get("title").and_then(Json::as_str).unwrap_or("").to_string(),
+            deviations: String::new(),
+        })
+    }
+
+    fn csvw_column(column: &Json) -> Result<ColumnRule, String> {
+        // Titles may be a string, a list or a map from language to either
+        let titles = column.get("titles").map(|titles| match titles {
+            Json::Object(languages) => languages.first().map_or(&Json::Null, |(_, titles)| titles),
+            titles => titles,
+        });
+        let title = titles.and_then(|titles| titles.as_str().or_else(|| titles.as_array().first()?.as_str()));
+        let name = title
+            .or_else(|| column.get("name").and_then(Json::as_str))
+            .ok_or_else(|| tr("a column has no name or title").to_string())?;
+        let datatype = column.get("datatype");
+        let base = match datatype {
+            Some(Json::String(base)) => base.as_str(),
+            Some(datatype) => datatype.get("base").and_then(Json::as_str).unwrap_or("string"),
+            None => "string",
+        };
+        let column_type = ColumnType::from_schema_type(base);
+        let facet = |keys: &[&str]| keys.iter().find_map(|key| datatype?.get(key)?.to_text()).unwrap_or_default();
+        let (min, max) = if column_type == ColumnType::Text {
+            (facet(&["minLength", "length"]), facet(&["maxLength", "length"]))
+        } else {
+            (facet(&["minimum", "minInclusive"]), facet(&["maximum", "maxInclusive"]))
+        };
+        Ok(ColumnRule {
+            name: name.to_string(),
+            column_type,
+            required: column.get("required").and_then(Json::as_bool).unwrap_or(false),
+            // CSVW puts the pattern in `format` for strings only; for other
+            // types it describes a number or date format
+            pattern: match column_type {
+                ColumnType::Text => facet(&["format"]),
+                _ => String::new(),
+            },
+            min,
+            max,
+            title: String::new(),
+            deviations: String::new(),
+        })
+    }
+
+    // As a Frictionless Table Schema
+    pub fn to_table_schema(&self) -> String {
+        let fields: Vec<String> = self
+            .columns
+            .iter()
+            .map(|rule| {
+                let mut field = format!("    {{\n      \"name\": {}", json_string(&rule.name));
+                if !rule.title.is_empty() {
+                    field.push_str(&format!(",\n      \"title\": {}", json_string(&rule.title)));
+                }
+                field.push_str(&format!(",\n      \"type\": \"{}\"", rule.column_type.schema_type()));
+                let mut constraints = Vec::new();
+                if rule.required {
+                    constraints.push("\"required\": true".to_string());
+                }
+                if !rule.pattern.is_empty() {
+                    constraints.push(format!("\"pattern\": {}", json_string(&rule.pattern)));
+                }
+                let (min_key, max_key) = match rule.column_type {
+                    ColumnType::Text => ("minLength", "maxLength"),
+                    _ => ("minimum", "maximum"),
+                };
+                for (key, limit) in [(min_key, &rule.min), (max_key, &rule.max)] {
+                    let limit = limit.trim();
+                    if limit.is_empty() {
+                        continue;
+                    }
+                    // Dates are strings; numbers and lengths are written bare
+                    let value = match rule.column_type {
+                        ColumnType::Date => json_string(limit),
+                        _ if crate::export::is_json_number(limit) => limit.to_string(),
+                        _ => json_string(limit),
+                    };
+                    constraints.push(format!("\"{}\": {}", key, value));
+                }
+                if !constraints.is_empty() {
+                    field.push_str(&format!(",\n      \"constraints\": {{ {} }}", constraints.join(", ")));
+                }
+                field.push_str("\n    }");
+                field
+            })
+            .collect();
+        let patterns: Vec<String> = self
+            .named_patterns()
+            .map(|(name, pattern)| format!("    {}: {}", json_string(name), json_string(pattern)))
+            .collect();
+        if patterns.is_empty() {
+            return format!("{{\n  \"fields\": [\n{}\n  ]\n}}\n", fields.join(",\n"));
+        }
+        format!("{{\n  \"fields\": [\n{}\n  ],\n  \"patterns\": {{\n{}\n  }}\n}}\n", fields.join(",\n"), patterns.join(",\n"))
+    }
+
+    // How `header` differs from the schema's columns. Columns out of order
+    // are the fewest that would have to move to put the rest in schema order.
+    pub fn check_header(&self, header: &[String]) -> HeaderCheck {
+        let position = |name: &String| self.columns.iter().position(|rule| rule.name == *name);
+        let missing = self
+            .columns
+            .iter()
+            .filter(|rule| !header.contains(&rule.name))
+            .map(|rule| rule.name.clone())
+            .collect();
+        let extra = header.iter().filter(|name| position(name).is_none()).cloned().collect();
+        // Longest run of shared columns already in schema order
+        let shared: Vec<(usize, &String)> = header.iter().filter_map(|name| Some((position(name)?, name))).collect();
+        let mut longest = vec![1; shared.len()];
+        let mut previous = vec![None; shared.len()];
+        for i in 0..shared.len() {
+            for j in 0..i {
+                if shared[j].0 < shared[i].0 && longest[j] + 1 > longest[i] {
+                    longest[i] = longest[j] + 1;
+                    previous[i] = Some(j);
+                }
+            }
+        }
+        let mut in_order = vec![false; shared.len()];
+        let mut next = (0..shared.len()).max_by_key(|&i| longest[i]);
+        while let Some(i) = next {
+            in_order[i] = true;
+            next = previous[i];
+        }
+        let misordered = shared
+            .iter()
+            .zip(in_order)
+            .filter(|(_, in_order)| !in_order)
+            .map(|((_, name), _)| (*name).clone())
+            .collect();
+        HeaderCheck { missing, extra, misordered }
+    }
+
+    // Row rules left blank in the editor are ignored
+    fn row_rules(&self) -> impl Iterator<Item = &String> {
+        self.row_rules.iter().filter(|rule| !rule.trim().is_empty())
+    }
+
+    // Named patterns left without a name in the editor are ignored
+    pub fn named_patterns(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.patterns.iter().map(|(name, pattern)| (name.trim(), pattern.as_str())).filter(|(name, _)| !name.is_empty())
+    }
+
+    // Patterns, limits or row rules that don't parse, one message each
+    pub fn errors(&self) -> Vec<String> {
+        let column_errors = self.columns.iter().filter_map(|rule| rule.compile(&[]).err());
+        let row_errors = self.row_rules().filter_map(|rule| compile_row_rule(rule, &[]).err());
+        let pattern_errors = self.named_patterns().filter_map(|(name, pattern)| Pattern::new(pattern).err().map(|err| tr!("{}: pattern: {}", name, err)));
+        column_errors.chain(row_errors).chain(pattern_errors).collect()
+    }
+
+    pub fn validator(&self, header: &[String]) -> Result<Validator, String> {
+        let rules = self.columns.iter().map(|rule| rule.compile(header)).collect::<Result<_, _>>()?;
+        let row_rules = self.row_rules().map(|rule| compile_row_rule(rule, header)).collect::<Result<_, _>>()?;
+        Ok(Validator { rules, row_rules })
+    }
+}
+
+// Differences between a file's header and the schema
+#[derive(Default)]
+pub struct HeaderCheck {
+    pub missing: Vec<String>,    // In the schema but not the file
+    pub extra: Vec<String>,      // In the file but not the schema
+    pub misordered: Vec<String>, // In both, but out of the schema's order
+}
+
+impl HeaderCheck {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.misordered.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    MissingColumn,
+    Required,
+    Type,
+    Pattern,
+    Min,
+    Max,
+    Deviation,
+    RowRule,
+}
+
+impl Check {
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Check::MissingColumn => "Column missing",
+            Check::Required => "Value required",
+            Check::Type => "Wrong type",
+            Check::Pattern => "Doesn't match pattern",
+            Check::Min => "Below minimum",
+            Check::Max => "Above maximum",
+            Check::Deviation => "Far from the mean",
+            Check::RowRule => "Rule not met",
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Problem {
+    pub row: Option<usize>, // Data index; None for a missing column
+    pub col: Option<usize>,
+    pub rule: usize, // Index into `names`
+    pub check: Check,
+}
+
+pub struct ValidationReport {
+    pub names: Vec<String>, // The schema's column names, then its row rules, as validated
+    pub columns: Vec<Option<usize>>, // Column each column rule checks, None if missing; row rules follow in `names`
+    pub problems: Vec<Problem>, // At most MAX_LISTED_PROBLEMS, in row order
+    pub counts: Vec<(usize, Check, usize)>, // Rule, check and number of problems, for every failing check
+    pub cells: HashMap<(usize, usize), Check>, // First listed problem by data index and column, for the grid
+    pub total: usize,
+    pub rows_checked: usize,
+}
+
+struct CompiledRule {
+    name: String,
+    col: Option<usize>, // None if the file doesn't have the column
+    column_type: ColumnType,
+    required: bool,
+    pattern: Option<Pattern>,
+    min: Option<Limit>,
+    max: Option<Limit>,
+    deviations: Option<f64>,
+}
+
+impl CompiledRule {
+    // The values within `deviations` standard deviations of the mean of the
+    // column's numbers, or None without a limit or enough numbers
+    fn spread(&self, data: &Table) -> Option<(f64, f64)> {
+        let (deviations, col) = (self.deviations?, self.col?);
+        let (mut count, mut sum, mut squares) = (0.0, 0.0, 0.0);
+        for idx in 0..data.len() {
+            let cell = data.row(idx).get(col).unwrap_or("");
+            if let Some(value) = parse_number(cell.trim()) {
+                count += 1.0;
+                sum += value;
+                squares += value * value;
+            }
+        }
+        if count < 2.0 {
+            return None;
+        }
+        let mean = sum / count;
+        let std_dev = ((squares - sum * mean) / (count - 1.0)).max(0.0).sqrt();
+        Some((mean - deviations * std_dev, mean + deviations * std_dev))
+    }
+
+    fn check(&self, cell: &str, spread: Option<(f64, f64)>) -> Option<Check> {
+        if cell.trim().is_empty() {
+            return self.required.then_some(Check::Required);
+        }
+        if !self.column_type.accepts(cell) {
+            return Some(Check::Type);
+        }
+        if self.pattern.as_ref().is_some_and(|pattern| !pattern.is_match(cell)) {
+            return Some(Check::Pattern);
+        }
+        if self.min.as_ref().is_some_and(|min| min.compare(cell).is_some_and(|order| order.is_lt())) {
+            return Some(Check::Min);
+        }
+        if self.max.as_ref().is_some_and(|max| max.compare(cell).is_some_and(|order| order.is_gt())) {
+            return Some(Check::Max);
+        }
+        if let (Some((low, high)), Some(value)) = (spread, parse_number(cell.trim()))
+            && (value < low || value > high)
+        {
+            return Some(Check::Deviation);
+        }
+        None
+    }
+}
+
+struct RowRule {
+    text: String,
+    expr: Expr,
+    cols: Option<Vec<usize>>, // For each of the expression's columns; None if the file lacks any
+}
+
+fn compile_row_rule(text: &str, header: &[String]) -> Result<RowRule, String> {
+    let expr = Expr::new(text).map_err(|err| tr!("{}: {}", text, err))?;
+    let cols = expr.columns().iter().map(|name| header.iter().position(|column| column == name)).collect();
+    Ok(RowRule {
+        text: text.to_string(),
+        expr,
+        cols,
+    })
+}
+
+impl RowRule {
+    // Met, or skipped because one of its cells is blank
+    fn is_met(&self, cells: &[&str]) -> bool {
+        cells.iter().any(|cell| cell.trim().is_empty()) || self.expr.is_true(cells) == Some(true)
+    }
+}
+
+pub struct Validator {
+    rules: Vec<CompiledRule>,
+    row_rules: Vec<RowRule>,
+}
+
+impl Validator {
+    pub fn run(&self, data: &Table, progress: &Progress) -> Result<ValidationReport, Cancelled> {
+        let mut report = ValidationReport {
+            names: self.rules.iter().map(|rule| rule.name.clone()).chain(self.row_rules.iter().map(|rule| rule.text.clone())).collect(),
+            columns: self.rules.iter().map(|rule| rule.col).collect(),
+            problems: Vec::new(),
+            counts: Vec::new(),
+            cells: HashMap::new(),
+            total: 0,
+            rows_checked: data.len(),
+        };
+        let mut counts = vec![[0; 8]; report.names.len()]; // By rule, then Check as usize
+        let missing = self.rules.iter().map(|rule| rule.col.is_none()).chain(self.row_rules.iter().map(|rule| rule.cols.is_none()));
+        for (rule, missing) in missing.enumerate() {
+            if missing {
+                report.problems.push(Problem {
+                    row: None,
+                    col: None,
+                    rule,
+                    check: Check::MissingColumn,
+                });
+                counts[rule][Check::MissingColumn as usize] += 1;
+            }
+        }
+        let spreads: Vec<Option<(f64, f64)>> = self.rules.iter().map(|rule| rule.spread(data)).collect();
+        progress.set_total(data.len());
+        for start in (0..data.len()).step_by(VALIDATE_CHUNK_ROWS) {
+            progress.check()?;
+            let end = (start + VALIDATE_CHUNK_ROWS).min(data.len());
+            for idx in start..end {
+                let row = data.row(idx);
+                for (rule, compiled) in self.rules.iter().enumerate() {
+                    let Some(col) = compiled.col else {
+                        continue;
+                    };
+                    if let Some(check) = compiled.check(row.get(col).unwrap_or(""), spreads[rule]) {
+                        counts[rule][check as usize] += 1;
+                        if report.problems.len() < MAX_LISTED_PROBLEMS {
+                            report.cells.entry((idx, col)).or_insert(check);
+                            report.problems.push(Problem {
+                                row: Some(idx),
+                                col: Some(col),
+                                rule,
+                                check,
+                            });
+                        }
+                    }
+                }
+                for (offset, row_rule) in self.row_rules.iter().enumerate() {
+                    let Some(cols) = &row_rule.cols else {
+                        continue;
+                    };
+                    let cells: Vec<&str> = cols.iter().map(|&col| row.get(col).unwrap_or("")).collect();
+                    if row_rule.is_met(&cells) {
+                        continue;
+                    }
+                    let rule = self.rules.len() + offset;
+                    counts[rule][Check::RowRule as usize] += 1;
+                    if report.problems.len() < MAX_LISTED_PROBLEMS {
+                        // Listed under the rule's first column, so it can be found in the grid
+                        let col = cols.first().copied();
+                        if let Some(col) = col {
+                            report.cells.entry((idx, col)).or_insert(Check::RowRule);
+                        }
+                        report.problems.push(Problem {
+                            row: Some(idx),
+                            col,
+                            rule,
+                            check: Check::RowRule,
+                        });
+                    }
+                }
+            }
+            progress.set_done(end);
+        }
+        const CHECKS: [Check; 8] = [
+            Check::MissingColumn,
+            Check::Required,
+            Check::Type,
+            Check::Pattern,
+            Check::Min,
+            Check::Max,
+            Check::Deviation,
+            Check::RowRule,
+        ];
+        for (rule, rule_counts) in counts.iter().enumerate() {
+            for (check, &count) in CHECKS.iter().zip(rule_counts) {
+                if count > 0 {
+                    report.counts.push((rule, *check, count));
+                    report.total += count;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,143 @@
+// Column search for the search box, run as a background job.
+//
+// Substring matches are case-insensitive. Lowercasing every cell allocates a
+// string per cell and dominated search time. ASCII queries, by far the common
+// case, are matched on the raw bytes instead: queries without letters go
+// straight to memchr's SIMD substring search, and the rest use memchr to find
+// the query's first byte in either case and compare only from those positions.
+// Other queries still lowercase.
+
+use crate::invisible;
+use crate::jobs::{Cancelled, Progress};
+use crate::schema::ColumnType;
+use crate::table::Table;
+use memchr::memchr2;
+use memchr::memmem::Finder;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// Rows tested between progress updates and cancellation checks
+const SEARCH_CHUNK_ROWS: usize = 64 * 1024;
+
+// A search owning what it needs to run on a worker thread
+pub struct SearchRequest {
+    pub data: Arc<Table>,
+    pub col: Option<usize>, // None searches every column
+    pub query: String,
+    pub exact: bool,  // Whole cell, case-sensitive
+    pub invert: bool, // Keep the rows that don't match instead
+    // When set, cells equal to any of these match and the query is ignored
+    pub values: Option<Arc<HashSet<String>>>,
+    // When set, non-empty cells this type rejects match and the query is ignored
+    pub invalid: Option<ColumnType>,
+    // When set, cells with invisible characters match and the query is ignored
+    pub invisible: bool,
+    // Rows to test in display order: the previous matches when refining, else
+    // the sort order. None tests every row in file order.
+    pub order: Option<Arc<Vec<usize>>>,
+}
+
+impl SearchRequest {
+    // Data indices of the matching rows, in display order
+    pub fn run(&self, progress: &Progress) -> Result<Vec<usize>, Cancelled> {
+        // A single exact column can use the table's dictionary lookup
+        if let (true, Some(col), None, None, false) = (self.exact, self.col, &self.values, self.invalid, self.invisible) {
+            let matches = self.data.rows_equal(col, &self.query);
+            if self.order.is_none() && !self.invert {
+                return Ok(matches);
+            }
+            let mut matched = vec![false; self.data.len()];
+            for idx in matches {
+                matched[idx] = true;
+            }
+            let keep = |idx: &usize| matched[*idx] != self.invert;
+            return Ok(match &self.order {
+                Some(order) => order.iter().copied().filter(keep).collect(),
+                None => (0..self.data.len()).filter(keep).collect(),
+            });
+        }
+
+        let matcher = Matcher::new(&self.query);
+        let cell_matches = |cell: &str| {
+            if self.invisible {
+                !invisible::kinds(cell).is_empty()
+            } else if let Some(column_type) = self.invalid {
+                !cell.trim().is_empty() && !column_type.accepts(cell)
+            } else if let Some(values) = &self.values {
+                values.contains(cell)
+            } else if self.exact {
+                cell == self.query
+            } else {
+                matcher.is_match(cell)
+            }
+        };
+        let total = self.order.as_ref().map_or(self.data.len(), |order| order.len());
+        progress.set_total(total);
+        let mut results = Vec::new();
+        for start in (0..total).step_by(SEARCH_CHUNK_ROWS) {
+            progress.check()?;
+            let end = (start + SEARCH_CHUNK_ROWS).min(total);
+            let candidates = (start..end).map(|pos| self.order.as_ref().map_or(pos, |order| order[pos]));
+            results.extend(candidates.filter(|&idx| {
+                let row = self.data.row(idx);
+                let matched = match self.col {
+                    Some(col) => row.get(col).is_some_and(cell_matches),
+                    None => row.iter().any(cell_matches),
+                };
+                matched != self.invert
+            }));
+            progress.set_done(end);
+        }
+        Ok(results)
+    }
+}
+
+pub struct Matcher {
+    query: String, // Lowercased
+    kind: MatchKind,
+}
+
+enum MatchKind {
+    Exact(Box<Finder<'static>>), // Nothing to fold, so bytes must match exactly
+    AsciiFold,
+    Lowercase,
+}
+
+impl Matcher {
+    pub fn new(query: &str) -> Self {
+        let query = query.to_lowercase();
+        let kind = if !query.is_ascii() {
+            MatchKind::Lowercase
+        } else if query.bytes().any(|byte| byte.is_ascii_alphabetic()) {
+            MatchKind::AsciiFold
+        } else {
+            MatchKind::Exact(Box::new(Finder::new(&query).into_owned()))
+        };
+        Matcher { query, kind }
+    }
+
+    pub fn is_match(&self, cell: &str) -> bool {
+        match &self.kind {
+            MatchKind::Exact(finder) => finder.find(cell.as_bytes()).is_some(),
+            MatchKind::AsciiFold => contains_ignore_ascii_case(cell.as_bytes(), self.query.as_bytes()),
+            MatchKind::Lowercase => cell.to_lowercase().contains(&self.query),
+        }
+    }
+}
+
+// `needle` must be non-empty and lowercase
+fn contains_ignore_ascii_case(haystack: &[u8], needle: &[u8]) -> bool {
+    let first = needle[0];
+    let mut start = 0;
+    while let Some(pos) = memchr2(first, first.to_ascii_uppercase(), &haystack[start..]) {
+        let at = start + pos;
+        let Some(candidate) = haystack.get(at..at + needle.len()) else {
+            return false;
+        };
+        if candidate.eq_ignore_ascii_case(needle) {
+            return true;
+        }
+        start = at + 1;
+    }
+    false
+}
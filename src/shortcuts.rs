@@ -0,0 +1,298 @@
+// Keyboard shortcuts for app actions.
+//
+// Every action has a default binding that can be changed in the Keyboard
+// Shortcuts window. Changed bindings are saved to the config as
+// `shortcut.<action>=<keys>`, e.g. `shortcut.open_file=Ctrl+O`, where Ctrl
+// stands for Cmd on macOS.
+
+use crate::config::Config;
+use crate::i18n::tr;
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    OpenFile,
+    SaveFile,
+    ExportView,
+    ExportSelection,
+    FocusSearch,
+    ClearSearch,
+    NextPage,
+    PreviousPage,
+    ToggleColumnControls,
+    ToggleSummaryFooter,
+    TogglePerfOverlay,
+    CommandPalette,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::OpenFile,
+        Action::SaveFile,
+        Action::ExportView,
+        Action::ExportSelection,
+        Action::FocusSearch,
+        Action::ClearSearch,
+        Action::NextPage,
+        Action::PreviousPage,
+        Action::ToggleColumnControls,
+        Action::ToggleSummaryFooter,
+        Action::TogglePerfOverlay,
+        Action::CommandPalette,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetZoom,
+    ];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Action::OpenFile => "Open file",
+            Action::SaveFile => "Save file",
+            Action::ExportView => "Export current view",
+            Action::ExportSelection => "Export selected rows",
+            Action::FocusSearch => "Focus search",
+            Action::ClearSearch => "Clear search",
+            Action::NextPage => "Next page",
+            Action::PreviousPage => "Previous page",
+            Action::ToggleColumnControls => "Toggle column controls",
+            Action::ToggleSummaryFooter => "Toggle summary footer",
+            Action::TogglePerfOverlay => "Toggle performance overlay",
+            Action::CommandPalette => "Command palette",
+            Action::ZoomIn => "Zoom in",
+            Action::ZoomOut => "Zoom out",
+            Action::ResetZoom => "Reset zoom",
+        })
+    }
+
+    fn config_key(self) -> &'static str {
+        match self {
+            Action::OpenFile => "shortcut.open_file",
+            Action::SaveFile => "shortcut.save_file",
+            Action::ExportView => "shortcut.export_view",
+            Action::ExportSelection => "shortcut.export_selection",
+            Action::FocusSearch => "shortcut.focus_search",
+            Action::ClearSearch => "shortcut.clear_search",
+            Action::NextPage => "shortcut.next_page",
+            Action::PreviousPage => "shortcut.previous_page",
+            Action::ToggleColumnControls => "shortcut.toggle_column_controls",
+            Action::ToggleSummaryFooter => "shortcut.toggle_summary_footer",
+            Action::TogglePerfOverlay => "shortcut.toggle_perf_overlay",
+            Action::CommandPalette => "shortcut.command_palette",
+            Action::ZoomIn => "shortcut.zoom_in",
+            Action::ZoomOut => "shortcut.zoom_out",
+            Action::ResetZoom => "shortcut.reset_zoom",
+        }
+    }
+
+    fn default_shortcut(self) -> KeyboardShortcut {
+        let (command, alt, shift, key) = match self {
+            Action::OpenFile => (true, false, false, Key::O),
+            Action::SaveFile => (true, false, false, Key::S),
+            Action::ExportView => (true, false, false, Key::E),
+            Action::ExportSelection => (true, false, true, Key::E),
+            Action::FocusSearch => (true, false, false, Key::F),
+            Action::ClearSearch => (true, false, true, Key::F),
+            Action::NextPage => (false, true, false, Key::ArrowRight),
+            Action::PreviousPage => (false, true, false, Key::ArrowLeft),
+            Action::ToggleColumnControls => (true, false, true, Key::C),
+            Action::ToggleSummaryFooter => (true, false, true, Key::S),
+            Action::TogglePerfOverlay => (false, false, false, Key::F12),
+            Action::CommandPalette => (true, false, true, Key::P),
+            Action::ZoomIn => (true, false, false, Key::PlusEquals),
+            Action::ZoomOut => (true, false, false, Key::Minus),
+            Action::ResetZoom => (true, false, false, Key::Num0),
+        };
+        KeyboardShortcut::new(modifiers(command, alt, shift), key)
+    }
+}
+
+pub struct Shortcuts {
+    bindings: Vec<(Action, KeyboardShortcut)>,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Shortcuts {
+            bindings: Action::ALL.iter().map(|&action| (action, action.default_shortcut())).collect(),
+        }
+    }
+}
+
+impl Shortcuts {
+    // Defaults, overridden by any valid bindings in the config
+    pub fn from_config(config: &Config) -> Self {
+        let mut shortcuts = Shortcuts::default();
+        for (action, shortcut) in &mut shortcuts.bindings {
+            if let Some(saved) = config.get(action.config_key()).and_then(parse_shortcut) {
+                *shortcut = saved;
+            }
+        }
+        shortcuts
+    }
+
+    pub fn save_to(&self, config: &mut Config) {
+        for (action, shortcut) in &self.bindings {
+            config.set(action.config_key(), format_shortcut(shortcut));
+        }
+    }
+
+    pub fn get(&self, action: Action) -> KeyboardShortcut {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(action.default_shortcut(), |(_, shortcut)| *shortcut)
+    }
+
+    pub fn set(&mut self, action: Action, shortcut: KeyboardShortcut) {
+        for (bound, existing) in &mut self.bindings {
+            if *bound == action {
+                *existing = shortcut;
+            }
+        }
+    }
+
+    // Actions whose shortcut was pressed this frame. While a text field has
+    // focus, shortcuts without Ctrl or Alt are left to the text field.
+    pub fn triggered(&self, ctx: &egui::Context) -> Vec<Action> {
+        let typing = ctx.wants_keyboard_input();
+        self.bindings
+            .iter()
+            .filter(|(_, shortcut)| !typing || shortcut.modifiers.command || shortcut.modifiers.alt)
+            .filter(|(_, shortcut)| ctx.input_mut(|i| i.consume_shortcut(shortcut)))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+}
+
+// The first key pressed this frame together with its modifiers, for
+// recording a new binding
+pub fn pressed_shortcut(ctx: &egui::Context) -> Option<KeyboardShortcut> {
+    ctx.input(|i| {
+        i.events.iter().find_map(|event| match event {
+            egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers: pressed,
+                ..
+            } if KEY_NAMES.iter().any(|(named, _)| named == key) => Some(KeyboardShortcut::new(
+                modifiers(pressed.command, pressed.alt, pressed.shift),
+                *key,
+            )),
+            _ => None,
+        })
+    })
+}
+
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let mut text = String::new();
+    if shortcut.modifiers.command {
+        text.push_str("Ctrl+");
+    }
+    if shortcut.modifiers.alt {
+        text.push_str("Alt+");
+    }
+    if shortcut.modifiers.shift {
+        text.push_str("Shift+");
+    }
+    let name = KEY_NAMES.iter().find(|(key, _)| *key == shortcut.key).map_or("?", |(_, name)| name);
+    text.push_str(name);
+    text
+}
+
+fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let name = parts.pop()?;
+    let key = KEY_NAMES.iter().find(|(_, known)| known.eq_ignore_ascii_case(name))?.0;
+    let (mut command, mut alt, mut shift) = (false, false, false);
+    for part in parts {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "cmd" => command = true,
+            "alt" => alt = true,
+            "shift" => shift = true,
+            _ => return None,
+        }
+    }
+    Some(KeyboardShortcut::new(modifiers(command, alt, shift), key))
+}
+
+// `command` is Ctrl, or Cmd on macOS
+fn modifiers(command: bool, alt: bool, shift: bool) -> Modifiers {
+    Modifiers {
+        alt,
+        ctrl: false,
+        shift,
+        mac_cmd: false,
+        command,
+    }
+}
+
+// Keys that can be bound, with the names used in the config
+const KEY_NAMES: [(Key, &str); 64] = [
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::Num0, "0"),
+    (Key::Num1, "1"),
+    (Key::Num2, "2"),
+    (Key::Num3, "3"),
+    (Key::Num4, "4"),
+    (Key::Num5, "5"),
+    (Key::Num6, "6"),
+    (Key::Num7, "7"),
+    (Key::Num8, "8"),
+    (Key::Num9, "9"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+    (Key::ArrowUp, "Up"),
+    (Key::ArrowDown, "Down"),
+    (Key::ArrowLeft, "Left"),
+    (Key::ArrowRight, "Right"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::Enter, "Enter"),
+    (Key::Tab, "Tab"),
+    (Key::Space, "Space"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::Backspace, "Backspace"),
+    (Key::PlusEquals, "Plus"),
+    (Key::Minus, "Minus"),
+];
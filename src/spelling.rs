@@ -0,0 +1,116 @@
+// Spell-checking text columns against a word list.
+//
+// The dictionary is a text file with one word per line, like
+// /usr/share/dict/words, or a Hunspell .dic file, whose affix flags after a
+// '/' are dropped. Without the affix rules a .dic file only knows the base
+// forms, so inflected words like "colours" are flagged unless listed.
+// Matching ignores case. Numbers, words with digits, words in capitals
+// (usually acronyms), single letters, and anything in an email address or
+// web link are skipped.
+
+use crate::jobs::{Cancelled, Progress};
+use crate::table::Table;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+// Rows scanned between progress updates and cancellation checks
+const SCAN_CHUNK_ROWS: usize = 16 * 1024;
+
+// Suspect cells listed; the word counts cover them all
+const MAX_LISTED_CELLS: usize = 10_000;
+
+pub struct Dictionary {
+    words: HashSet<String>, // Lowercase
+}
+
+impl Dictionary {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let bytes = std::fs::read(path)?;
+        let text = String::from_utf8_lossy(&bytes);
+        let words: HashSet<String> = text
+            .lines()
+            .map(|line| line.split('/').next().unwrap_or("").trim())
+            // A .dic file starts with its word count
+            .filter(|word| !word.is_empty() && !word.bytes().all(|byte| byte.is_ascii_digit()))
+            .map(str::to_lowercase)
+            .collect();
+        if words.is_empty() {
+            return Err("the file has no words".into());
+        }
+        Ok(Dictionary { words })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        // Possessives, as in "customer's"
+        self.words.contains(&lower)
+            || lower.strip_suffix("'s").or_else(|| lower.strip_suffix("’s")).is_some_and(|stem| self.words.contains(stem))
+    }
+}
+
+// The words of `cell` worth checking
+fn words(cell: &str) -> impl Iterator<Item = &str> {
+    cell.split_whitespace()
+        .filter(|token| !token.contains('@') && !token.contains("://") && !token.starts_with("www."))
+        .flat_map(|token| token.split(|ch: char| !ch.is_alphanumeric() && ch != '\'' && ch != '’'))
+        .map(|word| word.trim_matches(['\'', '’']))
+        .filter(|word| {
+            word.chars().count() > 1
+                && !word.chars().any(|ch| ch.is_numeric())
+                && !word.chars().all(|ch| ch.is_uppercase() || !ch.is_alphabetic())
+        })
+}
+
+pub struct SpellReport {
+    pub cells: Vec<(usize, usize, Vec<String>)>, // Data index, column and unknown words of suspect cells, in row order
+    pub suspect_cells: usize,                    // Listed or not
+    pub words: Vec<(String, usize)>,             // Unknown words with their counts, most common first
+}
+
+// `ignored` holds lowercase words to accept as well as the dictionary's
+pub fn check(
+    data: &Table,
+    columns: &[usize],
+    dictionary: &Dictionary,
+    ignored: &HashSet<String>,
+    progress: &Progress,
+) -> Result<SpellReport, Cancelled> {
+    let mut report = SpellReport {
+        cells: Vec::new(),
+        suspect_cells: 0,
+        words: Vec::new(),
+    };
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    progress.set_total(data.len());
+    for start in (0..data.len()).step_by(SCAN_CHUNK_ROWS) {
+        progress.check()?;
+        let end = (start + SCAN_CHUNK_ROWS).min(data.len());
+        for idx in start..end {
+            let row = data.row(idx);
+            for &col in columns {
+                let unknown: Vec<String> = words(row.get(col).unwrap_or(""))
+                    .filter(|word| !dictionary.knows(word) && !ignored.contains(&word.to_lowercase()))
+                    .map(str::to_string)
+                    .collect();
+                if unknown.is_empty() {
+                    continue;
+                }
+                for word in &unknown {
+                    *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+                }
+                report.suspect_cells += 1;
+                if report.cells.len() < MAX_LISTED_CELLS {
+                    report.cells.push((idx, col, unknown));
+                }
+            }
+        }
+        progress.set_done(end);
+    }
+    report.words = counts.into_iter().collect();
+    report.words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(report)
+}
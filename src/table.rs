@@ -0,0 +1,294 @@
+// Compact storage for CSV cell data.
+//
+// Every cell's text lives in one shared buffer; a row is a run of spans into
+// that buffer. Compared to `Vec<Vec<String>>` this needs one allocation per
+// table instead of one per cell and drops the per-string and per-row headers.
+//
+// Columns with only a handful of distinct values (status, country, ...) can
+// additionally be dictionary-encoded: each value is stored once and every row
+// carries an integer code, so equality filters compare integers, not text.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+// Columns with at most this many distinct values are dictionary-encoded
+const MAX_DICTIONARY_SIZE: usize = 1024;
+
+// Code recorded for rows too short to have a cell in a dictionary column
+const MISSING_CODE: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Dictionary {
+    lookup: HashMap<String, u32>,
+    spans: Vec<Span>, // Text of each code
+    codes: Vec<u32>,  // Code of each row
+}
+
+impl Dictionary {
+    // Record `text` for the next row, storing it in the buffer on first use
+    fn encode(&mut self, buffer: &mut String, text: &str) -> Span {
+        let code = match self.lookup.get(text) {
+            Some(&code) => code,
+            None => {
+                let code = self.spans.len() as u32;
+                self.spans.push(push_text(buffer, text));
+                self.lookup.insert(text.to_string(), code);
+                code
+            }
+        };
+        self.codes.push(code);
+        self.spans[code as usize]
+    }
+
+    fn memory_usage(&self) -> usize {
+        let lookup: usize = self.lookup.keys().map(|value| value.capacity()).sum::<usize>()
+            + self.lookup.capacity() * size_of::<(String, u32)>();
+        lookup + self.spans.capacity() * size_of::<Span>() + self.codes.capacity() * size_of::<u32>()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Table {
+    buffer: String,
+    spans: Vec<Span>,
+    // Index of each row's first span, plus one past the last row
+    row_starts: Vec<usize>,
+    // Per column; empty until `intern_low_cardinality_columns` runs
+    dictionaries: Vec<Option<Dictionary>>,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table {
+            buffer: String::new(),
+            spans: Vec::new(),
+            row_starts: vec![0],
+            dictionaries: Vec::new(),
+        }
+    }
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.row_starts.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Approximate heap memory held by the table, in bytes
+    pub fn memory_usage(&self) -> usize {
+        let dictionaries: usize = self.dictionaries.iter().flatten().map(Dictionary::memory_usage).sum();
+        self.buffer.capacity()
+            + self.spans.capacity() * size_of::<Span>()
+            + self.row_starts.capacity() * size_of::<usize>()
+            + dictionaries
+    }
+
+    // Append a row, copying its cells into the shared buffer
+    pub fn push_row<I, S>(&mut self, cells: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut width = 0;
+        for cell in cells {
+            let text = cell.as_ref();
+            let span = match self.dictionaries.get_mut(width).and_then(Option::as_mut) {
+                Some(dictionary) => dictionary.encode(&mut self.buffer, text),
+                None => push_text(&mut self.buffer, text),
+            };
+            self.spans.push(span);
+            width += 1;
+        }
+        for dictionary in self.dictionaries.iter_mut().skip(width).flatten() {
+            dictionary.codes.push(MISSING_CODE);
+        }
+        self.row_starts.push(self.spans.len());
+    }
+
+    // Move the rows of `other` onto the end of this table
+    pub fn append(&mut self, other: Table) {
+        if !self.dictionaries.is_empty() {
+            for row in other.rows() {
+                self.push_row(row.iter());
+            }
+            return;
+        }
+        let offset = self.buffer.len();
+        let span_offset = self.spans.len();
+        self.buffer.push_str(&other.buffer);
+        self.spans.extend(other.spans.iter().map(|span| Span {
+            start: span.start + offset,
+            end: span.end + offset,
+        }));
+        self.row_starts
+            .extend(other.row_starts[1..].iter().map(|start| start + span_offset));
+    }
+
+    pub fn row(&self, index: usize) -> Row<'_> {
+        let spans = &self.spans[self.row_starts[index]..self.row_starts[index + 1]];
+        Row {
+            buffer: &self.buffer,
+            spans,
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        (0..self.len()).map(move |index| self.row(index))
+    }
+
+    // Dictionary-encode the columns with few distinct values, rebuilding the
+    // buffer so each of their values is stored only once
+    pub fn intern_low_cardinality_columns(&mut self) {
+        let columns = self.rows().map(|row| row.len()).max().unwrap_or(0);
+        let encode: Vec<bool> = (0..columns).map(|col| self.is_low_cardinality(col)).collect();
+        if !encode.contains(&true) {
+            return;
+        }
+
+        let old = std::mem::take(self);
+        self.buffer.reserve(old.buffer.len());
+        self.spans.reserve(old.spans.len());
+        self.row_starts.reserve(old.len());
+        self.dictionaries = encode
+            .iter()
+            .map(|&encode| encode.then(Dictionary::default))
+            .collect();
+        for row in old.rows() {
+            self.push_row(row.iter());
+        }
+    }
+
+    fn is_low_cardinality(&self, col: usize) -> bool {
+        // Only worth it when values repeat on average at least twice
+        let limit = MAX_DICTIONARY_SIZE.min(self.len() / 2);
+        let mut seen = HashSet::new();
+        for row in self.rows() {
+            if let Some(cell) = row.get(col)
+                && seen.insert(cell)
+                && seen.len() > limit
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Indices of the rows whose cell in `col` is exactly `value`. Dictionary
+    // columns compare codes instead of text.
+    pub fn rows_equal(&self, col: usize, value: &str) -> Vec<usize> {
+        if let Some(Some(dictionary)) = self.dictionaries.get(col) {
+            let Some(&code) = dictionary.lookup.get(value) else {
+                return Vec::new();
+            };
+            return dictionary
+                .codes
+                .iter()
+                .enumerate()
+                .filter(|(_, row_code)| **row_code == code)
+                .map(|(idx, _)| idx)
+                .collect();
+        }
+        self.rows()
+            .enumerate()
+            .filter(|(_, row)| row.get(col) == Some(value))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    // Row indices ordered by their cells in `col`, leaving the rows themselves
+    // in place. Equal cells keep their file order.
+    pub fn sorted_order(&self, col: usize, descending: bool) -> Vec<usize> {
+        let keys: Vec<SortKey<'_>> = self.rows().map(|row| SortKey::new(row.get(col))).collect();
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|&a, &b| {
+            let ordering = keys[a].compare(&keys[b]);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        order
+    }
+}
+
+// A cell parsed once up front for sorting. Numbers compare numerically and
+// come before text; rows too short to have the cell come last.
+enum SortKey<'a> {
+    Number(f64),
+    Text(&'a str),
+    Missing,
+}
+
+impl<'a> SortKey<'a> {
+    fn new(cell: Option<&'a str>) -> Self {
+        match cell {
+            Some(cell) => match cell.trim().parse::<f64>() {
+                Ok(number) => SortKey::Number(number),
+                Err(_) => SortKey::Text(cell),
+            },
+            None => SortKey::Missing,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SortKey::Number(_) => 0,
+            SortKey::Text(_) => 1,
+            SortKey::Missing => 2,
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (SortKey::Number(a), SortKey::Number(b)) => a.total_cmp(b),
+            (SortKey::Text(a), SortKey::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+fn push_text(buffer: &mut String, text: &str) -> Span {
+    let start = buffer.len();
+    buffer.push_str(text);
+    Span {
+        start,
+        end: buffer.len(),
+    }
+}
+
+// A borrowed view of one table row
+#[derive(Clone, Copy, Debug)]
+pub struct Row<'a> {
+    buffer: &'a str,
+    spans: &'a [Span],
+}
+
+impl<'a> Row<'a> {
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn get(&self, col: usize) -> Option<&'a str> {
+        let buffer = self.buffer;
+        self.spans.get(col).map(|span| &buffer[span.start..span.end])
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'a str> + ExactSizeIterator + 'a {
+        let buffer = self.buffer;
+        self.spans.iter().map(move |span| &buffer[span.start..span.end])
+    }
+}
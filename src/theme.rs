@@ -0,0 +1,199 @@
+// Light/dark theme, accent colors and row density.
+//
+// Saved in the config as `theme=system|dark|light|high_contrast` plus optional
+// `theme.accent` and `theme.warning` colors written as `#rrggbb`. Colors left
+// unset keep the theme's own. Density is `density=compact|comfortable`.
+
+use crate::config::Config;
+use crate::i18n::tr;
+use eframe::egui::style::Spacing;
+use eframe::egui::{Color32, Stroke, Visuals, vec2};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ThemeMode {
+    System,
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 4] = [ThemeMode::System, ThemeMode::Dark, ThemeMode::Light, ThemeMode::HighContrast];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            ThemeMode::System => "Follow system",
+            ThemeMode::Dark => "Dark",
+            ThemeMode::Light => "Light",
+            ThemeMode::HighContrast => "High contrast",
+        })
+    }
+
+    fn config_value(self) -> &'static str {
+        match self {
+            ThemeMode::System => "system",
+            ThemeMode::Dark => "dark",
+            ThemeMode::Light => "light",
+            ThemeMode::HighContrast => "high_contrast",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+}
+
+impl Density {
+    pub const ALL: [Density; 2] = [Density::Compact, Density::Comfortable];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Density::Compact => "Compact",
+            Density::Comfortable => "Comfortable",
+        })
+    }
+
+    fn config_value(self) -> &'static str {
+        match self {
+            Density::Compact => "compact",
+            Density::Comfortable => "comfortable",
+        }
+    }
+
+    // Rows are never shorter than this, however little text they hold
+    pub fn min_row_height(self) -> f32 {
+        match self {
+            Density::Compact => 0.0,
+            Density::Comfortable => 20.0,
+        }
+    }
+
+    // Gap between table rows, given the style's usual spacing
+    pub fn row_spacing(self, default: f32) -> f32 {
+        match self {
+            Density::Compact => 0.0,
+            Density::Comfortable => default,
+        }
+    }
+}
+
+pub struct Theme {
+    pub mode: ThemeMode,
+    pub accent: Option<Color32>,  // Selection, focused cell and links
+    pub warning: Option<Color32>, // Warnings such as unsaved changes
+    pub density: Density,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            mode: ThemeMode::System,
+            accent: None,
+            warning: None,
+            density: Density::default(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_config(config: &Config) -> Self {
+        let mode = config
+            .get("theme")
+            .and_then(|value| ThemeMode::ALL.into_iter().find(|mode| mode.config_value() == value))
+            .unwrap_or(ThemeMode::System);
+        let density = config
+            .get("density")
+            .and_then(|value| Density::ALL.into_iter().find(|density| density.config_value() == value))
+            .unwrap_or_default();
+        Theme {
+            mode,
+            accent: config.get("theme.accent").and_then(parse_color),
+            warning: config.get("theme.warning").and_then(parse_color),
+            density,
+        }
+    }
+
+    pub fn save_to(&self, config: &mut Config) {
+        config.set("theme", self.mode.config_value());
+        config.set("theme.accent", self.accent.map(format_color).unwrap_or_default());
+        config.set("theme.warning", self.warning.map(format_color).unwrap_or_default());
+        config.set("density", self.density.config_value());
+    }
+
+    // `system_dark` is None when the platform doesn't report its theme, in
+    // which case following the system means dark
+    pub fn visuals(&self, system_dark: Option<bool>) -> Visuals {
+        let mut visuals = match self.mode {
+            ThemeMode::System if system_dark == Some(false) => Visuals::light(),
+            ThemeMode::System | ThemeMode::Dark => Visuals::dark(),
+            ThemeMode::Light => Visuals::light(),
+            ThemeMode::HighContrast => high_contrast_visuals(),
+        };
+        if let Some(accent) = self.accent {
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+        }
+        if let Some(warning) = self.warning {
+            visuals.warn_fg_color = warning;
+        }
+        visuals
+    }
+
+    // High contrast also makes controls bigger, so they are easier to hit
+    pub fn spacing(&self) -> Spacing {
+        let mut spacing = Spacing::default();
+        if self.mode == ThemeMode::HighContrast {
+            spacing.item_spacing = vec2(10.0, 6.0);
+            spacing.button_padding = vec2(8.0, 4.0);
+            spacing.interact_size.y = 28.0;
+            spacing.icon_width = 20.0;
+        }
+        spacing
+    }
+}
+
+// White on black with solid white outlines around every control. Striped
+// rows use the background color, so the only shading left is the selection.
+fn high_contrast_visuals() -> Visuals {
+    let mut visuals = Visuals::dark();
+    let outline = Stroke::new(2.0, Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::BLACK;
+    visuals.code_bg_color = Color32::BLACK;
+    visuals.window_stroke = outline;
+    let widgets = &mut visuals.widgets;
+    for widget in [&mut widgets.inactive, &mut widgets.hovered, &mut widgets.active, &mut widgets.open] {
+        widget.bg_fill = Color32::BLACK;
+        widget.weak_bg_fill = Color32::BLACK;
+        widget.bg_stroke = outline;
+        widget.fg_stroke = outline;
+    }
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+    widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+    widgets.hovered.bg_stroke = Stroke::new(3.0, Color32::YELLOW);
+    widgets.active.bg_stroke = Stroke::new(3.0, Color32::YELLOW);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 60, 180);
+    visuals.selection.stroke = outline;
+    visuals.hyperlink_color = Color32::from_rgb(0, 220, 255);
+    visuals.warn_fg_color = Color32::YELLOW;
+    visuals.error_fg_color = Color32::from_rgb(255, 110, 110);
+    visuals
+}
+
+fn format_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn parse_color(text: &str) -> Option<Color32> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+    Some(Color32::from_rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
@@ -0,0 +1,124 @@
+// Unit conversions for a column of numbers. A cell may carry the unit it is
+// converted from after the number, as in "12 km" or "98.6°F".
+
+use crate::decimals;
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conversion {
+    KmToMi,
+    MiToKm,
+    KgToLb,
+    LbToKg,
+    CelsiusToFahrenheit,
+    FahrenheitToCelsius,
+    BytesToMb,
+    MbToBytes,
+    Factor, // Multiply by a factor given in the window
+}
+
+const KM_PER_MI: f64 = 1.609_344;
+const KG_PER_LB: f64 = 0.453_592_37;
+const BYTES_PER_MB: f64 = 1_000_000.0;
+
+impl Conversion {
+    pub const ALL: [Conversion; 9] = [
+        Conversion::KmToMi,
+        Conversion::MiToKm,
+        Conversion::KgToLb,
+        Conversion::LbToKg,
+        Conversion::CelsiusToFahrenheit,
+        Conversion::FahrenheitToCelsius,
+        Conversion::BytesToMb,
+        Conversion::MbToBytes,
+        Conversion::Factor,
+    ];
+
+    pub fn label(self) -> &'static str {
+        tr(match self {
+            Conversion::KmToMi => "Kilometres to miles",
+            Conversion::MiToKm => "Miles to kilometres",
+            Conversion::KgToLb => "Kilograms to pounds",
+            Conversion::LbToKg => "Pounds to kilograms",
+            Conversion::CelsiusToFahrenheit => "°C to °F",
+            Conversion::FahrenheitToCelsius => "°F to °C",
+            Conversion::BytesToMb => "Bytes to MB (1,000,000 bytes)",
+            Conversion::MbToBytes => "MB to bytes",
+            Conversion::Factor => "Multiply by a factor",
+        })
+    }
+
+    // For the change log
+    pub fn description(self) -> &'static str {
+        match self {
+            Conversion::KmToMi => "km to mi",
+            Conversion::MiToKm => "mi to km",
+            Conversion::KgToLb => "kg to lb",
+            Conversion::LbToKg => "lb to kg",
+            Conversion::CelsiusToFahrenheit => "°C to °F",
+            Conversion::FahrenheitToCelsius => "°F to °C",
+            Conversion::BytesToMb => "bytes to MB",
+            Conversion::MbToBytes => "MB to bytes",
+            Conversion::Factor => "multiplied by a factor",
+        }
+    }
+
+    // Units a cell may end with, in lowercase
+    fn source_units(self) -> &'static [&'static str] {
+        match self {
+            Conversion::KmToMi => &["km"],
+            Conversion::MiToKm => &["mi"],
+            Conversion::KgToLb => &["kg"],
+            Conversion::LbToKg => &["lbs", "lb"],
+            Conversion::CelsiusToFahrenheit => &["°c", "c"],
+            Conversion::FahrenheitToCelsius => &["°f", "f"],
+            Conversion::BytesToMb => &["bytes", "b"],
+            Conversion::MbToBytes => &["mb"],
+            Conversion::Factor => &[],
+        }
+    }
+
+    // Ending for the name of a new column
+    pub fn target_unit(self) -> &'static str {
+        match self {
+            Conversion::KmToMi => "mi",
+            Conversion::MiToKm => "km",
+            Conversion::KgToLb => "lb",
+            Conversion::LbToKg => "kg",
+            Conversion::CelsiusToFahrenheit => "f",
+            Conversion::FahrenheitToCelsius => "c",
+            Conversion::BytesToMb => "mb",
+            Conversion::MbToBytes => "bytes",
+            Conversion::Factor => "converted",
+        }
+    }
+
+    fn apply(self, value: f64, factor: f64) -> f64 {
+        match self {
+            Conversion::KmToMi => value / KM_PER_MI,
+            Conversion::MiToKm => value * KM_PER_MI,
+            Conversion::KgToLb => value / KG_PER_LB,
+            Conversion::LbToKg => value * KG_PER_LB,
+            Conversion::CelsiusToFahrenheit => value * 9.0 / 5.0 + 32.0,
+            Conversion::FahrenheitToCelsius => (value - 32.0) * 5.0 / 9.0,
+            Conversion::BytesToMb => value / BYTES_PER_MB,
+            Conversion::MbToBytes => value * BYTES_PER_MB,
+            Conversion::Factor => value * factor,
+        }
+    }
+
+    // `cell` converted and rounded to `decimals` places, or None if it isn't
+    // a number
+    pub fn convert(self, cell: &str, factor: f64, decimals: usize) -> Option<String> {
+        let cell = cell.trim();
+        let lower = cell.to_ascii_lowercase();
+        let number = self
+            .source_units()
+            .iter()
+            .find_map(|unit| lower.strip_suffix(unit))
+            .map_or(cell, |rest| &cell[..rest.len()])
+            .trim_end();
+        let value = number.parse::<f64>().ok().filter(|value| value.is_finite())?;
+        Some(decimals::format(self.apply(value, factor), decimals))
+    }
+}
@@ -0,0 +1,68 @@
+// How a file was last arranged: column widths, hidden columns, sort and page.
+//
+// Kept in the config under `view.<path hash>.<field>`, so reopening a file
+// puts the table back the way it was left. Widths and visibility are only
+// restored while the file still has the same number of columns.
+
+use crate::config::Config;
+
+pub struct ViewState {
+    pub widths: Vec<f32>,
+    pub visible: Vec<bool>,
+    pub sort: Option<(usize, bool)>, // Sorted column and whether it is descending
+    pub page: usize,
+}
+
+impl ViewState {
+    pub fn load(config: &Config, path: &str) -> Option<Self> {
+        let columns: usize = config.get(&key(path, "columns"))?.parse().ok()?;
+        let widths: Vec<f32> = config
+            .get(&key(path, "widths"))?
+            .split(',')
+            .map(|width| width.parse().ok())
+            .collect::<Option<_>>()?;
+        if widths.len() != columns {
+            return None;
+        }
+        let mut visible = vec![true; columns];
+        for col in config.get(&key(path, "hidden")).unwrap_or("").split(',').filter(|col| !col.is_empty()) {
+            *visible.get_mut(col.parse::<usize>().ok()?)? = false;
+        }
+        let sort = config.get(&key(path, "sort")).and_then(|sort| {
+            let (col, direction) = sort.split_once(':')?;
+            Some((col.parse().ok()?, direction == "desc"))
+        });
+        let page = config.get(&key(path, "page")).and_then(|page| page.parse().ok()).unwrap_or(0);
+        Some(ViewState {
+            widths,
+            visible,
+            sort,
+            page,
+        })
+    }
+
+    pub fn save_to(&self, config: &mut Config, path: &str) {
+        let join = |values: Vec<String>| values.join(",");
+        config.set(&key(path, "columns"), self.widths.len().to_string());
+        config.set(&key(path, "widths"), join(self.widths.iter().map(|width| width.round().to_string()).collect()));
+        let hidden = self.visible.iter().enumerate().filter(|(_, visible)| !**visible);
+        config.set(&key(path, "hidden"), join(hidden.map(|(col, _)| col.to_string()).collect()));
+        let sort = match self.sort {
+            Some((col, descending)) => format!("{}:{}", col, if descending { "desc" } else { "asc" }),
+            None => String::new(),
+        };
+        config.set(&key(path, "sort"), sort);
+        config.set(&key(path, "page"), self.page.to_string());
+    }
+}
+
+fn key(path: &str, field: &str) -> String {
+    format!("view.{:016x}.{}", path_hash(path), field)
+}
+
+// FNV-1a, which unlike std's hasher is guaranteed to give the same value in
+// every build, so saved views survive an upgrade
+fn path_hash(path: &str) -> u64 {
+    path.bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}